@@ -0,0 +1,235 @@
+//!
+//! A dynamically sized set of bits, to help maintain things like coverage maps
+//! and feature masks.
+//!
+//! HanishKVC, 2022
+//!
+
+use core::fmt::Display;
+use core::ops::{BitAnd, BitOr, BitXor, Not};
+
+use crate::error::DataUtilsError;
+#[allow(unused_imports)]
+use crate::alloc_prelude::*;
+
+
+///
+/// A vector of bits, stored 64 bits per word, with bit i being bit i%64 of word i/64.
+/// The bits beyond len in the last word are always kept cleared.
+///
+/// Wrt the byte buffer conversions, bit i is bit i%8 (lsb first) of byte i/8.
+/// Wrt the bit string conversions, the i'th char ('0' or '1') is bit i.
+///
+/// Binary operations btw bitsets of different lengths, give a result of the longer length,
+/// with the missing bits of the shorter one treated has 0.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVec {
+
+    /// A bitset with len bits, all cleared
+    pub fn new(len: usize) -> BitVec {
+        BitVec { words: vec![0; len.div_ceil(64)], len }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clear the unused bits in the last word, after a operation which could have set them
+    fn trim(&mut self) {
+        let rem = self.len % 64;
+        if rem != 0 {
+            if let Some(last) = self.words.last_mut() {
+                *last &= (1u64 << rem) - 1;
+            }
+        }
+    }
+
+    /// Change the length, any newly added bits are cleared
+    pub fn resize(&mut self, len: usize) {
+        self.words.resize(len.div_ceil(64), 0);
+        self.len = len;
+        self.trim();
+    }
+
+    pub fn push(&mut self, val: bool) {
+        self.resize(self.len + 1);
+        if val {
+            self.words[(self.len-1)/64] |= 1 << ((self.len-1) % 64);
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+        Some((self.words[index/64] >> (index % 64)) & 1 == 1)
+    }
+
+    fn check_index(&self, index: usize) -> Result<(), DataUtilsError> {
+        if index >= self.len {
+            return Err(DataUtilsError::IndexOutOfBounds { index, len: self.len });
+        }
+        Ok(())
+    }
+
+    pub fn set(&mut self, index: usize, val: bool) -> Result<(), DataUtilsError> {
+        self.check_index(index)?;
+        let mask = 1u64 << (index % 64);
+        if val {
+            self.words[index/64] |= mask;
+        } else {
+            self.words[index/64] &= !mask;
+        }
+        Ok(())
+    }
+
+    /// Toggle the given bit, returning its new value
+    pub fn flip(&mut self, index: usize) -> Result<bool, DataUtilsError> {
+        self.check_index(index)?;
+        self.words[index/64] ^= 1u64 << (index % 64);
+        Ok(self.get(index) == Some(true))
+    }
+
+    /// Set or clear all the bits
+    pub fn fill(&mut self, val: bool) {
+        let wval = if val { u64::MAX } else { 0 };
+        self.words.iter_mut().for_each(|w| *w = wval);
+        self.trim();
+    }
+
+    /// The number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// The number of cleared bits
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// The number of set bits before the given index, ie in 0..index.
+    /// A index beyond len is treated has len.
+    pub fn rank(&self, index: usize) -> usize {
+        let index = index.min(self.len);
+        let full: usize = self.words[..index/64].iter().map(|w| w.count_ones() as usize).sum();
+        let rem = index % 64;
+        if rem == 0 {
+            return full;
+        }
+        full + (self.words[index/64] & ((1u64 << rem) - 1)).count_ones() as usize
+    }
+
+    /// Iterate over the indexes of the set bits, in increasing order
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(wi, w)| {
+            let mut w = *w;
+            core::iter::from_fn(move || {
+                if w == 0 {
+                    return None;
+                }
+                let bi = w.trailing_zeros() as usize;
+                w &= w - 1;
+                Some(wi*64 + bi)
+            })
+        })
+    }
+
+    /// A bitset of len bytes.len()*8, refer to the struct doc wrt the bit order
+    pub fn from_bytes(bytes: &[u8]) -> BitVec {
+        let mut bv = BitVec::new(bytes.len()*8);
+        for (i, b) in bytes.iter().enumerate() {
+            bv.words[i/8] |= (*b as u64) << ((i % 8)*8);
+        }
+        bv
+    }
+
+    /// The bits has bytes, with the unused bits of the last byte cleared
+    pub fn to_bytes(&self) -> Vec<u8> {
+        (0..self.len.div_ceil(8)).map(|i| (self.words[i/8] >> ((i % 8)*8)) as u8).collect()
+    }
+
+    /// A bitset from a string of 0s and 1s, where _ can be used has a seperator
+    pub fn from_bit_str(sbits: &str) -> Result<BitVec, DataUtilsError> {
+        let mut bv = BitVec::new(0);
+        for (i, c) in sbits.chars().enumerate() {
+            match c {
+                '0' => bv.push(false),
+                '1' => bv.push(true),
+                '_' => (),
+                _ => return Err(DataUtilsError::Invalid(format!("BitVec:FromBitStr:{}:Invalid char {} at {}", sbits, c, i))),
+            }
+        }
+        Ok(bv)
+    }
+
+    /// The bits has a string of 0s and 1s, starting with bit 0
+    pub fn to_bit_string(&self) -> String {
+        (0..self.len).map(|i| if self.get(i) == Some(true) { '1' } else { '0' }).collect()
+    }
+
+    /// Combine with the other bitset, word by word, using the given op
+    fn combine(&self, other: &BitVec, op: impl Fn(u64, u64) -> u64) -> BitVec {
+        let len = self.len.max(other.len);
+        let nwords = len.div_ceil(64);
+        let words = (0..nwords).map(|i| {
+            op(self.words.get(i).copied().unwrap_or(0), other.words.get(i).copied().unwrap_or(0))
+        }).collect();
+        let mut bv = BitVec { words, len };
+        bv.trim();
+        bv
+    }
+
+}
+
+impl BitAnd for &BitVec {
+    type Output = BitVec;
+
+    fn bitand(self, rhs: &BitVec) -> BitVec {
+        self.combine(rhs, |a, b| a & b)
+    }
+}
+
+impl BitOr for &BitVec {
+    type Output = BitVec;
+
+    fn bitor(self, rhs: &BitVec) -> BitVec {
+        self.combine(rhs, |a, b| a | b)
+    }
+}
+
+impl BitXor for &BitVec {
+    type Output = BitVec;
+
+    fn bitxor(self, rhs: &BitVec) -> BitVec {
+        self.combine(rhs, |a, b| a ^ b)
+    }
+}
+
+impl Not for &BitVec {
+    type Output = BitVec;
+
+    fn not(self) -> BitVec {
+        let mut bv = BitVec { words: self.words.iter().map(|w| !w).collect(), len: self.len };
+        bv.trim();
+        bv
+    }
+}
+
+impl Display for BitVec {
+
+    /// The bit string, refer to to_bit_string
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.to_bit_string())
+    }
+
+}
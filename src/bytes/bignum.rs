@@ -0,0 +1,40 @@
+//!
+//! Treat a byte buffer has a arbitrary length big endian unsigned integer,
+//! ex for incrementing IVs or sequence number fields wider than 64 bits.
+//!
+//! HanishKVC, 2022
+//!
+
+use core::cmp::Ordering;
+
+
+///
+/// Add the given value to the big endian unsigned integer in the buffer, in place.
+///
+/// The buffer keeps its size, so the result wraps around if it doesnt fit,
+/// in which case true is returned to indicate the carry out of the buffer.
+///
+pub fn add_u64(buf: &mut [u8], val: u64) -> bool {
+    let mut carry = val as u128;
+    for b in buf.iter_mut().rev() {
+        if carry == 0 {
+            return false;
+        }
+        let sum = *b as u128 + (carry & 0xFF);
+        *b = sum as u8;
+        carry = (carry >> 8) + (sum >> 8);
+    }
+    carry != 0
+}
+
+/// Increment the big endian unsigned integer in the buffer by 1, returns true if it wrapped around.
+pub fn increment(buf: &mut [u8]) -> bool {
+    add_u64(buf, 1)
+}
+
+/// Compare the big endian unsigned integers in the given buffers, leading 0 bytes are ignored.
+pub fn compare(a: &[u8], b: &[u8]) -> Ordering {
+    let a = &a[a.iter().position(|&x| x != 0).unwrap_or(a.len())..];
+    let b = &b[b.iter().position(|&x| x != 0).unwrap_or(b.len())..];
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
@@ -0,0 +1,132 @@
+//!
+//! Hamming code based error correction
+//!
+//! * Hamming(7,4) - corrects any single bit error in a 7 bit codeword
+//! * Hamming(8,4) - adds a overall parity bit, so that single bit errors are
+//!   corrected, while double bit errors are detected (SECDED).
+//!
+//! Codeword bit i (1 based, wrt the classic p1 p2 d1 p3 d2 d3 d4 layout) is
+//! stored in bit i-1 of the u8, with the overall parity of (8,4) in bit 7.
+//!
+//! HanishKVC, 2022
+//!
+
+#[allow(unused_imports)]
+use crate::alloc_prelude::*;
+
+
+/// The result of decoding a codeword
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EccStatus {
+    /// No error found
+    Clean,
+    /// A single bit error was found and corrected
+    Corrected,
+    /// Multiple bit errors were found, which cant be corrected
+    Uncorrectable,
+}
+
+fn bit(val: u8, pos: u32) -> u8 {
+    (val >> pos) & 1
+}
+
+/// Encode the given 4 bit value (the low nibble) into a Hamming(7,4) codeword
+pub fn hamming74_encode(nibble: u8) -> u8 {
+    let (d1, d2, d3, d4) = (bit(nibble, 0), bit(nibble, 1), bit(nibble, 2), bit(nibble, 3));
+    let p1 = d1 ^ d2 ^ d4;
+    let p2 = d1 ^ d3 ^ d4;
+    let p3 = d2 ^ d3 ^ d4;
+    p1 | (p2 << 1) | (d1 << 2) | (p3 << 3) | (d2 << 4) | (d3 << 5) | (d4 << 6)
+}
+
+/// The position (1 based) of the bit in error, or 0 if no error, for a Hamming(7,4) codeword
+fn hamming74_syndrome(code: u8) -> u32 {
+    let mut syndrome = 0;
+    for i in 1..=7u32 {
+        if bit(code, i-1) == 1 {
+            syndrome ^= i;
+        }
+    }
+    syndrome
+}
+
+fn hamming74_data(code: u8) -> u8 {
+    bit(code, 2) | (bit(code, 4) << 1) | (bit(code, 5) << 2) | (bit(code, 6) << 3)
+}
+
+///
+/// Decode the given Hamming(7,4) codeword (bit 7 is ignored) into its 4 bit value.
+///
+/// Any single bit error is corrected, however multi bit errors cant be detected
+/// and will lead to wrong data, use the (8,4) code if that matters.
+///
+pub fn hamming74_decode(code: u8) -> (u8, EccStatus) {
+    let code = code & 0x7F;
+    let syndrome = hamming74_syndrome(code);
+    if syndrome == 0 {
+        return (hamming74_data(code), EccStatus::Clean);
+    }
+    (hamming74_data(code ^ (1 << (syndrome-1))), EccStatus::Corrected)
+}
+
+/// Encode the given 4 bit value (the low nibble) into a Hamming(8,4) SECDED codeword
+pub fn hamming84_encode(nibble: u8) -> u8 {
+    let code = hamming74_encode(nibble);
+    code | (((code.count_ones() & 1) as u8) << 7)
+}
+
+///
+/// Decode the given Hamming(8,4) codeword into its 4 bit value.
+///
+/// Single bit errors are corrected, double bit errors are reported has
+/// Uncorrectable, in which case the returned value is not reliable.
+///
+pub fn hamming84_decode(code: u8) -> (u8, EccStatus) {
+    let syndrome = hamming74_syndrome(code & 0x7F);
+    let parity_err = code.count_ones() & 1 == 1;
+    match (syndrome, parity_err) {
+        (0, false) => (hamming74_data(code), EccStatus::Clean),
+        (0, true) => (hamming74_data(code), EccStatus::Corrected),
+        (_, true) => (hamming74_data(code ^ (1 << (syndrome-1))), EccStatus::Corrected),
+        (_, false) => (hamming74_data(code), EccStatus::Uncorrectable),
+    }
+}
+
+/// SECDED encode the given bytes, each byte becomes 2 Hamming(8,4) codewords (low nibble first)
+pub fn secded_encode(data: &[u8]) -> Vec<u8> {
+    let mut vout = Vec::with_capacity(data.len()*2);
+    for d in data {
+        vout.push(hamming84_encode(d & 0x0F));
+        vout.push(hamming84_encode(d >> 4));
+    }
+    vout
+}
+
+///
+/// Decode the given SECDED (Hamming(8,4)) encoded bytes, generated by secded_encode.
+///
+/// Returns the decoded bytes along with the number of corrected codewords,
+/// or a error if the encoded data is of odd length or has a uncorrectable codeword.
+///
+pub fn secded_decode(code: &[u8]) -> Result<(Vec<u8>, usize), String> {
+    if !code.len().is_multiple_of(2) {
+        return Err(format!("ERRR:DU:Ecc:SecdedDecode:Odd length {}", code.len()));
+    }
+    let mut vout = Vec::with_capacity(code.len()/2);
+    let mut corrected = 0;
+    for (i, c) in code.iter().enumerate() {
+        let (nibble, status) = hamming84_decode(*c);
+        match status {
+            EccStatus::Clean => (),
+            EccStatus::Corrected => corrected += 1,
+            EccStatus::Uncorrectable => return Err(format!("ERRR:DU:Ecc:SecdedDecode:Uncorrectable codeword at {}", i)),
+        }
+        if i % 2 == 0 {
+            vout.push(nibble);
+        } else {
+            let last = vout.len()-1;
+            vout[last] |= nibble << 4;
+        }
+    }
+    Ok((vout, corrected))
+}
@@ -0,0 +1,150 @@
+//!
+//! Length prefixed framing of byte buffers, and deframing of a incrementally
+//! received byte stream.
+//!
+//! HanishKVC, 2022
+//!
+
+#[allow(unused_imports)]
+use crate::alloc_prelude::*;
+
+
+/// How the length of each frame is encoded before the frame data
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LenPrefix {
+    U8,
+    U16BE,
+    U32LE,
+    /// unsigned LEB128, ie 7 bits per byte, with the high bit set if more bytes follow
+    Varint,
+}
+
+/// Encode the given length as a unsigned LEB128 varint
+fn varint_encode(len: usize, vout: &mut Vec<u8>) {
+    let mut len = len as u64;
+    loop {
+        let b = (len & 0x7F) as u8;
+        len >>= 7;
+        if len == 0 {
+            vout.push(b);
+            break;
+        }
+        vout.push(b | 0x80);
+    }
+}
+
+/// Decode a unsigned LEB128 varint at the start of the given buffer, returning
+/// the value and the number of bytes used, or None if more bytes are needed.
+///
+/// Its a error if the value doesnt fit in u64 (and in turn usize).
+fn varint_decode(buf: &[u8]) -> Result<Option<(usize, usize)>, String> {
+    let mut val: u64 = 0;
+    for (i, b) in buf.iter().enumerate() {
+        if i >= 10 {
+            break;
+        }
+        // The 10th byte can only contribute the top bit of a u64
+        if (i == 9) && (b & 0x7F) > 1 {
+            return Err("ERRR:DU:Bytes:Frames:Varint length prefix overflows u64".to_string());
+        }
+        val |= ((b & 0x7F) as u64) << (7*i);
+        if b & 0x80 == 0 {
+            let len = usize::try_from(val).map_err(|_| format!("ERRR:DU:Bytes:Frames:Varint length {} doesnt fit in usize", val))?;
+            return Ok(Some((len, i+1)));
+        }
+    }
+    if buf.len() >= 10 {
+        return Err("ERRR:DU:Bytes:Frames:Varint length prefix longer than 10 bytes".to_string());
+    }
+    Ok(None)
+}
+
+/// Encode the given frames, each prefixed with its length as specified.
+///
+/// Its a error if a frame is too long for the specified length prefix.
+pub fn encode_frames(frames: &[Vec<u8>], prefix: LenPrefix) -> Result<Vec<u8>, String> {
+    let mut vout = Vec::new();
+    for frame in frames {
+        let len = frame.len();
+        let err = || format!("ERRR:DU:Bytes:Frames:Frame of {} bytes too long for {:?}", len, prefix);
+        match prefix {
+            LenPrefix::U8 => vout.push(u8::try_from(len).map_err(|_| err())?),
+            LenPrefix::U16BE => vout.extend(u16::try_from(len).map_err(|_| err())?.to_be_bytes()),
+            LenPrefix::U32LE => vout.extend(u32::try_from(len).map_err(|_| err())?.to_le_bytes()),
+            LenPrefix::Varint => varint_encode(len, &mut vout),
+        }
+        vout.extend_from_slice(frame);
+    }
+    Ok(vout)
+}
+
+///
+/// Decode length prefixed frames from a byte stream, which is fed to it incrementally.
+/// The bytes fed are buffered, till complete frames are available.
+///
+/// By default any frame length allowed by the prefix is accepted, use with_max_len
+/// to limit it, so that a bad length prefix doesnt lead to buffering without bound.
+///
+#[derive(Debug, Clone)]
+pub struct FrameDecoder {
+    prefix: LenPrefix,
+    buf: Vec<u8>,
+    max_len: usize,
+}
+
+impl FrameDecoder {
+
+    pub fn new(prefix: LenPrefix) -> FrameDecoder {
+        FrameDecoder { prefix, buf: Vec::new(), max_len: usize::MAX }
+    }
+
+    /// Treat frames longer than the given length has a error
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Add the given bytes to the stream being decoded
+    pub fn feed(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+
+    /// Number of bytes buffered, which are not yet part of a complete frame
+    pub fn pending(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Get the length of the next frame and the size of its prefix, if available
+    fn next_len(&self) -> Result<Option<(usize, usize)>, String> {
+        let fixed = |n: usize| self.buf.len() >= n;
+        let olen = match self.prefix {
+            LenPrefix::U8 => fixed(1).then(|| (self.buf[0] as usize, 1)),
+            LenPrefix::U16BE => fixed(2).then(|| (u16::from_be_bytes([self.buf[0], self.buf[1]]) as usize, 2)),
+            LenPrefix::U32LE => fixed(4).then(|| (u32::from_le_bytes(self.buf[..4].try_into().unwrap()) as usize, 4)),
+            LenPrefix::Varint => varint_decode(&self.buf)?,
+        };
+        Ok(olen)
+    }
+
+    /// Get the next complete frame, if available.
+    ///
+    /// Returns a error, if the stream has a malformed length prefix, or a frame
+    /// longer than the max length.
+    pub fn next_frame(&mut self) -> Result<Option<Vec<u8>>, String> {
+        let (len, plen) = match self.next_len()? {
+            Some(lp) => lp,
+            None => return Ok(None),
+        };
+        if len > self.max_len {
+            return Err(format!("ERRR:DU:Bytes:Frames:Frame length {} beyond max {}", len, self.max_len));
+        }
+        let flen = plen.checked_add(len).ok_or_else(|| format!("ERRR:DU:Bytes:Frames:Frame length {} too large", len))?;
+        if self.buf.len() < flen {
+            return Ok(None);
+        }
+        let frame = self.buf[plen..flen].to_vec();
+        self.buf.drain(..flen);
+        Ok(Some(frame))
+    }
+
+}
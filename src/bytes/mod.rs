@@ -0,0 +1,894 @@
+//!
+//! Helpers to build, parse and manipulate binary byte buffers
+//!
+//! HanishKVC, 2022
+//!
+
+pub mod frames;
+pub mod bignum;
+pub mod ecc;
+
+use core::ops::Range;
+
+use crate::fmath;
+
+#[allow(unused_imports)]
+use crate::alloc_prelude::*;
+
+/// Generate the put and get members for a given integer type and endianness
+macro_rules! cursor_int_rw {
+    ($put:ident, $get:ident, $t:ty, $to:ident, $from:ident) => {
+        #[doc = concat!("Write a ", stringify!($t), " at the current position, using ", stringify!($to))]
+        pub fn $put(&mut self, val: $t) {
+            self.put_bytes(&val.$to());
+        }
+
+        #[doc = concat!("Read a ", stringify!($t), " from the current position, using ", stringify!($from))]
+        pub fn $get(&mut self) -> Result<$t, String> {
+            let vbytes = self.get_bytes(core::mem::size_of::<$t>())?;
+            Ok(<$t>::$from(vbytes.try_into().unwrap()))
+        }
+    };
+}
+
+///
+/// A cursor over a owned byte buffer, to write and read data at its current position.
+///
+/// Writes overwrite existing data at the current position, and extend the buffer
+/// as required. Reads are bounds checked and return a error if there isnt enough data.
+///
+#[derive(Debug, Clone, Default)]
+pub struct ByteCursor {
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl ByteCursor {
+
+    pub fn new() -> ByteCursor {
+        ByteCursor::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> ByteCursor {
+        ByteCursor { buf: Vec::with_capacity(capacity), pos: 0 }
+    }
+
+    /// Create a cursor over the given buffer, positioned at its beginning
+    pub fn from_vec(buf: Vec<u8>) -> ByteCursor {
+        ByteCursor { buf, pos: 0 }
+    }
+
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Move to the given position, which can be atmost the current length of the buffer
+    pub fn seek(&mut self, pos: usize) -> Result<(), String> {
+        if pos > self.buf.len() {
+            return Err(format!("ERRR:DU:Bytes:ByteCursor:Seek:Position {} beyond length {}", pos, self.buf.len()));
+        }
+        self.pos = pos;
+        Ok(())
+    }
+
+    /// Number of bytes available for reading from the current position
+    pub fn remaining(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buf
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.buf
+    }
+
+    /// Write the given bytes at the current position
+    pub fn put_bytes(&mut self, vbytes: &[u8]) {
+        let end = self.pos + vbytes.len();
+        if end > self.buf.len() {
+            self.buf.resize(end, 0);
+        }
+        self.buf[self.pos..end].copy_from_slice(vbytes);
+        self.pos = end;
+    }
+
+    /// Read the given number of bytes from the current position
+    pub fn get_bytes(&mut self, len: usize) -> Result<&[u8], String> {
+        if len > self.remaining() {
+            return Err(format!("ERRR:DU:Bytes:ByteCursor:Get:Need {} bytes, only {} remaining at {}", len, self.remaining(), self.pos));
+        }
+        let start = self.pos;
+        self.pos += len;
+        Ok(&self.buf[start..self.pos])
+    }
+
+    pub fn put_u8(&mut self, val: u8) {
+        self.put_bytes(&[val]);
+    }
+
+    pub fn get_u8(&mut self) -> Result<u8, String> {
+        Ok(self.get_bytes(1)?[0])
+    }
+
+    cursor_int_rw!(put_u16_le, get_u16_le, u16, to_le_bytes, from_le_bytes);
+    cursor_int_rw!(put_u16_be, get_u16_be, u16, to_be_bytes, from_be_bytes);
+    cursor_int_rw!(put_u32_le, get_u32_le, u32, to_le_bytes, from_le_bytes);
+    cursor_int_rw!(put_u32_be, get_u32_be, u32, to_be_bytes, from_be_bytes);
+    cursor_int_rw!(put_u64_le, get_u64_le, u64, to_le_bytes, from_le_bytes);
+    cursor_int_rw!(put_u64_be, get_u64_be, u64, to_be_bytes, from_be_bytes);
+
+}
+
+/// A range of differing bytes between two buffers a and b.
+///
+/// The range covers len_a bytes from offset in a, and len_b bytes from offset_b in b.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffRange {
+    pub offset: usize,
+    pub offset_b: usize,
+    pub len_a: usize,
+    pub len_b: usize,
+}
+
+///
+/// Find the ranges of bytes which differ between the given buffers, comparing
+/// bytes at the same offsets. If the buffers differ in length, the extra bytes
+/// are reported has (part of) the last range.
+///
+pub fn diff(a: &[u8], b: &[u8]) -> Vec<DiffRange> {
+    let mut vdiffs: Vec<DiffRange> = Vec::new();
+    let common = a.len().min(b.len());
+    let mut i = 0;
+    while i < common {
+        if a[i] == b[i] {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while (i < common) && (a[i] != b[i]) {
+            i += 1;
+        }
+        vdiffs.push(DiffRange { offset: start, offset_b: start, len_a: i-start, len_b: i-start });
+    }
+    if a.len() != b.len() {
+        match vdiffs.last_mut() {
+            Some(last) if last.offset + last.len_a == common => {
+                last.len_a = a.len() - last.offset;
+                last.len_b = b.len() - last.offset;
+            }
+            _ => vdiffs.push(DiffRange { offset: common, offset_b: common, len_a: a.len()-common, len_b: b.len()-common }),
+        }
+    }
+    vdiffs
+}
+
+/// Number of matching bytes needed, to treat the buffers as back in sync
+const DIFF_SYNC_LEN: usize = 4;
+
+///
+/// Find the ranges of bytes which differ between the given buffers, allowing for
+/// bytes inserted/removed in either buffer. On a mismatch, the buffers are
+/// realigned by skipping upto max_shift bytes in either of them, choosing the
+/// smallest skip after which atleast a few bytes match again.
+///
+/// This is a simple greedy alignment, and not a minimal edit script.
+///
+pub fn diff_aligned(a: &[u8], b: &[u8], max_shift: usize) -> Vec<DiffRange> {
+    let in_sync = |i: usize, j: usize| {
+        let k = DIFF_SYNC_LEN.min(a.len()-i).min(b.len()-j);
+        if k == 0 {
+            return (i == a.len()) && (j == b.len());
+        }
+        a[i..i+k] == b[j..j+k]
+    };
+    let mut vdiffs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while (i < a.len()) && (j < b.len()) {
+        if a[i] == b[j] {
+            i += 1;
+            j += 1;
+            continue;
+        }
+        let mut oshift = None;
+        'search: for s in 1..=2*max_shift {
+            for da in (0..=s.min(max_shift)).rev() {
+                let db = s - da;
+                if (db > max_shift) || (i+da > a.len()) || (j+db > b.len()) {
+                    continue;
+                }
+                if in_sync(i+da, j+db) {
+                    oshift = Some((da, db));
+                    break 'search;
+                }
+            }
+        }
+        let (da, db) = oshift.unwrap_or((a.len()-i, b.len()-j));
+        vdiffs.push(DiffRange { offset: i, offset_b: j, len_a: da, len_b: db });
+        i += da;
+        j += db;
+    }
+    if (i < a.len()) || (j < b.len()) {
+        vdiffs.push(DiffRange { offset: i, offset_b: j, len_a: a.len()-i, len_b: b.len()-j });
+    }
+    vdiffs
+}
+
+/// Replace replace_len bytes at offset with data (which can be of a different length)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Patch {
+    pub offset: usize,
+    pub replace_len: usize,
+    pub data: Vec<u8>,
+}
+
+///
+/// Apply the given patches to the buffer. The offsets of all the patches refer
+/// to the original buffer, so the order of the patches doesnt matter.
+///
+/// The patches shouldnt overlap each other (including multiple patches at the
+/// same offset) or go beyond the end of the buffer. If they do, the buffer is
+/// left untouched and a error is returned.
+///
+pub fn apply_patches(buf: &mut Vec<u8>, patches: &[Patch]) -> Result<(), String> {
+    let mut vsorted: Vec<&Patch> = patches.iter().collect();
+    vsorted.sort_by_key(|p| p.offset);
+    for (i, p) in vsorted.iter().enumerate() {
+        let pend = match p.offset.checked_add(p.replace_len) {
+            Some(pend) if pend <= buf.len() => pend,
+            _ => return Err(format!("ERRR:DU:Bytes:ApplyPatches:Patch at {} of len {} beyond buffer length {}", p.offset, p.replace_len, buf.len())),
+        };
+        if let Some(next) = vsorted.get(i+1) {
+            if (next.offset < pend) || (next.offset == p.offset) {
+                return Err(format!("ERRR:DU:Bytes:ApplyPatches:Patches at {} and {} overlap", p.offset, next.offset));
+            }
+        }
+    }
+    for p in vsorted.iter().rev() {
+        buf.splice(p.offset..p.offset+p.replace_len, p.data.iter().copied());
+    }
+    Ok(())
+}
+
+/// Generate the get member of SliceReadExt for a given integer type and endianness
+macro_rules! slice_int_get {
+    ($get:ident, $t:ty, $from:ident) => {
+        #[doc = concat!("Read a ", stringify!($t), " at the given offset, using ", stringify!($from))]
+        fn $get(&self, offset: usize) -> Result<$t, String> {
+            let vbytes = self.get_bytes(offset, core::mem::size_of::<$t>())?;
+            Ok(<$t>::$from(vbytes.try_into().unwrap()))
+        }
+    };
+}
+
+/// Generate the put member of SliceWriteExt for a given integer type and endianness
+macro_rules! slice_int_put {
+    ($put:ident, $t:ty, $to:ident) => {
+        #[doc = concat!("Write a ", stringify!($t), " at the given offset, using ", stringify!($to))]
+        fn $put(&mut self, offset: usize, val: $t) -> Result<(), String> {
+            self.put_bytes(offset, &val.$to())
+        }
+    };
+}
+
+/// Bounds checked reading of fields at given offsets within a byte slice
+pub trait SliceReadExt {
+
+    /// Get len bytes at the given offset
+    fn get_bytes(&self, offset: usize, len: usize) -> Result<&[u8], String>;
+
+    fn get_u8(&self, offset: usize) -> Result<u8, String> {
+        Ok(self.get_bytes(offset, 1)?[0])
+    }
+
+    slice_int_get!(get_u16_le, u16, from_le_bytes);
+    slice_int_get!(get_u16_be, u16, from_be_bytes);
+    slice_int_get!(get_u32_le, u32, from_le_bytes);
+    slice_int_get!(get_u32_be, u32, from_be_bytes);
+    slice_int_get!(get_u64_le, u64, from_le_bytes);
+    slice_int_get!(get_u64_be, u64, from_be_bytes);
+
+}
+
+impl SliceReadExt for [u8] {
+
+    fn get_bytes(&self, offset: usize, len: usize) -> Result<&[u8], String> {
+        match offset.checked_add(len) {
+            Some(end) if end <= self.len() => Ok(&self[offset..end]),
+            _ => Err(format!("ERRR:DU:Bytes:SliceRead:{} bytes at {} beyond length {}", len, offset, self.len())),
+        }
+    }
+
+}
+
+/// Bounds checked writing of fields at given offsets within a mutable byte slice
+pub trait SliceWriteExt {
+
+    /// Overwrite the bytes at the given offset with the given bytes
+    fn put_bytes(&mut self, offset: usize, vbytes: &[u8]) -> Result<(), String>;
+
+    fn put_u8(&mut self, offset: usize, val: u8) -> Result<(), String> {
+        self.put_bytes(offset, &[val])
+    }
+
+    slice_int_put!(put_u16_le, u16, to_le_bytes);
+    slice_int_put!(put_u16_be, u16, to_be_bytes);
+    slice_int_put!(put_u32_le, u32, to_le_bytes);
+    slice_int_put!(put_u32_be, u32, to_be_bytes);
+    slice_int_put!(put_u64_le, u64, to_le_bytes);
+    slice_int_put!(put_u64_be, u64, to_be_bytes);
+
+}
+
+impl SliceWriteExt for [u8] {
+
+    fn put_bytes(&mut self, offset: usize, vbytes: &[u8]) -> Result<(), String> {
+        match offset.checked_add(vbytes.len()) {
+            Some(end) if end <= self.len() => {
+                self[offset..end].copy_from_slice(vbytes);
+                Ok(())
+            }
+            _ => Err(format!("ERRR:DU:Bytes:SliceWrite:{} bytes at {} beyond length {}", vbytes.len(), offset, self.len())),
+        }
+    }
+
+}
+
+/// The escape byte used by the run-length encoding
+pub const RLE_ESC: u8 = 0xFF;
+
+/// Minimum run length, which is encoded as a run (shorter runs are kept literally)
+const RLE_MIN_RUN: usize = 4;
+
+///
+/// Run-length encode the given data, using the following scheme
+///
+/// * a run of RLE_MIN_RUN (4) or more of the same byte is encoded as [RLE_ESC, count, byte],
+///   where count is 1..=255, longer runs are split into multiple such triplets
+/// * the escape byte itself is always encoded as a run, even if its a single byte
+/// * any other byte is kept as is
+///
+/// So data without long runs or 0xFF bytes passes through unchanged.
+///
+pub fn rle_encode(data: &[u8]) -> Vec<u8> {
+    let mut vout = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        let b = data[i];
+        let run = data[i..].iter().take(255).take_while(|x| **x == b).count();
+        if (run >= RLE_MIN_RUN) || (b == RLE_ESC) {
+            vout.extend_from_slice(&[RLE_ESC, run as u8, b]);
+        } else {
+            vout.extend(core::iter::repeat_n(b, run));
+        }
+        i += run;
+    }
+    vout
+}
+
+/// Decode data encoded using rle_encode
+pub fn rle_decode(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut vout = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        if data[i] != RLE_ESC {
+            vout.push(data[i]);
+            i += 1;
+            continue;
+        }
+        if i + 2 >= data.len() {
+            return Err(format!("ERRR:DU:Bytes:RleDecode:Truncated run at {}", i));
+        }
+        let count = data[i+1] as usize;
+        if count == 0 {
+            return Err(format!("ERRR:DU:Bytes:RleDecode:Zero length run at {}", i));
+        }
+        vout.extend(core::iter::repeat_n(data[i+2], count));
+        i += 3;
+    }
+    Ok(vout)
+}
+
+/// Xor the data with the given key, repeating the key as required.
+/// A empty key leaves the data unchanged.
+pub fn xor_with(data: &mut [u8], key: &[u8]) {
+    if key.is_empty() {
+        return;
+    }
+    for (d, k) in data.iter_mut().zip(key.iter().cycle()) {
+        *d ^= k;
+    }
+}
+
+/// Rotate the bits of each byte left by the given number of bits
+pub fn rotl_per_byte(data: &mut [u8], bits: u32) {
+    for d in data.iter_mut() {
+        *d = d.rotate_left(bits);
+    }
+}
+
+/// Rotate the bits of each byte right by the given number of bits
+pub fn rotr_per_byte(data: &mut [u8], bits: u32) {
+    for d in data.iter_mut() {
+        *d = d.rotate_right(bits);
+    }
+}
+
+/// Add the given value to each byte, modulo 256. Use 256-val (ie val.wrapping_neg())
+/// to undo the same.
+pub fn add_mod256(data: &mut [u8], val: u8) {
+    for d in data.iter_mut() {
+        *d = d.wrapping_add(val);
+    }
+}
+
+///
+/// Encode the given data using Consistent Overhead Byte Stuffing, so that the
+/// encoded data doesnt contain any 0 bytes.
+///
+/// The 0 delimiter, which marks the end of a frame, is not added to the output,
+/// the caller can append it has required. A full block (of 254 non zero bytes)
+/// at the end of the data is not followed by a empty block, as in canonical cobs.
+///
+pub fn cobs_encode(data: &[u8]) -> Vec<u8> {
+    let mut vout = Vec::with_capacity(data.len() + data.len()/254 + 2);
+    let mut codepos = 0;
+    vout.push(0);
+    let mut code = 1u8;
+    for (i, &d) in data.iter().enumerate() {
+        if d == 0 {
+            vout[codepos] = code;
+            codepos = vout.len();
+            vout.push(0);
+            code = 1;
+            continue;
+        }
+        vout.push(d);
+        code += 1;
+        if (code == 0xFF) && (i+1 < data.len()) {
+            vout[codepos] = code;
+            codepos = vout.len();
+            vout.push(0);
+            code = 1;
+        }
+    }
+    vout[codepos] = code;
+    vout
+}
+
+///
+/// Decode the given Consistent Overhead Byte Stuffing encoded data.
+///
+/// The data shouldnt include the 0 delimiter, a 0 byte or a code which runs
+/// beyond the end of the data will lead to a error.
+///
+pub fn cobs_decode(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut vout = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        let code = data[i] as usize;
+        if code == 0 {
+            return Err(format!("ERRR:DU:Bytes:CobsDecode:Unexpected 0 byte at {}", i));
+        }
+        if i + code > data.len() {
+            return Err(format!("ERRR:DU:Bytes:CobsDecode:Code {} at {} runs past end of data", code, i));
+        }
+        let block = &data[i+1..i+code];
+        if let Some(zpos) = block.iter().position(|&d| d == 0) {
+            return Err(format!("ERRR:DU:Bytes:CobsDecode:Unexpected 0 byte at {}", i+1+zpos));
+        }
+        vout.extend_from_slice(block);
+        i += code;
+        if (code < 0xFF) && (i < data.len()) {
+            vout.push(0);
+        }
+    }
+    Ok(vout)
+}
+
+///
+/// Split the given data at each delim byte, which is not escaped, ie not preceded by
+/// the esc byte (which itself is not escaped), returning the spans of the parts.
+///
+/// Similar to slice split, n delimiters give n+1 parts, so a trailing delimiter gives
+/// a empty last part and empty data gives a single empty part. The parts include the
+/// escape bytes has is, refer to unescape_delimited. If esc is None or same has delim,
+/// there is no escaping.
+///
+pub fn split_delimited_spans(data: &[u8], delim: u8, esc: Option<u8>) -> Vec<Range<usize>> {
+    let esc = esc.filter(|e| *e != delim);
+    let mut vspans = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < data.len() {
+        if Some(data[i]) == esc {
+            i += 2;
+            continue;
+        }
+        if data[i] == delim {
+            vspans.push(start..i);
+            start = i + 1;
+        }
+        i += 1;
+    }
+    vspans.push(start..data.len());
+    vspans
+}
+
+/// Split the given data at each unescaped delim byte, returning the parts has slices
+/// of the data, refer to split_delimited_spans.
+pub fn split_delimited(data: &[u8], delim: u8, esc: Option<u8>) -> Vec<&[u8]> {
+    split_delimited_spans(data, delim, esc).into_iter().map(|r| &data[r]).collect()
+}
+
+/// Remove the escape bytes from a part returned by split_delimited, ie esc X becomes X.
+/// A trailing esc byte (which doesnt escape anything) is kept has is.
+pub fn unescape_delimited(part: &[u8], esc: u8) -> Vec<u8> {
+    let mut vout = Vec::with_capacity(part.len());
+    let mut i = 0;
+    while i < part.len() {
+        if part[i] == esc && i + 1 < part.len() {
+            i += 1;
+        }
+        vout.push(part[i]);
+        i += 1;
+    }
+    vout
+}
+
+///
+/// Convert the given buffer into a printable string, where printable ascii chars
+/// are kept has is, while other bytes are represented has \xNN.
+/// The \ char itself is represented has \\, so that the conversion is reversible.
+///
+pub fn to_printable(data: &[u8]) -> String {
+    let mut sout = String::with_capacity(data.len());
+    for &d in data {
+        match d {
+            b'\\' => sout.push_str("\\\\"),
+            0x20..=0x7E => sout.push(d as char),
+            _ => sout.push_str(&format!("\\x{:02X}", d)),
+        }
+    }
+    sout
+}
+
+/// Convert back a string generated by to_printable into the corresponding buffer
+pub fn from_printable(s: &str) -> Result<Vec<u8>, String> {
+    let mut vout = Vec::with_capacity(s.len());
+    let sb = s.as_bytes();
+    let mut i = 0;
+    while i < sb.len() {
+        if sb[i] != b'\\' {
+            vout.push(sb[i]);
+            i += 1;
+            continue;
+        }
+        match sb.get(i+1) {
+            Some(b'\\') => {
+                vout.push(b'\\');
+                i += 2;
+            }
+            Some(b'x') if i+4 <= sb.len() => {
+                // from_str_radix allows a leading + sign, so check for hex digits explicitly
+                if !sb[i+2..i+4].iter().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(format!("ERRR:DU:Bytes:FromPrintable:Invalid escape at {}", i));
+                }
+                let shex = core::str::from_utf8(&sb[i+2..i+4]).map_err(|e| format!("ERRR:DU:Bytes:FromPrintable:Invalid escape at {}:{}", i, e))?;
+                let d = u8::from_str_radix(shex, 16).map_err(|e| format!("ERRR:DU:Bytes:FromPrintable:Invalid escape at {}:{}", i, e))?;
+                vout.push(d);
+                i += 4;
+            }
+            _ => return Err(format!("ERRR:DU:Bytes:FromPrintable:Invalid escape at {}", i)),
+        }
+    }
+    Ok(vout)
+}
+
+/// Swap the high and low nibbles of each byte
+pub fn swap_nibbles(data: &mut [u8]) {
+    for d in data.iter_mut() {
+        *d = d.rotate_left(4);
+    }
+}
+
+/// Reverse the order of the bits within each byte, the byte order is retained
+pub fn reverse_bits_per_byte(data: &mut [u8]) {
+    for d in data.iter_mut() {
+        *d = d.reverse_bits();
+    }
+}
+
+/// Reverse the bit order of the buffer has a whole, ie the last bit becomes the first bit
+pub fn reverse_bits(data: &mut [u8]) {
+    data.reverse();
+    reverse_bits_per_byte(data);
+}
+
+/// Number of bits which differ btw the given buffers, they should be of the same length.
+pub fn hamming_distance(a: &[u8], b: &[u8]) -> Result<usize, String> {
+    if a.len() != b.len() {
+        return Err(format!("ERRR:DU:Bytes:HammingDistance:Length mismatch {} vs {}", a.len(), b.len()));
+    }
+    Ok(a.iter().zip(b.iter()).map(|(x, y)| (x ^ y).count_ones() as usize).sum())
+}
+
+/// Number of bits which differ btw the given buffers, with the shorter buffer
+/// treated has if padded with the given pad byte to the length of the longer one.
+pub fn hamming_distance_padded(a: &[u8], b: &[u8], pad: u8) -> usize {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let common: usize = short.iter().zip(long.iter()).map(|(x, y)| (x ^ y).count_ones() as usize).sum();
+    let extra: usize = long[short.len()..].iter().map(|x| (x ^ pad).count_ones() as usize).sum();
+    common + extra
+}
+
+/// Count of each byte value in the given data
+pub fn byte_histogram(data: &[u8]) -> [u64; 256] {
+    let mut hist = [0u64; 256];
+    for &d in data {
+        hist[d as usize] += 1;
+    }
+    hist
+}
+
+///
+/// Chi-square statistic of the byte frequencies in the given data, wrt a uniform
+/// distribution, useful has a quick check of the quality of random data.
+///
+/// For uniformly random data, it should be around 255 (the degrees of freedom),
+/// with values beyond ~330 (or below ~190) being suspicious at the 1% level.
+/// Has a rule of thumb, the data should be atleast a few KB for the check to be
+/// meaningful. Returns 0.0 for empty data.
+///
+pub fn chi_square_uniformity(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let expected = data.len() as f64 / 256.0;
+    byte_histogram(data).iter().map(|&c| {
+        let diff = c as f64 - expected;
+        diff * diff / expected
+    }).sum()
+}
+
+/// Shannon entropy of the given data, in bits per byte (0.0 to 8.0)
+pub fn entropy(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let total = data.len() as f64;
+    byte_histogram(data).iter().filter(|&&c| c > 0).map(|&c| {
+        let p = c as f64 / total;
+        -p * fmath::f64::log2(p)
+    }).sum()
+}
+
+///
+/// Shannon entropy of each window of the given size, sliding over the data by
+/// one byte at a time. The entry at index i corresponds to data[i..i+ww], so
+/// there are data.len()-ww+1 entries (none if data is shorter than ww or ww is 0).
+///
+/// High entropy regions (near 8.0) are typically compressed or encrypted data.
+///
+pub fn sw_entropy(data: &[u8], ww: usize) -> Vec<f64> {
+    if ww == 0 || data.len() < ww {
+        return Vec::new();
+    }
+    let mut hist = byte_histogram(&data[..ww]);
+    let total = ww as f64;
+    let plogp = |c: u64| if c == 0 { 0.0 } else { let p = c as f64 / total; -p * fmath::f64::log2(p) };
+    let mut ent: f64 = hist.iter().map(|&c| plogp(c)).sum();
+    let mut vout = Vec::with_capacity(data.len()-ww+1);
+    vout.push(ent.max(0.0));
+    for i in ww..data.len() {
+        let (dout, din) = (data[i-ww] as usize, data[i] as usize);
+        if dout != din {
+            ent -= plogp(hist[dout]) + plogp(hist[din]);
+            hist[dout] -= 1;
+            hist[din] += 1;
+            ent += plogp(hist[dout]) + plogp(hist[din]);
+        }
+        vout.push(ent.max(0.0));
+    }
+    vout
+}
+
+/// Find the first occurance of the given byte, checking 8 bytes at a time
+fn memchr(haystack: &[u8], needle: u8) -> Option<usize> {
+    const LO: u64 = 0x0101010101010101;
+    const HI: u64 = 0x8080808080808080;
+    let rep = LO * needle as u64;
+    let mut i = 0;
+    for chunk in haystack.chunks_exact(8) {
+        let mut wb = [0u8; 8];
+        wb.copy_from_slice(chunk);
+        let w = u64::from_ne_bytes(wb) ^ rep;
+        // is there a zero byte in w, ie a matching byte in the chunk
+        if (w.wrapping_sub(LO) & !w & HI) != 0 {
+            break;
+        }
+        i += 8;
+    }
+    haystack[i..].iter().position(|b| *b == needle).map(|p| p + i)
+}
+
+///
+/// Search for a needle within haystacks, using the Two-Way string matching algorithm,
+/// which takes linear time (wrt the haystack length) even in the worst case, without
+/// needing any per needle tables beyond a couple of numbers. Single byte needles are
+/// searched for, 8 bytes at a time.
+///
+/// Create it once for a needle, when searching for it in many haystacks.
+///
+#[derive(Debug, Clone)]
+pub struct Finder<'a> {
+    needle: &'a [u8],
+    /// The critical position, which splits the needle into the left and right parts
+    crit: usize,
+    /// The period of the needle, or the shift to use, if the needle is not periodic
+    period: usize,
+    periodic: bool,
+}
+
+impl<'a> Finder<'a> {
+
+    pub fn new(needle: &'a [u8]) -> Finder<'a> {
+        let (crit_lt, period_lt) = Finder::maximal_suffix(needle, false);
+        let (crit_gt, period_gt) = Finder::maximal_suffix(needle, true);
+        let (crit, period) = if crit_lt > crit_gt { (crit_lt, period_lt) } else { (crit_gt, period_gt) };
+        if period + crit <= needle.len() && needle[..crit] == needle[period..period+crit] {
+            Finder { needle, crit, period, periodic: true }
+        } else {
+            let period = crit.max(needle.len() - crit) + 1;
+            Finder { needle, crit, period, periodic: false }
+        }
+    }
+
+    /// The start and period of the maximal suffix of the needle, wrt the byte order
+    /// (or the reversed order)
+    fn maximal_suffix(needle: &[u8], reversed: bool) -> (usize, usize) {
+        let (mut left, mut right, mut offset, mut period) = (0, 1, 0, 1);
+        while right + offset < needle.len() {
+            let (a, b) = (needle[right + offset], needle[left + offset]);
+            if (!reversed && a < b) || (reversed && a > b) {
+                right += offset + 1;
+                offset = 0;
+                period = right - left;
+            } else if a == b {
+                if offset + 1 == period {
+                    right += offset + 1;
+                    offset = 0;
+                } else {
+                    offset += 1;
+                }
+            } else {
+                left = right;
+                right += 1;
+                offset = 0;
+                period = 1;
+            }
+        }
+        (left, period)
+    }
+
+    pub fn needle(&self) -> &'a [u8] {
+        self.needle
+    }
+
+    /// The position of the first occurance of the needle in the haystack.
+    /// A empty needle is found at 0.
+    pub fn find_in(&self, haystack: &[u8]) -> Option<usize> {
+        let (needle, crit, n) = (self.needle, self.crit, self.needle.len());
+        match n {
+            0 => return Some(0),
+            1 => return memchr(haystack, needle[0]),
+            _ => (),
+        }
+        let mut pos = 0;
+        // The length of the needle prefix already known to match, in the periodic case
+        let mut memory = 0;
+        while pos + n <= haystack.len() {
+            let mut i = crit.max(memory);
+            while i < n && needle[i] == haystack[pos + i] {
+                i += 1;
+            }
+            if i < n {
+                pos += i - crit + 1;
+                memory = 0;
+                continue;
+            }
+            let mut j = crit;
+            while j > memory && needle[j - 1] == haystack[pos + j - 1] {
+                j -= 1;
+            }
+            if j <= memory {
+                return Some(pos);
+            }
+            pos += self.period;
+            if self.periodic {
+                memory = n - self.period;
+            }
+        }
+        None
+    }
+
+    /// The positions of all (including overlapping) occurances of the needle in the haystack
+    pub fn find_all_in(&self, haystack: &[u8]) -> Vec<usize> {
+        let mut vpos = Vec::new();
+        let mut start = 0;
+        while start <= haystack.len() {
+            match self.find_in(&haystack[start..]) {
+                Some(p) => {
+                    vpos.push(start + p);
+                    start += p + 1;
+                },
+                None => break,
+            }
+        }
+        vpos
+    }
+
+}
+
+/// The position of the first occurance of needle in haystack, refer to Finder
+pub fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    Finder::new(needle).find_in(haystack)
+}
+
+/// The positions of all (including overlapping) occurances of needle in haystack, refer to Finder
+pub fn find_all(haystack: &[u8], needle: &[u8]) -> Vec<usize> {
+    Finder::new(needle).find_all_in(haystack)
+}
+
+///
+/// Check if the data matches the pattern, wrt the bits set in the mask, ie a mask byte
+/// of 0xFF needs a exact match, while 0x00 is a dont care byte.
+///
+/// The data, pattern and mask should all be of the same length, else it is not a match.
+///
+pub fn match_masked(data: &[u8], pattern: &[u8], mask: &[u8]) -> bool {
+    if data.len() != pattern.len() || pattern.len() != mask.len() {
+        return false;
+    }
+    data.iter().zip(pattern).zip(mask).all(|((d, p), m)| (d & m) == (p & m))
+}
+
+///
+/// Compile a textual pattern like "DE AD ?? EF" into the pattern and mask bytes used
+/// by match_masked. Each byte is given by 2 hex digits, with ? being a dont care digit,
+/// so D? matches 0xD0 to 0xDF. Whitespace btw the bytes is optional and ignored.
+///
+pub fn parse_masked_pattern(spattern: &str) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let vdigits: Vec<char> = spattern.chars().filter(|c| !c.is_whitespace()).collect();
+    if !vdigits.len().is_multiple_of(2) {
+        return Err(format!("ERRR:DU:Bytes:ParseMaskedPattern:{}:Odd number of digits", spattern));
+    }
+    let mut vpattern = Vec::with_capacity(vdigits.len()/2);
+    let mut vmask = Vec::with_capacity(vdigits.len()/2);
+    for pair in vdigits.chunks(2) {
+        let (mut pbyte, mut mbyte) = (0u8, 0u8);
+        for c in pair {
+            let (p, m) = match c {
+                '?' => (0, 0),
+                _ => match c.to_digit(16) {
+                    Some(d) => (d as u8, 0xF),
+                    None => return Err(format!("ERRR:DU:Bytes:ParseMaskedPattern:{}:Invalid char {}", spattern, c)),
+                },
+            };
+            pbyte = (pbyte << 4) | p;
+            mbyte = (mbyte << 4) | m;
+        }
+        vpattern.push(pbyte);
+        vmask.push(mbyte);
+    }
+    Ok((vpattern, vmask))
+}
@@ -0,0 +1,147 @@
+//!
+//! Checksums over byte buffers
+//!
+//! Each checksum is available has a one-shot function, as well as a struct,
+//! which allows the checksum to be calculated incrementally, ie
+//! new -> update (one or more times) -> value.
+//!
+//! HanishKVC, 2022
+//!
+
+
+const ADLER32_MOD: u32 = 65521;
+
+/// Incremental Adler-32 checksum (as used by zlib)
+#[derive(Debug, Clone)]
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Default for Adler32 {
+    fn default() -> Self {
+        Adler32 { a: 1, b: 0 }
+    }
+}
+
+impl Adler32 {
+
+    pub fn new() -> Adler32 {
+        Adler32::default()
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        // 5552 is the largest n such that the sums dont overflow u32, before taking modulo
+        for chunk in data.chunks(5552) {
+            for d in chunk {
+                self.a += *d as u32;
+                self.b += self.a;
+            }
+            self.a %= ADLER32_MOD;
+            self.b %= ADLER32_MOD;
+        }
+    }
+
+    pub fn value(&self) -> u32 {
+        (self.b << 16) | self.a
+    }
+
+}
+
+/// Adler-32 checksum of the given data
+pub fn adler32(data: &[u8]) -> u32 {
+    let mut ck = Adler32::new();
+    ck.update(data);
+    ck.value()
+}
+
+/// Incremental Fletcher-16 checksum, over bytes
+#[derive(Debug, Clone, Default)]
+pub struct Fletcher16 {
+    sum1: u16,
+    sum2: u16,
+}
+
+impl Fletcher16 {
+
+    pub fn new() -> Fletcher16 {
+        Fletcher16::default()
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for d in data {
+            self.sum1 = ((self.sum1 as u32 + *d as u32) % 255) as u16;
+            self.sum2 = ((self.sum2 as u32 + self.sum1 as u32) % 255) as u16;
+        }
+    }
+
+    pub fn value(&self) -> u16 {
+        (self.sum2 << 8) | self.sum1
+    }
+
+}
+
+/// Fletcher-16 checksum of the given data
+pub fn fletcher16(data: &[u8]) -> u16 {
+    let mut ck = Fletcher16::new();
+    ck.update(data);
+    ck.value()
+}
+
+/// Incremental Fletcher-32 checksum, over little endian 16 bit words.
+///
+/// If the total data has a odd number of bytes, its padded with a 0 byte.
+/// The data can be split at any byte boundry across the update calls.
+#[derive(Debug, Clone, Default)]
+pub struct Fletcher32 {
+    sum1: u32,
+    sum2: u32,
+    pending: Option<u8>,
+}
+
+impl Fletcher32 {
+
+    pub fn new() -> Fletcher32 {
+        Fletcher32::default()
+    }
+
+    fn update_word(&mut self, word: u16) {
+        self.sum1 = (self.sum1 + word as u32) % 65535;
+        self.sum2 = (self.sum2 + self.sum1) % 65535;
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        let mut data = data;
+        if let Some(lo) = self.pending {
+            match data.split_first() {
+                Some((hi, rest)) => {
+                    self.update_word(u16::from_le_bytes([lo, *hi]));
+                    self.pending = None;
+                    data = rest;
+                }
+                None => return,
+            }
+        }
+        let words = data.chunks_exact(2);
+        self.pending = words.remainder().first().copied();
+        for w in words {
+            self.update_word(u16::from_le_bytes([w[0], w[1]]));
+        }
+    }
+
+    pub fn value(&self) -> u32 {
+        let mut ck = self.clone();
+        if let Some(lo) = ck.pending.take() {
+            ck.update_word(lo as u16);
+        }
+        (ck.sum2 << 16) | ck.sum1
+    }
+
+}
+
+/// Fletcher-32 checksum of the given data
+pub fn fletcher32(data: &[u8]) -> u32 {
+    let mut ck = Fletcher32::new();
+    ck.update(data);
+    ck.value()
+}
@@ -0,0 +1,74 @@
+//!
+//! A minimal clock abstraction, so that the time dependent logics (like the
+//! XTimeStamp variant) can be driven by a caller controlled clock, in tests
+//! and to reproduce a given run.
+//!
+//! HanishKVC, 2022
+//!
+
+use core::cell::Cell;
+#[cfg(feature = "std")]
+use std::time;
+
+
+/// A source of the current time, has milliseconds since UnixEpoch
+pub trait Clock {
+
+    fn now_ms(&self) -> u128;
+
+}
+
+///
+/// The system (wall) clock.
+/// NOTE: Without std, there is no clock available, so it is always 0.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+
+    fn now_ms(&self) -> u128 {
+        #[cfg(feature = "std")]
+        {
+            time::SystemTime::now().duration_since(time::UNIX_EPOCH).map(|ts| ts.as_millis()).unwrap_or(0)
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            0
+        }
+    }
+
+}
+
+///
+/// A clock which only moves when told to, so that time dependent behaviour can be
+/// tested deterministically. It can be set/advanced through a shared reference.
+///
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    ms: Cell<u128>,
+}
+
+impl ManualClock {
+
+    pub fn new(ms: u128) -> ManualClock {
+        ManualClock { ms: Cell::new(ms) }
+    }
+
+    pub fn set(&self, ms: u128) {
+        self.ms.set(ms);
+    }
+
+    pub fn advance(&self, ms: u128) {
+        self.ms.set(self.ms.get() + ms);
+    }
+
+}
+
+impl Clock for ManualClock {
+
+    fn now_ms(&self) -> u128 {
+        self.ms.get()
+    }
+
+}
@@ -0,0 +1,195 @@
+//!
+//! Encode/Decode binary data using a given alphabet, like base32, base58, base64
+//! or a custom alphabet used by some protocol.
+//!
+//! HanishKVC, 2022
+//!
+
+#[allow(unused_imports)]
+use crate::alloc_prelude::*;
+
+
+/// Marks the ascii chars which are not part of the alphabet, in the decode table
+const INVALID: u8 = 0xFF;
+
+///
+/// A encoder/decoder for the given alphabet of ascii chars, with the index of a char
+/// in the alphabet being its digit value.
+///
+/// * If the alphabet size is a power of 2 (like 32 or 64), the data is treated has a bit
+///   stream (msb first) with each char encoding log2(size) bits, like the rfc 4648 base32
+///   and base64 encodings, but without any padding chars. The unused bits of the last char
+///   are zero.
+/// * Else the data is treated has a big endian number which is converted to the radix,
+///   with each leading zero byte encoded has the first char of the alphabet, like base58.
+///
+/// Refer to ignore_case and with_alias, for alphabets like crockford base32.
+///
+#[derive(Debug, Clone)]
+pub struct BaseN {
+    alphabet: Vec<u8>,
+    decode_table: [u8; 128],
+    /// log2 of the alphabet size, if it is a power of 2
+    bits: Option<u32>,
+}
+
+impl BaseN {
+
+    /// The alphabet should consist of atleast 2 and atmost 128 unique ascii chars
+    pub fn new(alphabet: &str) -> Result<BaseN, String> {
+        let alphabet = alphabet.as_bytes().to_vec();
+        if alphabet.len() < 2 || alphabet.len() > 128 {
+            return Err(format!("ERRR:DU:BaseN:New:Alphabet size {} not in 2..=128", alphabet.len()));
+        }
+        let mut decode_table = [INVALID; 128];
+        for (i, c) in alphabet.iter().enumerate() {
+            if !c.is_ascii() || (decode_table[*c as usize] != INVALID) {
+                return Err(format!("ERRR:DU:BaseN:New:Alphabet char 0x{:02X} at {} is not ascii or is repeated", c, i));
+            }
+            decode_table[*c as usize] = i as u8;
+        }
+        let bits = if alphabet.len().is_power_of_two() { Some(alphabet.len().trailing_zeros()) } else { None };
+        Ok(BaseN { alphabet, decode_table, bits })
+    }
+
+    /// Allow the chars to be in either case, when decoding
+    pub fn ignore_case(mut self) -> Self {
+        for c in 0..128u8 {
+            let digit = self.decode_table[c as usize];
+            if digit != INVALID {
+                let other = if c.is_ascii_lowercase() { c.to_ascii_uppercase() } else { c.to_ascii_lowercase() };
+                if self.decode_table[other as usize] == INVALID {
+                    self.decode_table[other as usize] = digit;
+                }
+            }
+        }
+        self
+    }
+
+    /// Decode the given alias char has the given actual char of the alphabet, like
+    /// O has 0 in crockford base32. Its ignored if actual is not in the alphabet.
+    pub fn with_alias(mut self, alias: char, actual: char) -> Self {
+        if alias.is_ascii() && actual.is_ascii() {
+            let digit = self.decode_table[actual as usize];
+            if digit != INVALID {
+                self.decode_table[alias as usize] = digit;
+            }
+        }
+        self
+    }
+
+    /// Crockford's base32, which decodes case insensitively with I and L has 1 and O has 0
+    pub fn crockford32() -> BaseN {
+        let b32 = BaseN::new("0123456789ABCDEFGHJKMNPQRSTVWXYZ").unwrap();
+        b32.ignore_case().with_alias('I', '1').with_alias('i', '1').with_alias('L', '1').with_alias('l', '1').with_alias('O', '0').with_alias('o', '0')
+    }
+
+    pub fn encode(&self, data: &[u8]) -> String {
+        let vdigits = match self.bits {
+            Some(bits) => self.encode_bits(data, bits),
+            None => self.encode_radix(data),
+        };
+        vdigits.iter().map(|d| self.alphabet[*d as usize] as char).collect()
+    }
+
+    fn encode_bits(&self, data: &[u8], bits: u32) -> Vec<u8> {
+        let mask = (1u32 << bits) - 1;
+        let mut vdigits = Vec::with_capacity((data.len()*8).div_ceil(bits as usize));
+        let (mut acc, mut nbits) = (0u32, 0u32);
+        for b in data {
+            acc = (acc << 8) | *b as u32;
+            nbits += 8;
+            while nbits >= bits {
+                nbits -= bits;
+                vdigits.push(((acc >> nbits) & mask) as u8);
+            }
+            acc &= (1 << nbits) - 1;
+        }
+        if nbits > 0 {
+            vdigits.push(((acc << (bits - nbits)) & mask) as u8);
+        }
+        vdigits
+    }
+
+    fn encode_radix(&self, data: &[u8]) -> Vec<u8> {
+        let radix = self.alphabet.len() as u32;
+        let nzeros = data.iter().take_while(|b| **b == 0).count();
+        // the digits, least significant first
+        let mut vdigits: Vec<u8> = Vec::new();
+        for b in &data[nzeros..] {
+            let mut carry = *b as u32;
+            for d in vdigits.iter_mut() {
+                carry += (*d as u32) << 8;
+                *d = (carry % radix) as u8;
+                carry /= radix;
+            }
+            while carry > 0 {
+                vdigits.push((carry % radix) as u8);
+                carry /= radix;
+            }
+        }
+        vdigits.extend(core::iter::repeat_n(0, nzeros));
+        vdigits.reverse();
+        vdigits
+    }
+
+    pub fn decode(&self, sin: &str) -> Result<Vec<u8>, String> {
+        let mut vdigits = Vec::with_capacity(sin.len());
+        for (i, c) in sin.chars().enumerate() {
+            let digit = if c.is_ascii() { self.decode_table[c as usize] } else { INVALID };
+            if digit == INVALID {
+                return Err(format!("ERRR:DU:BaseN:Decode:{}:Invalid char {} at {}", sin, c, i));
+            }
+            vdigits.push(digit);
+        }
+        match self.bits {
+            Some(bits) => self.decode_bits(sin, &vdigits, bits),
+            None => Ok(self.decode_radix(&vdigits)),
+        }
+    }
+
+    fn decode_bits(&self, sin: &str, vdigits: &[u8], bits: u32) -> Result<Vec<u8>, String> {
+        let mut vbytes = Vec::with_capacity(vdigits.len()*bits as usize/8);
+        let (mut acc, mut nbits) = (0u32, 0u32);
+        for d in vdigits {
+            acc = (acc << bits) | *d as u32;
+            nbits += bits;
+            if nbits >= 8 {
+                nbits -= 8;
+                vbytes.push((acc >> nbits) as u8);
+            }
+            acc &= (1 << nbits) - 1;
+        }
+        // The encoder never generates a char which doesnt contribute to a byte
+        if nbits >= bits {
+            return Err(format!("ERRR:DU:BaseN:Decode:{}:Invalid length {}", sin, vdigits.len()));
+        }
+        if acc != 0 {
+            return Err(format!("ERRR:DU:BaseN:Decode:{}:Non zero trailing bits", sin));
+        }
+        Ok(vbytes)
+    }
+
+    fn decode_radix(&self, vdigits: &[u8]) -> Vec<u8> {
+        let radix = self.alphabet.len() as u32;
+        let nzeros = vdigits.iter().take_while(|d| **d == 0).count();
+        // the bytes, least significant first
+        let mut vbytes: Vec<u8> = Vec::new();
+        for d in &vdigits[nzeros..] {
+            let mut carry = *d as u32;
+            for b in vbytes.iter_mut() {
+                carry += *b as u32 * radix;
+                *b = carry as u8;
+                carry >>= 8;
+            }
+            while carry > 0 {
+                vbytes.push(carry as u8);
+                carry >>= 8;
+            }
+        }
+        vbytes.extend(core::iter::repeat_n(0, nzeros));
+        vbytes.reverse();
+        vbytes
+    }
+
+}
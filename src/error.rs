@@ -0,0 +1,59 @@
+//!
+//! The crate level error type, returned by the panic free variants of the logics,
+//! which were originally written to panic on bad input data.
+//!
+//! HanishKVC, 2022
+//!
+
+use core::fmt::Display;
+use core::num::ParseIntError;
+
+#[allow(unused_imports)]
+use crate::alloc_prelude::*;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataUtilsError {
+    /// The given text couldnt be parsed has a integer
+    ParseInt { input: String, reason: String },
+    /// The given text couldnt be parsed has a float
+    ParseFloat { input: String, reason: String },
+    /// The given text couldnt be parsed has hex data
+    ParseHex { input: String, reason: String },
+    /// The value doesnt fit within the target type
+    OutOfRange { value: String, target: String },
+    /// The index is beyond the data available
+    IndexOutOfBounds { index: usize, len: usize },
+    /// Any other invalid input
+    Invalid(String),
+}
+
+impl Display for DataUtilsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DataUtilsError::ParseInt { input, reason } => write!(f, "ERRR:DU:ParseInt:[{}]:{}", input, reason),
+            DataUtilsError::ParseFloat { input, reason } => write!(f, "ERRR:DU:ParseFloat:[{}]:{}", input, reason),
+            DataUtilsError::ParseHex { input, reason } => write!(f, "ERRR:DU:ParseHex:[{}]:{}", input, reason),
+            DataUtilsError::OutOfRange { value, target } => write!(f, "ERRR:DU:OutOfRange:{} beyond {} range", value, target),
+            DataUtilsError::IndexOutOfBounds { index, len } => write!(f, "ERRR:DU:IndexOutOfBounds:Index {}, available length {}", index, len),
+            DataUtilsError::Invalid(msg) => write!(f, "ERRR:DU:Invalid:{}", msg),
+        }
+    }
+}
+
+impl core::error::Error for DataUtilsError {}
+
+/// Allow use of ? within the logics, which return String errors
+impl From<DataUtilsError> for String {
+    fn from(err: DataUtilsError) -> String {
+        err.to_string()
+    }
+}
+
+impl DataUtilsError {
+
+    pub(crate) fn parse_int(input: &str, err: ParseIntError) -> DataUtilsError {
+        DataUtilsError::ParseInt { input: input.to_string(), reason: err.to_string() }
+    }
+
+}
@@ -0,0 +1,154 @@
+//!
+//! Named bit flags, to help decode/encode protocol flag fields into/from a
+//! readable form like "ACK|SYN".
+//!
+//! HanishKVC, 2022
+//!
+
+use core::fmt::Display;
+
+#[allow(unused_imports)]
+use crate::alloc_prelude::*;
+
+
+///
+/// A set of named bit flags, within a integer of the given width (in bits),
+/// along with the current value.
+///
+/// Set bits which dont have a name, are rendered has a hex value.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlagSet {
+    /// name and bit position, sorted wrt the bit position
+    vnames: Vec<(String, u32)>,
+    width: u32,
+    value: u64,
+}
+
+impl FlagSet {
+
+    ///
+    /// Create a flag set with the given name to bit position mapping, with all flags cleared.
+    ///
+    /// width is the size in bits of the integer holding the flags, has in the protocol field.
+    ///
+    pub fn new(vnamebits: &[(&str, u32)], width: u32) -> Result<FlagSet, String> {
+        if width == 0 || width > 64 {
+            return Err(format!("ERRR:DU:FlagSet:New:Invalid width {}", width));
+        }
+        let mut vnames: Vec<(String, u32)> = Vec::new();
+        for (name, bit) in vnamebits {
+            if *bit >= width {
+                return Err(format!("ERRR:DU:FlagSet:New:Bit {} of {} beyond width {}", bit, name, width));
+            }
+            if name.is_empty() || name.contains('|') || name.trim() != *name {
+                return Err(format!("ERRR:DU:FlagSet:New:Invalid name [{}]", name));
+            }
+            if vnames.iter().any(|(n, b)| (n == name) || (b == bit)) {
+                return Err(format!("ERRR:DU:FlagSet:New:Duplicate name or bit {}:{}", name, bit));
+            }
+            vnames.push((name.to_string(), *bit));
+        }
+        vnames.sort_by_key(|(_, bit)| *bit);
+        Ok(FlagSet { vnames, width, value: 0 })
+    }
+
+    fn mask(&self) -> u64 {
+        if self.width == 64 {
+            u64::MAX
+        } else {
+            (1u64 << self.width) - 1
+        }
+    }
+
+    fn bit_of(&self, name: &str) -> Result<u32, String> {
+        match self.vnames.iter().find(|(n, _)| n == name) {
+            Some((_, bit)) => Ok(*bit),
+            None => Err(format!("ERRR:DU:FlagSet:Unknown flag [{}]", name)),
+        }
+    }
+
+    /// The current value has a integer
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// Set the current value, it should fit within the width of the flag set
+    pub fn set_value(&mut self, value: u64) -> Result<(), String> {
+        if value & !self.mask() != 0 {
+            return Err(format!("ERRR:DU:FlagSet:SetValue:Value 0x{:X} doesnt fit in {} bits", value, self.width));
+        }
+        self.value = value;
+        Ok(())
+    }
+
+    /// Set the named flag
+    pub fn set(&mut self, name: &str) -> Result<(), String> {
+        self.value |= 1 << self.bit_of(name)?;
+        Ok(())
+    }
+
+    /// Clear the named flag
+    pub fn clear(&mut self, name: &str) -> Result<(), String> {
+        self.value &= !(1 << self.bit_of(name)?);
+        Ok(())
+    }
+
+    /// Check if the named flag is set
+    pub fn is_set(&self, name: &str) -> Result<bool, String> {
+        Ok(self.value & (1 << self.bit_of(name)?) != 0)
+    }
+
+    /// Names of the flags which are set
+    pub fn names(&self) -> Vec<&str> {
+        self.vnames.iter().filter(|(_, bit)| self.value & (1 << bit) != 0).map(|(n, _)| n.as_str()).collect()
+    }
+
+    ///
+    /// Set the current value from the given string of | seperated flag names
+    /// like "ACK|SYN". Hex (0x prefixed) or decimal values are also allowed
+    /// has items, and a empty string (or 0) means no flags set.
+    ///
+    pub fn parse(&mut self, s: &str) -> Result<(), String> {
+        let mut value = 0u64;
+        for item in s.split('|') {
+            let item = item.trim();
+            if item.is_empty() {
+                if s.trim().is_empty() {
+                    continue;
+                }
+                return Err(format!("ERRR:DU:FlagSet:Parse:Empty item in [{}]", s));
+            }
+            if item.starts_with(|c: char| c.is_ascii_digit()) {
+                // from_str_radix/parse allow a leading + sign, so check for the digits explicitly
+                let ival = match item.strip_prefix("0x").or_else(|| item.strip_prefix("0X")) {
+                    Some(shex) if shex.bytes().all(|c| c.is_ascii_hexdigit()) => u64::from_str_radix(shex, 16).map_err(|e| e.to_string()),
+                    Some(_) => Err("Invalid hex digit".to_string()),
+                    None => item.parse::<u64>().map_err(|e| e.to_string()),
+                };
+                value |= ival.map_err(|e| format!("ERRR:DU:FlagSet:Parse:Invalid value [{}]:{}", item, e))?;
+                continue;
+            }
+            value |= 1 << self.bit_of(item)?;
+        }
+        self.set_value(value)
+    }
+
+}
+
+impl Display for FlagSet {
+    /// Render the set flags by name, seperated by |, any unnamed set bits are
+    /// rendered has a single hex value at the end. If no flags are set, its 0.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut vparts: Vec<String> = self.names().iter().map(|n| n.to_string()).collect();
+        let named = self.vnames.iter().fold(0u64, |acc, (_, bit)| acc | (1 << bit));
+        let unnamed = self.value & !named;
+        if unnamed != 0 {
+            vparts.push(format!("0x{:X}", unnamed));
+        }
+        if vparts.is_empty() {
+            return write!(f, "0");
+        }
+        write!(f, "{}", vparts.join("|"))
+    }
+}
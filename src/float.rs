@@ -0,0 +1,67 @@
+//!
+//! Some Float data related utility functions
+//!
+//! HanishKVC, 2022
+//!
+
+use crate::error::DataUtilsError;
+#[allow(unused_imports)]
+use crate::alloc_prelude::*;
+
+
+///
+/// Convert given string value to a f64, after trimming it. Supports decimal values
+/// with optional exponent (like 1.5e3), along with inf and nan.
+///
+pub fn floatvalue(sval: &str) -> Result<f64, DataUtilsError> {
+    sval.trim().parse::<f64>().map_err(|e| DataUtilsError::ParseFloat { input: sval.to_string(), reason: e.to_string() })
+}
+
+///
+/// Convert given string value, which could have a unit suffix (like 2.5ms), into its
+/// f64 value and the unit. Whitespace btw the value and the unit is allowed.
+///
+/// * if units is not empty, the suffix (if any) should be one of the given units,
+///   the longest matching unit is used. ex parse_unit_f64("2.5ms", &["s", "ms", "%"])
+/// * if units is empty, any suffix which doesnt belong to the value is taken has the unit.
+///
+/// A value without a unit suffix gives a empty unit. The value is not scaled wrt the unit.
+/// inf and nan are only accepted by themselves, so that words like nanoseconds or
+/// information are not misread has a value followed by a unit.
+///
+pub fn parse_unit_f64(sval: &str, units: &[&str]) -> Result<(f64, String), DataUtilsError> {
+    let strimmed = sval.trim();
+    let err = |reason: &str| DataUtilsError::ParseFloat { input: sval.to_string(), reason: reason.to_string() };
+    if let Ok(fval) = strimmed.parse::<f64>() {
+        return Ok((fval, String::new()));
+    }
+    if !units.is_empty() {
+        let mut vunits = units.to_vec();
+        vunits.sort_by_key(|u| core::cmp::Reverse(u.len()));
+        for unit in vunits {
+            if let Some(snum) = strimmed.strip_suffix(unit) {
+                if let Some(fval) = unit_value(snum) {
+                    return Ok((fval, unit.to_string()));
+                }
+            }
+        }
+        return Err(err("Not a value with one of the allowed units"));
+    }
+    // Find the longest prefix which is a valid value
+    for (i, _) in strimmed.char_indices().rev() {
+        let (snum, sunit) = strimmed.split_at(i);
+        if let Some(fval) = unit_value(snum) {
+            return Ok((fval, sunit.to_string()));
+        }
+    }
+    Err(err("No value found"))
+}
+
+/// Parse the value part before a unit, which should end in a digit or a . (so not inf/nan)
+fn unit_value(snum: &str) -> Option<f64> {
+    let snum = snum.trim_end();
+    if !snum.ends_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    snum.parse::<f64>().ok()
+}
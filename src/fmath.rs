@@ -0,0 +1,57 @@
+//!
+//! The float math functions used by the crate, which come from std normally,
+//! and from libm when building has no_std.
+//!
+//! HanishKVC, 2022
+//!
+
+
+macro_rules! fmath_fn {
+    ($name:ident, $t:ty, $libm:ident) => {
+        #[inline]
+        pub(crate) fn $name(x: $t) -> $t {
+            #[cfg(feature = "std")]
+            {
+                x.$name()
+            }
+            #[cfg(not(feature = "std"))]
+            {
+                libm::$libm(x)
+            }
+        }
+    };
+    ($name:ident, $t:ty, $libm:ident, $other:ident) => {
+        #[inline]
+        pub(crate) fn $name(x: $t, $other: $t) -> $t {
+            #[cfg(feature = "std")]
+            {
+                x.$name($other)
+            }
+            #[cfg(not(feature = "std"))]
+            {
+                libm::$libm(x, $other)
+            }
+        }
+    };
+}
+
+pub(crate) mod f32 {
+    fmath_fn!(exp, f32, expf);
+    fmath_fn!(round, f32, roundf);
+    fmath_fn!(hypot, f32, hypotf, y);
+    // NOTE: Has in std, atan2(y, x) ie the y part comes first
+    fmath_fn!(atan2, f32, atan2f, other);
+}
+
+pub(crate) mod f64 {
+    fmath_fn!(sqrt, f64, sqrt);
+    fmath_fn!(log10, f64, log10);
+    fmath_fn!(log2, f64, log2);
+    fmath_fn!(sin, f64, sin);
+    fmath_fn!(cos, f64, cos);
+    fmath_fn!(floor, f64, floor);
+    fmath_fn!(ceil, f64, ceil);
+    fmath_fn!(ln, f64, log);
+    fmath_fn!(exp, f64, exp);
+    fmath_fn!(powf, f64, pow, n);
+}
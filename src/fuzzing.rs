@@ -0,0 +1,70 @@
+//!
+//! Fuzz entry points for the parsers of the crate, used by the cargo-fuzz
+//! targets under fuzz/. Each entry takes arbitrary bytes, feeds them to the
+//! corresponding parser(s) and panics if any of the expected invariants
+//! (like round trips) doesnt hold. The parsers themselves shouldnt panic.
+//!
+//! HanishKVC, 2022
+//!
+
+use crate::hex;
+use crate::integer;
+use crate::text;
+use crate::variant::Variant;
+
+
+/// Fuzz vu8_from_hex, and the round trip through hex_from_vu8
+pub fn fuzz_entry_hex(data: &[u8]) {
+    let sdata = String::from_utf8_lossy(data);
+    if let Ok(vbuf) = hex::vu8_from_hex(&sdata) {
+        assert_eq!(vbuf.len()*2, sdata.len());
+        assert_eq!(hex::hex_from_vu8(&vbuf), sdata.to_uppercase());
+    }
+    let shex = hex::hex_from_vu8(data);
+    assert_eq!(hex::vu8_from_hex(&shex).as_deref(), Ok(data));
+}
+
+/// Fuzz the tokenizer (lenient, checked and ref forms) and next_token
+pub fn fuzz_entry_token(data: &[u8]) {
+    let sdata = String::from_utf8_lossy(data);
+    let _ = text::next_token(&sdata);
+    let _ = text::next_token_checked(&sdata);
+    let _ = text::next_token_ref(&sdata);
+    let mut tk = text::Tokenizer::new(&sdata);
+    let mut prevlen = sdata.len() + 1;
+    while let Ok(Some(tok)) = tk.next_checked() {
+        assert!(tok.span.end <= sdata.len());
+        assert!(tok.span.start <= tok.span.end);
+        assert!(tk.remaining().len() < prevlen, "tokenizer not making progress");
+        prevlen = tk.remaining().len();
+    }
+    for tok in text::Tokenizer::new(&sdata) {
+        assert!(tok.span.end <= sdata.len());
+    }
+    let mut tk = text::Tokenizer::new(&sdata);
+    while let Ok(Some(stok)) = tk.next_ref() {
+        assert!(stok.len() <= sdata.len());
+    }
+    if let Ok(sraw) = text::unescape_str(&sdata) {
+        assert_eq!(text::unescape_str(&text::escape_str(&sraw)).as_deref(), Ok(sraw.as_str()));
+    }
+}
+
+/// Fuzz Variant::from_literal and try_intvalue
+pub fn fuzz_entry_variant(data: &[u8]) {
+    let sdata = String::from_utf8_lossy(data);
+    let _ = integer::try_intvalue::<isize>(&sdata);
+    let _ = integer::try_intvalue::<u8>(&sdata);
+    if let Ok(v) = Variant::from_literal(&sdata) {
+        let _ = v.get_isize();
+        let _ = v.get_string();
+        let vbuf = v.get_bufvu8();
+        for i in 0..vbuf.len().min(4) {
+            assert!(v.try_get_byteelement(i).is_ok());
+        }
+        assert!(v.try_get_byteelement(vbuf.len()).is_err());
+        if let Variant::IntValue(ival) = v {
+            assert_eq!(Variant::from_literal(&ival.to_string()).map(|x| x.get_isize().unwrap()), Ok(ival));
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//!
+//! Fast non-cryptographic hashes over byte buffers, usable has dedup keys and so.
+//!
+//! NOTE: These are not suitable where cryptographic strength is required.
+//!
+//! HanishKVC, 2022
+//!
+
+
+const FNV32_OFFSET: u32 = 0x811C9DC5;
+const FNV32_PRIME: u32 = 0x01000193;
+const FNV64_OFFSET: u64 = 0xCBF29CE484222325;
+const FNV64_PRIME: u64 = 0x00000100000001B3;
+
+/// 32 bit FNV-1a hash of the given data
+pub fn fnv1a_32(data: &[u8]) -> u32 {
+    let mut h = FNV32_OFFSET;
+    for d in data {
+        h ^= *d as u32;
+        h = h.wrapping_mul(FNV32_PRIME);
+    }
+    h
+}
+
+/// 64 bit FNV-1a hash of the given data
+pub fn fnv1a_64(data: &[u8]) -> u64 {
+    let mut h = FNV64_OFFSET;
+    for d in data {
+        h ^= *d as u64;
+        h = h.wrapping_mul(FNV64_PRIME);
+    }
+    h
+}
+
+const XXH_P1: u64 = 0x9E3779B185EBCA87;
+const XXH_P2: u64 = 0xC2B2AE3D27D4EB4F;
+const XXH_P3: u64 = 0x165667B19E3779F9;
+const XXH_P4: u64 = 0x85EBCA77C2B2AE63;
+const XXH_P5: u64 = 0x27D4EB2F165667C5;
+
+fn xxh64_round(acc: u64, input: u64) -> u64 {
+    acc.wrapping_add(input.wrapping_mul(XXH_P2)).rotate_left(31).wrapping_mul(XXH_P1)
+}
+
+fn xxh64_merge(acc: u64, val: u64) -> u64 {
+    (acc ^ xxh64_round(0, val)).wrapping_mul(XXH_P1).wrapping_add(XXH_P4)
+}
+
+fn read_u64(data: &[u8]) -> u64 {
+    u64::from_le_bytes(data[..8].try_into().unwrap())
+}
+
+fn read_u32(data: &[u8]) -> u64 {
+    u32::from_le_bytes(data[..4].try_into().unwrap()) as u64
+}
+
+/// 64 bit hash of the given data with the given seed, as per the XXH64 algorithm.
+pub fn xxh64(data: &[u8], seed: u64) -> u64 {
+    let mut rem = data;
+    let mut h;
+    if data.len() >= 32 {
+        let mut v = [
+            seed.wrapping_add(XXH_P1).wrapping_add(XXH_P2),
+            seed.wrapping_add(XXH_P2),
+            seed,
+            seed.wrapping_sub(XXH_P1),
+        ];
+        let stripes = data.chunks_exact(32);
+        rem = stripes.remainder();
+        for stripe in stripes {
+            for (i, vi) in v.iter_mut().enumerate() {
+                *vi = xxh64_round(*vi, read_u64(&stripe[i*8..]));
+            }
+        }
+        h = v[0].rotate_left(1)
+            .wrapping_add(v[1].rotate_left(7))
+            .wrapping_add(v[2].rotate_left(12))
+            .wrapping_add(v[3].rotate_left(18));
+        for vi in v {
+            h = xxh64_merge(h, vi);
+        }
+    } else {
+        h = seed.wrapping_add(XXH_P5);
+    }
+    h = h.wrapping_add(data.len() as u64);
+    while rem.len() >= 8 {
+        h ^= xxh64_round(0, read_u64(rem));
+        h = h.rotate_left(27).wrapping_mul(XXH_P1).wrapping_add(XXH_P4);
+        rem = &rem[8..];
+    }
+    if rem.len() >= 4 {
+        h ^= read_u32(rem).wrapping_mul(XXH_P1);
+        h = h.rotate_left(23).wrapping_mul(XXH_P2).wrapping_add(XXH_P3);
+        rem = &rem[4..];
+    }
+    for d in rem {
+        h ^= (*d as u64).wrapping_mul(XXH_P5);
+        h = h.rotate_left(11).wrapping_mul(XXH_P1);
+    }
+    h ^= h >> 33;
+    h = h.wrapping_mul(XXH_P2);
+    h ^= h >> 29;
+    h = h.wrapping_mul(XXH_P3);
+    h ^= h >> 32;
+    h
+}
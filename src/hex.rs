@@ -4,6 +4,8 @@
 //! HanishKVC, 2022
 //!
 
+#[allow(unused_imports)]
+use crate::alloc_prelude::*;
 
 
 ///
@@ -18,8 +20,15 @@ pub fn vu8_from_hex(ins: &str) -> Result<Vec<u8>, String> {
     if ins.len() % 2 != 0 {
         return Err("ERRR:DU:Vu8FromHex:Hex string length not even, something wrong???".to_string());
     }
+    if !ins.is_ascii() {
+        return Err(format!("ERRR:DU:Vu8FromHex:{}:Non ascii chars in hex string", ins));
+    }
     let mut vu8 = Vec::new();
-    for i in (0..ins.len()-1).step_by(2) {
+    for i in (0..ins.len()).step_by(2) {
+        // from_str_radix allows a leading + sign, so check for hex digits explicitly
+        if !ins[i..i+2].bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(format!("ERRR:DU:VU8FromHex:{}>>{}<<:Invalid hex digit", ins, &ins[i..i+2]));
+        }
         let cu8 = u8::from_str_radix(&ins[i..i+2], 16);
         if cu8.is_err() {
             return Err(format!("ERRR:DU:VU8FromHex:{}>>{}<<:{}", ins, &ins[i..i+2], cu8.unwrap_err()));
@@ -30,13 +39,13 @@ pub fn vu8_from_hex(ins: &str) -> Result<Vec<u8>, String> {
 }
 
 ///
-/// Convert Vec<u8> to hex string
+/// Convert a byte buffer (Vec<u8>, slice, array, ...) to hex string
 ///
-pub fn hex_from_vu8(inv: &Vec<u8>) -> String {
-    let hex = vec!["0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "A", "B", "C", "D", "E", "F"];
-    let mut outs = String::new();
-    for i in 0..inv.len() {
-        let cu8 = inv[i];
+pub fn hex_from_vu8<B: AsRef<[u8]>>(inv: B) -> String {
+    let hex = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "A", "B", "C", "D", "E", "F"];
+    let inv = inv.as_ref();
+    let mut outs = String::with_capacity(inv.len()*2);
+    for &cu8 in inv {
         let bhigh = (cu8 & 0xF0) >> 4;
         let blow = cu8 & 0x0F;
         //log_d(&format!("DBUG:DU:HexFromVU8:{}+{}+{}", outs, bhigh, blow));
@@ -46,3 +55,82 @@ pub fn hex_from_vu8(inv: &Vec<u8>) -> String {
     outs
 }
 
+
+///
+/// Convert hex string to a fixed size byte array, without any heap allocation.
+/// The hex string should have exactly 2*N hex digits.
+///
+pub fn decode_array<const N: usize>(ins: &str) -> Result<[u8; N], String> {
+    let ins = ins.as_bytes();
+    if ins.len() != N*2 {
+        return Err(format!("ERRR:DU:Hex:DecodeArray:Expected {} hex digits, got {}", N*2, ins.len()));
+    }
+    let nibble = |c: u8| match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        b'A'..=b'F' => Ok(c - b'A' + 10),
+        _ => Err(format!("ERRR:DU:Hex:DecodeArray:Invalid hex digit [{}]", c as char)),
+    };
+    let mut aout = [0u8; N];
+    for (i, b) in aout.iter_mut().enumerate() {
+        *b = (nibble(ins[i*2])? << 4) | nibble(ins[i*2+1])?;
+    }
+    Ok(aout)
+}
+
+/// A problem found in a hex string, refer to validate.
+/// The positions are byte offsets (0 based) within the string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HexIssue {
+    /// A char which is not a hex digit
+    InvalidChar { pos: usize, ch: char },
+    /// A whitespace char embedded within the hex string
+    Whitespace { pos: usize, ch: char },
+    /// The number of hex digits is not even, so the last byte is incomplete
+    OddLength { digits: usize },
+}
+
+///
+/// Check the given hex string, reporting all the problems in it in one go, rather than
+/// failing at the first problem like vu8_from_hex. A empty result means the string is
+/// valid for vu8_from_hex.
+///
+pub fn validate(ins: &str) -> Vec<HexIssue> {
+    let mut vissues = Vec::new();
+    let mut digits = 0usize;
+    for (pos, ch) in ins.char_indices() {
+        if ch.is_ascii_hexdigit() {
+            digits += 1;
+        } else if ch.is_whitespace() {
+            vissues.push(HexIssue::Whitespace { pos, ch });
+        } else {
+            vissues.push(HexIssue::InvalidChar { pos, ch });
+        }
+    }
+    if !digits.is_multiple_of(2) {
+        vissues.push(HexIssue::OddLength { digits });
+    }
+    vissues
+}
+
+/// Allow byte buffers (Vec<u8>, slices, arrays, ...) to be converted to hex string directly
+pub trait ToHex {
+    fn to_hex(&self) -> String;
+}
+
+impl<T: AsRef<[u8]> + ?Sized> ToHex for T {
+    fn to_hex(&self) -> String {
+        hex_from_vu8(self.as_ref())
+    }
+}
+
+/// Allow creating a byte buffer from a hex string
+pub trait FromHex: Sized {
+    fn from_hex(ins: &str) -> Result<Self, String>;
+}
+
+impl FromHex for Vec<u8> {
+    fn from_hex(ins: &str) -> Result<Self, String> {
+        vu8_from_hex(ins)
+    }
+}
@@ -4,35 +4,147 @@
 //! HanishKVC, 2022
 //!
 
+use core::cmp::Ordering;
 use core::convert::From;
-use std::num::ParseIntError;
+use core::fmt;
+use core::num::ParseIntError;
+use core::ops;
+use core::str::FromStr;
 
+use crate::error::DataUtilsError;
+#[allow(unused_imports)]
+use crate::alloc_prelude::*;
 
+
+///
+/// Define a minimal bounded wrapper around the given unsigned integer type, which
+/// * allows conversion from isize, only if the value fits within the wrapped type
+/// * can be displayed, parsed (decimal or 0x hex), compared and used in arithmetic
+///   with the wrapped primitive type, so that it can be used has a value by itself.
 ///
-/// Allow conversion btw isize and u8 through a minimal wrapper around u8
-/// Additionally this allows conversion only if the isize value fits within u8 space
-/// else it will panic with a error message.
-/// This also helps make intvalue generic wrt the types I want (ie isize and u8 immidiately)
+/// The arithmetic follows the wrapped primitive type's overflow behaviour.
 ///
+macro_rules! bounded_uint {
+    ($(#[$meta:meta])* $name:ident, $t:ty) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+        pub struct $name(pub $t);
 
-#[derive(Debug)]
-pub struct U8X(pub u8);
+        impl $name {
 
-impl Into<u8> for U8X {
-    fn into(self) -> u8 {
-        let U8X(u8val) = self;
-        return u8val;
-    }
+            #[doc = concat!("Convert the given isize to ", stringify!($name), ", if it fits within ", stringify!($t), " space")]
+            pub fn try_from_isize(ival: isize) -> Result<$name, DataUtilsError> {
+                match <$t>::try_from(ival) {
+                    Ok(uval) => Ok($name(uval)),
+                    Err(_) => Err(DataUtilsError::OutOfRange { value: ival.to_string(), target: stringify!($t).to_string() }),
+                }
+            }
+
+        }
+
+        impl From<$name> for $t {
+            fn from(val: $name) -> $t {
+                val.0
+            }
+        }
+
+        impl From<isize> for $name {
+            #[doc = concat!("NOTE: Panics if the value doesnt fit in ", stringify!($t), ", prefer ", stringify!($name), "::try_from_isize")]
+            fn from(ival: isize) -> Self {
+                match $name::try_from_isize(ival) {
+                    Ok(val) => val,
+                    Err(e) => panic!("ERRR:DU:{}FromISize:{}", stringify!($name), e),
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = DataUtilsError;
+
+            /// Decimal or hexadecimal (if starts with 0x) string value, refer to try_intvalue
+            fn from_str(sval: &str) -> Result<Self, Self::Err> {
+                try_intvalue::<$t>(sval).map($name)
+            }
+        }
+
+        impl PartialEq<$t> for $name {
+            fn eq(&self, other: &$t) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialOrd<$t> for $name {
+            fn partial_cmp(&self, other: &$t) -> Option<Ordering> {
+                self.0.partial_cmp(other)
+            }
+        }
+
+        bounded_uint!(@ops $name, $t, Add add, Sub sub, Mul mul, Div div, Rem rem);
+    };
+    (@ops $name:ident, $t:ty, $($tr:ident $f:ident),+) => {
+        $(
+            impl ops::$tr<$t> for $name {
+                type Output = $name;
+                fn $f(self, rhs: $t) -> $name {
+                    $name(ops::$tr::$f(self.0, rhs))
+                }
+            }
+
+            impl ops::$tr for $name {
+                type Output = $name;
+                fn $f(self, rhs: $name) -> $name {
+                    $name(ops::$tr::$f(self.0, rhs.0))
+                }
+            }
+        )+
+    };
 }
 
-impl From<isize> for U8X {
-    fn from(ival: isize) -> Self {
-        if (ival < 0) || (ival > u8::MAX.into()) {
-            panic!("ERRR:DU:U8XFromISize:isize{} beyond u8 range", ival);
+bounded_uint!(
+    ///
+    /// Allow conversion btw isize and u8 through a minimal wrapper around u8
+    /// Additionally this allows conversion only if the isize value fits within u8 space
+    /// else it will panic with a error message, use try_from_isize to avoid the panic.
+    /// This also helps make intvalue generic wrt the types I want (ie isize and u8 immidiately)
+    ///
+    U8X, u8
+);
+
+bounded_uint!(
+    /// Similar to U8X, but wrt u16
+    U16X, u16
+);
+
+bounded_uint!(
+    /// Similar to U8X, but wrt u32
+    U32X, u32
+);
+
+///
+/// Convert given string value to a isize, by treating it has a decimal
+/// or hexdecimal (if starts with 0x) string value. A sign is not allowed
+/// after the 0x prefix.
+///
+/// Inturn try convert the isize to specified type, returning a error (rather
+/// than panicing) if the value doesnt fit within the specified type.
+pub fn try_intvalue<T: TryFrom<isize>>(sval: &str) -> Result<T, DataUtilsError> {
+    let strimmed = sval.trim();
+    let ival = match strimmed.strip_prefix("0x") {
+        // from_str_radix allows a leading sign of its own
+        Some(shex) if shex.starts_with(['+', '-']) => {
+            return Err(DataUtilsError::ParseInt { input: sval.to_string(), reason: "Sign after 0x prefix".to_string() });
         }
-        let uval = ival as usize;
-        return U8X(uval as u8);
-    }
+        Some(shex) => isize::from_str_radix(shex, 16),
+        None => strimmed.parse::<isize>(),
+    };
+    let ival = ival.map_err(|e| DataUtilsError::parse_int(sval, e))?;
+    T::try_from(ival).map_err(|_| DataUtilsError::OutOfRange { value: ival.to_string(), target: core::any::type_name::<T>().to_string() })
 }
 
 ///
@@ -40,7 +152,8 @@ impl From<isize> for U8X {
 /// or hexdecimal (if starts with 0x) string value.
 ///
 /// Inturn try convert the isize to specified type.
-pub fn intvalue<T: std::convert::From<isize>>(sval: &str) -> Result<T, ParseIntError> {
+#[deprecated(since = "0.1.2", note = "use try_intvalue, which doesnt panic if the value doesnt fit the type")]
+pub fn intvalue<T: core::convert::From<isize>>(sval: &str) -> Result<T, ParseIntError> {
     let sval = sval.trim();
     let ival;
     if sval.starts_with("0x") {
@@ -50,3 +163,154 @@ pub fn intvalue<T: std::convert::From<isize>>(sval: &str) -> Result<T, ParseIntE
     }
     return Ok(T::try_from(ival).unwrap());
 }
+
+/// The details of a integer literal, refer to classify_literal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LiteralInfo {
+    /// 2, 8, 10 or 16, wrt the 0b, 0o, none or 0x prefix
+    pub radix: u32,
+    /// if the literal had a - sign or a signed type suffix (like i16)
+    pub signed: bool,
+    /// the bit width of the type suffix (like 16 for u16), if any
+    pub width_hint: Option<u32>,
+    pub value: i128,
+}
+
+/// The type suffixes supported by classify_literal, along with their signedness and width
+const LITERAL_SUFFIXES: [(&str, bool, u32); 12] = [
+    ("i8", true, 8), ("i16", true, 16), ("i32", true, 32), ("i64", true, 64), ("isize", true, isize::BITS),
+    ("i128", true, 128), ("u8", false, 8), ("u16", false, 16), ("u32", false, 32), ("u64", false, 64),
+    ("usize", false, usize::BITS), ("u128", false, 128),
+];
+
+///
+/// Classify the given integer literal, without committing to a target type.
+///
+/// * a optional + or - sign
+/// * a optional 0x, 0o or 0b (or upper case) radix prefix, else decimal
+/// * the digits, which can be seperated using _
+/// * a optional rust style type suffix like u8, i32, usize, which gives the width_hint,
+///   in which case the value should fit within that type.
+///
+/// The value should fit within i128.
+///
+pub fn classify_literal(sval: &str) -> Result<LiteralInfo, DataUtilsError> {
+    let err = |reason: &str| DataUtilsError::ParseInt { input: sval.to_string(), reason: reason.to_string() };
+    let mut rem = sval.trim();
+    let negative = rem.starts_with('-');
+    if let Some(r) = rem.strip_prefix('-').or_else(|| rem.strip_prefix('+')) {
+        rem = r;
+    }
+    let radix = match rem.get(..2).map(|p| p.to_ascii_lowercase()) {
+        Some(p) if p == "0x" => 16,
+        Some(p) if p == "0o" => 8,
+        Some(p) if p == "0b" => 2,
+        _ => 10,
+    };
+    if radix != 10 {
+        rem = &rem[2..];
+    }
+    let mut suffix = None;
+    for (sfx, signed, bits) in LITERAL_SUFFIXES {
+        // A hex digit run could end with what looks like a suffix, so require a _ before it for hex
+        if let Some(r) = rem.strip_suffix(sfx) {
+            if radix != 16 || r.ends_with('_') {
+                rem = r;
+                suffix = Some((signed, bits));
+                break;
+            }
+        }
+    }
+    let sdigits: String = rem.chars().filter(|c| *c != '_').collect();
+    if sdigits.is_empty() || rem.starts_with('_') {
+        return Err(err("No digits"));
+    }
+    // from_str_radix allows a leading sign of its own, so only allow the digits of the radix
+    if !sdigits.chars().all(|c| c.is_digit(radix)) {
+        return Err(err("Invalid digit"));
+    }
+    let magnitude = u128::from_str_radix(&sdigits, radix).map_err(|e| err(&e.to_string()))?;
+    let value = if negative {
+        0i128.checked_sub_unsigned(magnitude)
+    } else {
+        i128::try_from(magnitude).ok()
+    };
+    let value = value.ok_or_else(|| err("Beyond i128 range"))?;
+    let (signed, width_hint) = match suffix {
+        Some((ssigned, bits)) => {
+            let (min, max) = if ssigned {
+                if bits >= 128 { (i128::MIN, i128::MAX) } else { (-(1i128 << (bits-1)), (1i128 << (bits-1)) - 1) }
+            } else {
+                (0, if bits >= 128 { i128::MAX } else { (1i128 << bits) - 1 })
+            };
+            if value < min || value > max {
+                return Err(DataUtilsError::OutOfRange { value: value.to_string(), target: format!("{}{}", if ssigned { "i" } else { "u" }, bits) });
+            }
+            (ssigned || negative, Some(bits))
+        }
+        None => (negative, None),
+    };
+    Ok(LiteralInfo { radix, signed, width_hint, value })
+}
+
+/// Spread the bits of the given value, so that there is a 0 bit btw each of its bits
+fn spread_bits2(val: u32) -> u64 {
+    let mut v = val as u64;
+    v = (v | (v << 16)) & 0x0000FFFF0000FFFF;
+    v = (v | (v << 8)) & 0x00FF00FF00FF00FF;
+    v = (v | (v << 4)) & 0x0F0F0F0F0F0F0F0F;
+    v = (v | (v << 2)) & 0x3333333333333333;
+    v = (v | (v << 1)) & 0x5555555555555555;
+    v
+}
+
+/// Gather back the even bits of the given value, ie the reverse of spread_bits2
+fn gather_bits2(val: u64) -> u32 {
+    let mut v = val & 0x5555555555555555;
+    v = (v | (v >> 1)) & 0x3333333333333333;
+    v = (v | (v >> 2)) & 0x0F0F0F0F0F0F0F0F;
+    v = (v | (v >> 4)) & 0x00FF00FF00FF00FF;
+    v = (v | (v >> 8)) & 0x0000FFFF0000FFFF;
+    v = (v | (v >> 16)) & 0x00000000FFFFFFFF;
+    v as u32
+}
+
+///
+/// Interleave the bits of x and y into a Morton (Z-order) key,
+/// with x occupying the even bits and y the odd bits.
+///
+pub fn interleave2(x: u32, y: u32) -> u64 {
+    spread_bits2(x) | (spread_bits2(y) << 1)
+}
+
+/// Split a Morton key generated by interleave2 back into its (x, y)
+pub fn deinterleave2(key: u64) -> (u32, u32) {
+    (gather_bits2(key), gather_bits2(key >> 1))
+}
+
+///
+/// Interleave the bits of x, y and z into a Morton (Z-order) key, with
+/// x occupying bits 0,3,6..., y bits 1,4,7... and z bits 2,5,8...
+///
+/// A u128 is used for the key, so that the full u32 range is retained.
+///
+pub fn interleave3(x: u32, y: u32, z: u32) -> u128 {
+    let mut key = 0u128;
+    for i in 0..32 {
+        key |= (((x >> i) & 1) as u128) << (3*i);
+        key |= (((y >> i) & 1) as u128) << (3*i + 1);
+        key |= (((z >> i) & 1) as u128) << (3*i + 2);
+    }
+    key
+}
+
+/// Split a Morton key generated by interleave3 back into its (x, y, z)
+pub fn deinterleave3(key: u128) -> (u32, u32, u32) {
+    let (mut x, mut y, mut z) = (0u32, 0u32, 0u32);
+    for i in 0..32 {
+        x |= (((key >> (3*i)) & 1) as u32) << i;
+        y |= (((key >> (3*i + 1)) & 1) as u32) << i;
+        z |= (((key >> (3*i + 2)) & 1) as u32) << i;
+    }
+    (x, y, z)
+}
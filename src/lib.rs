@@ -1,17 +1,85 @@
 //!
 //! DataUtils - a assorted set of helper entities and logics wrt basic data types.
+//! By default std is used, disabling the std feature builds the crate has
+//! no_std + alloc, with the std only logics (file io, system time, ...) left out.
+//! In that case the libm feature is needed, for the float math used by sigpro.
 //!
 //! HanishKVC, 2022
 //!
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("datautilsk: without the std feature, the libm feature is required");
+
+/// The std prelude items, which need to be imported explicitly when building has no_std
+#[allow(unused_imports)]
+mod alloc_prelude {
+    pub use alloc::borrow::ToOwned;
+    pub use alloc::boxed::Box;
+    pub use alloc::format;
+    pub use alloc::string::{String, ToString};
+    pub use alloc::vec;
+    pub use alloc::vec::Vec;
+}
+
+///
+/// Internal diagnostic output, ie du_log!(level, fmt, args...) with level being
+/// one of error, warn, info, debug, trace.
+///
+/// With the log feature, its routed through the log facade, so that embedders
+/// can control the verbosity and destination. Else error, warn and info go to
+/// stderr (if std is available), while debug and trace are dropped.
+///
+macro_rules! du_log {
+    (debug, $($arg:tt)+) => { du_log!(@quiet debug, $($arg)+) };
+    (trace, $($arg:tt)+) => { du_log!(@quiet trace, $($arg)+) };
+    (@quiet $lvl:ident, $($arg:tt)+) => {{
+        #[cfg(feature = "log")]
+        log::$lvl!($($arg)+);
+        #[cfg(not(feature = "log"))]
+        let _ = format_args!($($arg)+);
+    }};
+    ($lvl:ident, $($arg:tt)+) => {{
+        #[cfg(feature = "log")]
+        log::$lvl!($($arg)+);
+        #[cfg(all(not(feature = "log"), feature = "std"))]
+        std::eprintln!($($arg)+);
+        #[cfg(all(not(feature = "log"), not(feature = "std")))]
+        let _ = format_args!($($arg)+);
+    }};
+}
+
+mod fmath;
+
+pub mod error;
 pub mod variant;
 pub mod integer;
+pub mod float;
 pub mod hex;
+pub mod encode;
 pub mod sigpro;
+pub mod text;
+pub mod pack;
+pub mod bytes;
+pub mod checksum;
+pub mod hash;
+pub mod net;
+pub mod flags;
+pub mod bits;
+pub mod rng;
+pub mod clock;
+pub mod randdata;
+pub mod prelude;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+#[cfg(feature = "std")]
 pub mod testlib;
 
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use crate::testlib;
 
@@ -37,6 +105,11 @@ mod tests {
         testlib::test_vecavg();
     }
 
+    #[test]
+    fn test_dotf32() {
+        testlib::test_dotf32();
+    }
+
     #[test]
     fn test_lowpassavg() {
         testlib::test_lowpassavg();
@@ -47,4 +120,497 @@ mod tests {
         testlib::test_crosscorr();
     }
 
+    #[test]
+    fn test_plainslice() {
+        testlib::test_plainslice();
+    }
+
+    #[test]
+    fn test_linfit() {
+        testlib::test_linfit();
+    }
+
+    #[test]
+    fn test_downsample() {
+        testlib::test_downsample();
+    }
+
+    #[test]
+    fn test_clampseries() {
+        testlib::test_clampseries();
+    }
+
+    #[test]
+    fn test_uniformgrid() {
+        testlib::test_uniformgrid();
+    }
+
+    #[test]
+    fn test_rmsenvelope() {
+        testlib::test_rmsenvelope();
+    }
+
+    #[test]
+    fn test_snrthd() {
+        testlib::test_snrthd();
+    }
+
+    #[test]
+    fn test_joinseries() {
+        testlib::test_joinseries();
+    }
+
+    #[test]
+    fn test_swminmax() {
+        testlib::test_swminmax();
+    }
+
+    #[test]
+    fn test_kalman1d() {
+        testlib::test_kalman1d();
+    }
+
+    #[test]
+    fn test_crossings() {
+        testlib::test_crossings();
+    }
+
+    #[test]
+    fn test_missing() {
+        testlib::test_missing();
+    }
+
+    #[test]
+    fn test_nexttoken() {
+        testlib::test_nexttoken();
+    }
+
+    #[test]
+    fn test_tokenizer() {
+        testlib::test_tokenizer();
+    }
+
+    #[test]
+    fn test_tokenizerconfig() {
+        testlib::test_tokenizerconfig();
+    }
+
+    #[test]
+    fn test_tokenizergroups() {
+        testlib::test_tokenizergroups();
+    }
+
+    #[test]
+    fn test_splitcsv() {
+        testlib::test_splitcsv();
+    }
+
+    #[test]
+    fn test_substitute() {
+        testlib::test_substitute();
+    }
+
+    #[test]
+    fn test_tokenerror() {
+        testlib::test_tokenerror();
+    }
+
+    #[test]
+    fn test_nexttokenref() {
+        testlib::test_nexttokenref();
+    }
+
+    #[test]
+    fn test_linecontinuation() {
+        testlib::test_linecontinuation();
+    }
+
+    #[test]
+    fn test_extractbalanced() {
+        testlib::test_extractbalanced();
+    }
+
+    #[test]
+    fn test_escapestr() {
+        testlib::test_escapestr();
+    }
+
+    #[test]
+    fn test_preprocessor() {
+        testlib::test_preprocessor();
+    }
+
+    #[test]
+    fn test_pack() {
+        testlib::test_pack();
+    }
+
+    #[test]
+    fn test_bytecursor() {
+        testlib::test_bytecursor();
+    }
+
+    #[test]
+    fn test_checksums() {
+        testlib::test_checksums();
+    }
+
+    #[test]
+    fn test_hashes() {
+        testlib::test_hashes();
+    }
+
+    #[test]
+    fn test_bytesdiff() {
+        testlib::test_bytesdiff();
+    }
+
+    #[test]
+    fn test_applypatches() {
+        testlib::test_applypatches();
+    }
+
+    #[test]
+    fn test_sliceext() {
+        testlib::test_sliceext();
+    }
+
+    #[test]
+    fn test_frames() {
+        testlib::test_frames();
+    }
+
+    #[test]
+    fn test_rle() {
+        testlib::test_rle();
+    }
+
+    #[test]
+    fn test_bytetransforms() {
+        testlib::test_bytetransforms();
+    }
+
+    #[test]
+    fn test_cobs() {
+        testlib::test_cobs();
+    }
+
+    #[test]
+    fn test_bignum() {
+        testlib::test_bignum();
+    }
+
+    #[test]
+    fn test_net() {
+        testlib::test_net();
+    }
+
+    #[test]
+    fn test_printable() {
+        testlib::test_printable();
+    }
+
+    #[test]
+    fn test_flagset() {
+        testlib::test_flagset();
+    }
+
+    #[test]
+    fn test_bitreverse() {
+        testlib::test_bitreverse();
+    }
+
+    #[test]
+    fn test_morton() {
+        testlib::test_morton();
+    }
+
+    #[test]
+    fn test_hammingdistance() {
+        testlib::test_hammingdistance();
+    }
+
+    #[test]
+    fn test_ecc() {
+        testlib::test_ecc();
+    }
+
+    #[test]
+    fn test_entropy() {
+        testlib::test_entropy();
+    }
+
+    #[test]
+    fn test_bytehistogram() {
+        testlib::test_bytehistogram();
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        testlib::test_levenshtein();
+    }
+
+    #[test]
+    fn test_asserthelpers() {
+        testlib::test_asserthelpers();
+    }
+
+    #[test]
+    fn test_proproundtrip() {
+        testlib::test_proproundtrip();
+    }
+
+    #[test]
+    fn test_golden() {
+        testlib::test_golden();
+    }
+
+    #[test]
+    fn test_xfcsv() {
+        testlib::test_xfcsv();
+    }
+
+    #[test]
+    fn test_seededrng() {
+        testlib::test_seededrng();
+    }
+
+    #[test]
+    fn test_panicfree() {
+        testlib::test_panicfree();
+    }
+
+    #[test]
+    fn test_slicefirst() {
+        testlib::test_slicefirst();
+    }
+
+    #[test]
+    fn test_prelude() {
+        testlib::test_prelude();
+    }
+
+    #[test]
+    fn test_logfacade() {
+        testlib::test_logfacade();
+    }
+
+    #[cfg(feature = "fuzzing")]
+    #[test]
+    fn test_fuzzentries() {
+        testlib::test_fuzzentries();
+    }
+
+    #[test]
+    fn test_constgeneric() {
+        testlib::test_constgeneric();
+    }
+
+    #[test]
+    fn test_slicenumext() {
+        testlib::test_slicenumext();
+    }
+
+    #[test]
+    fn test_variantformatint() {
+        testlib::test_variantformatint();
+    }
+
+    #[test]
+    fn test_variantbuilder() {
+        testlib::test_variantbuilder();
+    }
+
+    #[test]
+    fn test_variantparsestream() {
+        testlib::test_variantparsestream();
+    }
+
+    #[test]
+    fn test_compileliteral() {
+        testlib::test_compileliteral();
+    }
+
+    #[test]
+    fn test_evalexpr() {
+        testlib::test_evalexpr();
+    }
+
+    #[test]
+    fn test_grid2d() {
+        testlib::test_grid2d();
+    }
+
+    #[test]
+    fn test_complex() {
+        testlib::test_complex();
+    }
+
+    #[test]
+    fn test_detectclipping() {
+        testlib::test_detectclipping();
+    }
+
+    #[test]
+    fn test_swcorrelation() {
+        testlib::test_swcorrelation();
+    }
+
+    #[test]
+    fn test_fitexpdecay() {
+        testlib::test_fitexpdecay();
+    }
+
+    #[test]
+    fn test_onlinefilters() {
+        testlib::test_onlinefilters();
+    }
+
+    #[test]
+    fn test_processchunked() {
+        testlib::test_processchunked();
+    }
+
+    #[test]
+    fn test_renderascii() {
+        testlib::test_renderascii();
+    }
+
+    #[test]
+    fn test_exportxf() {
+        testlib::test_exportxf();
+    }
+
+    #[test]
+    fn test_hexvalidate() {
+        testlib::test_hexvalidate();
+    }
+
+    #[cfg(feature = "wav")]
+    #[test]
+    fn test_wavio() {
+        testlib::test_wavio();
+    }
+
+    #[cfg(feature = "plot")]
+    #[test]
+    fn test_plotchart() {
+        testlib::test_plotchart();
+    }
+
+    #[test]
+    fn test_utf8helpers() {
+        testlib::test_utf8helpers();
+    }
+
+    #[test]
+    fn test_floatvalue() {
+        testlib::test_floatvalue();
+    }
+
+    #[test]
+    fn test_classifyliteral() {
+        testlib::test_classifyliteral();
+    }
+
+    #[test]
+    fn test_randdata() {
+        testlib::test_randdata();
+    }
+
+    #[test]
+    fn test_boundeduint() {
+        testlib::test_boundeduint();
+    }
+
+    #[test]
+    fn test_variantarena() {
+        testlib::test_variantarena();
+    }
+
+    #[test]
+    fn test_clock() {
+        testlib::test_clock();
+    }
+
+    #[test]
+    fn test_sharedbuf() {
+        testlib::test_sharedbuf();
+    }
+
+    #[test]
+    fn test_variantformat() {
+        testlib::test_variantformat();
+    }
+
+    #[test]
+    fn test_basen() {
+        testlib::test_basen();
+    }
+
+    #[test]
+    fn test_bitvec() {
+        testlib::test_bitvec();
+    }
+
+    #[test]
+    fn test_ringseries() {
+        testlib::test_ringseries();
+    }
+
+    #[test]
+    fn test_splitdelimited() {
+        testlib::test_splitdelimited();
+    }
+
+    #[test]
+    fn test_bytesfind() {
+        testlib::test_bytesfind();
+    }
+
+    #[test]
+    fn test_matchmasked() {
+        testlib::test_matchmasked();
+    }
+
+    #[test]
+    fn test_movingavgconstfn() {
+        testlib::test_movingavgconstfn();
+    }
+
+    #[test]
+    fn test_interleave() {
+        testlib::test_interleave();
+    }
+
+    #[test]
+    fn test_samples() {
+        testlib::test_samples();
+    }
+
+    #[test]
+    fn test_runningpercentile() {
+        testlib::test_runningpercentile();
+    }
+
+    #[test]
+    fn test_vecmad() {
+        testlib::test_vecmad();
+    }
+
+    #[test]
+    fn test_globmatch() {
+        testlib::test_globmatch();
+    }
+
+    #[test]
+    fn test_naturalcmp() {
+        testlib::test_naturalcmp();
+    }
+
+    #[test]
+    fn test_jarowinkler() {
+        testlib::test_jarowinkler();
+    }
+
 }
@@ -0,0 +1,55 @@
+//!
+//! Parse/Format network addresses from/to byte arrays, suitable for packet
+//! construction/dissection.
+//!
+//! HanishKVC, 2022
+//!
+
+#[allow(unused_imports)]
+use crate::alloc_prelude::*;
+
+
+/// Parse a MAC address like aa:bb:cc:dd:ee:ff (or with - has the seperator)
+pub fn mac_from_str(s: &str) -> Result<[u8; 6], String> {
+    let sep = if s.contains('-') { '-' } else { ':' };
+    let mut mac = [0u8; 6];
+    let mut parts = s.split(sep);
+    for b in mac.iter_mut() {
+        let part = parts.next().unwrap_or("");
+        if part.is_empty() || (part.len() > 2) || !part.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("ERRR:DU:Net:MacFromStr:Invalid mac [{}]", s));
+        }
+        *b = u8::from_str_radix(part, 16).map_err(|e| format!("ERRR:DU:Net:MacFromStr:Invalid mac [{}]:{}", s, e))?;
+    }
+    if parts.next().is_some() {
+        return Err(format!("ERRR:DU:Net:MacFromStr:Invalid mac [{}], too many parts", s));
+    }
+    Ok(mac)
+}
+
+/// Format the given MAC address has aa:bb:cc:dd:ee:ff
+pub fn mac_to_string(mac: &[u8; 6]) -> String {
+    mac.iter().map(|b| format!("{:02x}", b)).collect::<Vec<String>>().join(":")
+}
+
+/// Parse a dotted decimal IPv4 address like 192.168.1.1
+pub fn ipv4_from_str(s: &str) -> Result<[u8; 4], String> {
+    let addr: core::net::Ipv4Addr = s.trim().parse().map_err(|e| format!("ERRR:DU:Net:Ipv4FromStr:Invalid ipv4 [{}]:{}", s, e))?;
+    Ok(addr.octets())
+}
+
+/// Format the given IPv4 address in dotted decimal notation
+pub fn ipv4_to_string(ip: &[u8; 4]) -> String {
+    core::net::Ipv4Addr::from(*ip).to_string()
+}
+
+/// Parse a IPv6 address, including the :: compressed notation
+pub fn ipv6_from_str(s: &str) -> Result<[u8; 16], String> {
+    let addr: core::net::Ipv6Addr = s.trim().parse().map_err(|e| format!("ERRR:DU:Net:Ipv6FromStr:Invalid ipv6 [{}]:{}", s, e))?;
+    Ok(addr.octets())
+}
+
+/// Format the given IPv6 address, in the canonical (RFC 5952) compressed form
+pub fn ipv6_to_string(ip: &[u8; 16]) -> String {
+    core::net::Ipv6Addr::from(*ip).to_string()
+}
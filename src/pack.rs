@@ -0,0 +1,273 @@
+//!
+//! Pack/Unpack values into/from a byte buffer, as specified by a format string,
+//! similar to python's struct module.
+//!
+//! The format string consists of whitespace seperated items
+//!
+//! * u8, u16, u32, u64, i8, i16, i32, i64 - integer fields
+//! * Ns - a byte array of N bytes (a string or buffer value)
+//! * Nx - N pad bytes (0), which dont consume/produce any value
+//!
+//! Endianness markers, which apply to the following integer fields, can be
+//! prefixed to any item (or given as a item by themselves)
+//!
+//! * < - little endian
+//! * > or ! - big endian (network byte order)
+//! * = - native endian
+//!
+//! The default is native endian. ex: "<u16 u32 8s >u16 2x"
+//!
+//! HanishKVC, 2022
+//!
+
+use crate::variant::Variant;
+#[allow(unused_imports)]
+use crate::alloc_prelude::*;
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Endian {
+    Little,
+    Big,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PackItem {
+    Int { bytes: usize, signed: bool, endian: Endian },
+    Bytes(usize),
+    Pad(usize),
+}
+
+fn native_endian() -> Endian {
+    if cfg!(target_endian = "big") {
+        Endian::Big
+    } else {
+        Endian::Little
+    }
+}
+
+/// Parse the given format string, calling the given handler for each of its items
+fn for_each_item<F: FnMut(PackItem) -> Result<(), String>>(fmt: &str, mut handler: F) -> Result<(), String> {
+    let mut endian = native_endian();
+    for tok in fmt.split_whitespace() {
+        let mut item = tok;
+        while let Some(c) = item.chars().next() {
+            match c {
+                '<' => endian = Endian::Little,
+                '>' | '!' => endian = Endian::Big,
+                '=' => endian = native_endian(),
+                _ => break,
+            }
+            item = &item[1..];
+        }
+        if item.is_empty() {
+            continue;
+        }
+        let pitem = match item {
+            "u8" => PackItem::Int { bytes: 1, signed: false, endian },
+            "u16" => PackItem::Int { bytes: 2, signed: false, endian },
+            "u32" => PackItem::Int { bytes: 4, signed: false, endian },
+            "u64" => PackItem::Int { bytes: 8, signed: false, endian },
+            "i8" => PackItem::Int { bytes: 1, signed: true, endian },
+            "i16" => PackItem::Int { bytes: 2, signed: true, endian },
+            "i32" => PackItem::Int { bytes: 4, signed: true, endian },
+            "i64" => PackItem::Int { bytes: 8, signed: true, endian },
+            _ => {
+                // the last char need not be ascii, so dont split by byte position
+                let pitem = if let Some(scount) = item.strip_suffix('s') {
+                    scount.parse::<usize>().ok().map(PackItem::Bytes)
+                } else if let Some(scount) = item.strip_suffix('x') {
+                    scount.parse::<usize>().ok().map(PackItem::Pad)
+                } else {
+                    None
+                };
+                match pitem {
+                    Some(pitem) => pitem,
+                    None => return Err(format!("ERRR:DU:Pack:ParseFormat:Unknown item [{}] in [{}]", tok, fmt)),
+                }
+            }
+        };
+        handler(pitem)?;
+    }
+    Ok(())
+}
+
+/// Parse the given format string into its items
+fn parse_format(fmt: &str) -> Result<Vec<PackItem>, String> {
+    let mut vitems = Vec::new();
+    for_each_item(fmt, |pitem| {
+        vitems.push(pitem);
+        Ok(())
+    })?;
+    du_log!(trace, "DBUG:DU:Pack:ParseFormat:[{}]:{:?}", fmt, vitems);
+    Ok(vitems)
+}
+
+/// Decode a integer field of the given size, signedness and endianness
+fn int_from_field(field: &[u8], bytes: usize, signed: bool, endian: Endian) -> i64 {
+    let mut vbytes = [0u8; 8];
+    vbytes[..bytes].copy_from_slice(field);
+    if endian == Endian::Big {
+        vbytes[..bytes].reverse();
+    }
+    if signed && (vbytes[bytes-1] & 0x80 != 0) {
+        vbytes[bytes..].fill(0xFF);
+    }
+    i64::from_le_bytes(vbytes)
+}
+
+/// Check that the given integer fits within a integer field of the given size
+fn int_fits(ival: isize, bytes: usize, signed: bool) -> bool {
+    if bytes >= 8 {
+        return true;
+    }
+    let bits = (bytes * 8) as u32;
+    let ival = ival as i128;
+    if signed {
+        let max = (1i128 << (bits-1)) - 1;
+        (ival >= -max-1) && (ival <= max)
+    } else {
+        (ival >= 0) && (ival < (1i128 << bits))
+    }
+}
+
+///
+/// Pack the given values into a byte buffer, as specified by the format string.
+///
+/// Integer fields use the values' get_isize, while byte array fields use the
+/// values' get_bufvu8 (zero padded, if shorter than the field).
+/// For 64 bit fields, the isize value is used as is (ie its bit pattern).
+///
+pub fn pack(fmt: &str, values: &[Variant]) -> Result<Vec<u8>, String> {
+    let vitems = parse_format(fmt)?;
+    let size = items_size(&vitems, fmt)?;
+    let mut vout = Vec::new();
+    // so that a huge Ns/Nx count gives a error, rather than a allocation failure
+    vout.try_reserve_exact(size).map_err(|e| format!("ERRR:DU:Pack:Pack:Format [{}] of {} bytes:{}", fmt, size, e))?;
+    let mut vals = values.iter();
+    for pitem in vitems {
+        if let PackItem::Pad(count) = pitem {
+            vout.resize(vout.len()+count, 0);
+            continue;
+        }
+        let val = match vals.next() {
+            Some(val) => val,
+            None => return Err(format!("ERRR:DU:Pack:Pack:Too few values {} for format [{}]", values.len(), fmt)),
+        };
+        match pitem {
+            PackItem::Int { bytes, signed, endian } => {
+                let ival = val.get_isize().map_err(|e| format!("ERRR:DU:Pack:Pack:{}", e))?;
+                if !int_fits(ival, bytes, signed) {
+                    return Err(format!("ERRR:DU:Pack:Pack:Value {} doesnt fit in {} bytes, signed {}", ival, bytes, signed));
+                }
+                let vbytes = (ival as i64).to_le_bytes();
+                match endian {
+                    Endian::Little => vout.extend_from_slice(&vbytes[..bytes]),
+                    Endian::Big => vout.extend(vbytes[..bytes].iter().rev()),
+                }
+            }
+            PackItem::Bytes(count) => {
+                let vbuf = val.get_bufvu8();
+                if vbuf.len() > count {
+                    return Err(format!("ERRR:DU:Pack:Pack:Value of {} bytes longer than {}s", vbuf.len(), count));
+                }
+                vout.extend_from_slice(&vbuf);
+                vout.resize(vout.len()+count-vbuf.len(), 0);
+            }
+            PackItem::Pad(_) => unreachable!(),
+        }
+    }
+    if vals.next().is_some() {
+        return Err(format!("ERRR:DU:Pack:Pack:Too many values {} for format [{}]", values.len(), fmt));
+    }
+    Ok(vout)
+}
+
+///
+/// Unpack values from the given byte buffer, as specified by the format string.
+///
+/// Integer fields are returned as IntValue, byte array fields as BufValue.
+/// NOTE: u64 values beyond isize::MAX wrap around, as IntValue is a isize.
+///
+/// The buffer should have atleast the number of bytes specified by the format,
+/// any extra bytes are ignored.
+///
+pub fn unpack(fmt: &str, buf: &[u8]) -> Result<Vec<Variant>, String> {
+    let vitems = parse_format(fmt)?;
+    let mut vout = Vec::new();
+    let mut pos: usize = 0;
+    for pitem in vitems {
+        let size = match pitem {
+            PackItem::Int { bytes, .. } => bytes,
+            PackItem::Bytes(count) | PackItem::Pad(count) => count,
+        };
+        if pos.checked_add(size).is_none_or(|end| end > buf.len()) {
+            return Err(format!("ERRR:DU:Pack:Unpack:Buffer of {} bytes too short for format [{}]", buf.len(), fmt));
+        }
+        let field = &buf[pos..pos+size];
+        pos += size;
+        match pitem {
+            PackItem::Int { bytes, signed, endian } => {
+                vout.push(Variant::IntValue(int_from_field(field, bytes, signed, endian) as isize));
+            }
+            PackItem::Bytes(_) => vout.push(Variant::BufValue(field.to_vec())),
+            PackItem::Pad(_) => (),
+        }
+    }
+    Ok(vout)
+}
+
+/// Total size of the given items, erroring out if it doesnt fit in usize
+fn items_size(vitems: &[PackItem], fmt: &str) -> Result<usize, String> {
+    let mut size: usize = 0;
+    for pitem in vitems {
+        let psize = match pitem {
+            PackItem::Int { bytes, .. } => *bytes,
+            PackItem::Bytes(count) | PackItem::Pad(count) => *count,
+        };
+        size = size.checked_add(psize).ok_or_else(|| format!("ERRR:DU:Pack:CalcSize:Format [{}] size overflows", fmt))?;
+    }
+    Ok(size)
+}
+
+/// Number of bytes that the given format string corresponds to
+pub fn calcsize(fmt: &str) -> Result<usize, String> {
+    let vitems = parse_format(fmt)?;
+    items_size(&vitems, fmt)
+}
+
+///
+/// Unpack the integer fields of the given byte buffer, as specified by the format
+/// string, into a fixed size array, without any heap allocation.
+///
+/// The format should have exactly N integer fields, pad items are allowed,
+/// but byte array (Ns) items are not. u64 values beyond i64::MAX wrap around.
+///
+pub fn unpack_into<const N: usize>(fmt: &str, buf: &[u8]) -> Result<[i64; N], String> {
+    let mut aout = [0i64; N];
+    let mut count = 0;
+    let mut pos: usize = 0;
+    for_each_item(fmt, |pitem| {
+        let size = match pitem {
+            PackItem::Int { bytes, .. } => bytes,
+            PackItem::Pad(count) => count,
+            PackItem::Bytes(_) => return Err(format!("ERRR:DU:Pack:UnpackInto:Byte array items not supported [{}]", fmt)),
+        };
+        if pos.checked_add(size).is_none_or(|end| end > buf.len()) {
+            return Err(format!("ERRR:DU:Pack:UnpackInto:Buffer of {} bytes too short for format [{}]", buf.len(), fmt));
+        }
+        if let PackItem::Int { bytes, signed, endian } = pitem {
+            if count >= N {
+                return Err(format!("ERRR:DU:Pack:UnpackInto:More than {} integer fields in format [{}]", N, fmt));
+            }
+            aout[count] = int_from_field(&buf[pos..pos+size], bytes, signed, endian);
+            count += 1;
+        }
+        pos += size;
+        Ok(())
+    })?;
+    if count != N {
+        return Err(format!("ERRR:DU:Pack:UnpackInto:Format [{}] has {} integer fields, expected {}", fmt, count, N));
+    }
+    Ok(aout)
+}
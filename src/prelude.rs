@@ -0,0 +1,17 @@
+//!
+//! The commonly used types and traits of the crate, so that they can be
+//! brought into scope with a single use datautilsk::prelude::*;
+//!
+//! HanishKVC, 2022
+//!
+
+pub use crate::error::DataUtilsError;
+pub use crate::variant::{Variant, VDataType, VariantBuilder};
+pub use crate::integer::{U8X, U16X, U32X};
+pub use crate::hex::{ToHex, FromHex};
+pub use crate::bytes::{ByteCursor, SliceReadExt, SliceWriteExt};
+pub use crate::checksum::{Adler32, Fletcher16, Fletcher32};
+pub use crate::flags::FlagSet;
+pub use crate::rng::{RandSource, SplitMix64};
+pub use crate::text::{Token, TokenKind, TokenError, Tokenizer, TokenizerConfig};
+pub use crate::sigpro::SliceNumExt;
@@ -0,0 +1,123 @@
+//!
+//! Generate random numeric data with different distributions, using a injected
+//! RandSource, so that the data generated can be reproduced by using a seeded
+//! generator like SplitMix64.
+//!
+//! HanishKVC, 2022
+//!
+
+use core::ops::RangeInclusive;
+
+use crate::fmath;
+use crate::rng::RandSource;
+#[allow(unused_imports)]
+use crate::alloc_prelude::*;
+
+
+/// A integer in the given (inclusive) range, with each value being equally likely.
+///
+/// If the range is empty (ie start > end), its start is returned.
+pub fn int_in(rng: &mut impl RandSource, range: RangeInclusive<i64>) -> i64 {
+    let (start, end) = (*range.start(), *range.end());
+    if start >= end {
+        return start;
+    }
+    let span = end.abs_diff(start);
+    if span == u64::MAX {
+        return rng.next_u64() as i64;
+    }
+    let n = span + 1;
+    // Reject the values beyond the largest multiple of n, to avoid modulo bias
+    let zone = u64::MAX - (u64::MAX % n);
+    loop {
+        let v = rng.next_u64();
+        if v < zone {
+            return start.wrapping_add((v % n) as i64);
+        }
+    }
+}
+
+/// Choose one of the given items, with the probability of each item being proportional
+/// to its weight. Negative weights are treated has 0.
+///
+/// Returns None if there are no items, or if all the weights are 0.
+pub fn choose_weighted<'a, T>(rng: &mut impl RandSource, items: &'a [(T, f64)]) -> Option<&'a T> {
+    let total: f64 = items.iter().map(|(_, w)| w.max(0.0)).sum();
+    if total.is_nan() || total <= 0.0 {
+        return None;
+    }
+    let mut target = rng.next_f64()*total;
+    let mut last = None;
+    for (item, w) in items {
+        if *w > 0.0 {
+            if target < *w {
+                return Some(item);
+            }
+            target -= *w;
+            last = Some(item);
+        }
+    }
+    // Float rounding could leave target at the edge, so use the last item with a weight
+    last
+}
+
+/// A value in the range lo (inclusive) to hi (exclusive), with a uniform distribution
+pub fn uniform(rng: &mut impl RandSource, lo: f64, hi: f64) -> f64 {
+    lo + rng.next_f64()*(hi - lo)
+}
+
+/// The number of failures before the first success, in a series of trials each with
+/// the given success probability p, ie a geometric distribution with mean (1-p)/p.
+///
+/// p should be within (0.0, 1.0], p <= 0.0 (never succeeds) gives u64::MAX.
+pub fn geometric(rng: &mut impl RandSource, p: f64) -> u64 {
+    if p >= 1.0 {
+        return 0;
+    }
+    if p.is_nan() || p <= 0.0 {
+        return u64::MAX;
+    }
+    // 1 - next_f64 is in (0, 1], so its log is finite
+    let u = 1.0 - rng.next_f64();
+    fmath::f64::floor(fmath::f64::ln(u)/fmath::f64::ln(1.0 - p)) as u64
+}
+
+///
+/// Zipf distribution over the ranks 1..=n, where the probability of rank k is
+/// proportional to 1/k^s. This helps generate data where a few values are very
+/// common, while there is a long tail of rare values.
+///
+/// The cumulative distribution is precomputed, so that each sample is a binary search.
+///
+#[derive(Debug, Clone)]
+pub struct Zipf {
+    vcdf: Vec<f64>,
+}
+
+impl Zipf {
+
+    /// n should be atleast 1 and s should be non negative
+    pub fn new(n: usize, s: f64) -> Result<Zipf, String> {
+        if n == 0 || s.is_nan() || s < 0.0 {
+            return Err(format!("ERRR:DU:RandData:Zipf:Invalid n {} or s {}", n, s));
+        }
+        let mut vcdf = Vec::with_capacity(n);
+        let mut sum = 0.0;
+        for k in 1..=n {
+            sum += 1.0/fmath::f64::powf(k as f64, s);
+            vcdf.push(sum);
+        }
+        for c in vcdf.iter_mut() {
+            *c /= sum;
+        }
+        Ok(Zipf { vcdf })
+    }
+
+    /// A rank in the range 1..=n
+    pub fn sample(&self, rng: &mut impl RandSource) -> u64 {
+        let u = rng.next_f64();
+        let i = self.vcdf.partition_point(|c| *c <= u);
+        (i.min(self.vcdf.len()-1) + 1) as u64
+    }
+
+}
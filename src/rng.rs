@@ -0,0 +1,74 @@
+//!
+//! A minimal random number source abstraction, so that the randomised logics
+//! in the crate can be driven by a seeded (deterministic) generator, in tests
+//! and to reproduce a given run.
+//!
+//! NOTE: These are not suitable where cryptographic strength is required.
+//!
+//! HanishKVC, 2022
+//!
+
+#[cfg(feature = "std")]
+use std::time;
+
+
+/// A source of random numbers, only next_u64 needs to be implemented
+pub trait RandSource {
+
+    fn next_u64(&mut self) -> u64;
+
+    /// A value in the range 0..n, n should be non zero
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// A value in the range 0.0 (inclusive) to 1.0 (exclusive), with 53 bits of randomness
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0/((1u64 << 53) as f64))
+    }
+
+    /// Fill the given buffer with random bytes
+    fn fill_bytes(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(8) {
+            let vbytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&vbytes[..chunk.len()]);
+        }
+    }
+
+}
+
+///
+/// SplitMix64 pseudo random generator, which is small, fast and good enough
+/// for generating test/protocol data. The same seed gives the same sequence.
+///
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+
+    pub fn new(seed: u64) -> SplitMix64 {
+        SplitMix64 { state: seed }
+    }
+
+    /// Seed using the current system time, for when reproducibility is not needed
+    #[cfg(feature = "std")]
+    pub fn from_time() -> SplitMix64 {
+        let ts = time::SystemTime::now().duration_since(time::UNIX_EPOCH).unwrap();
+        SplitMix64::new(ts.as_nanos() as u64)
+    }
+
+}
+
+impl RandSource for SplitMix64 {
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+}
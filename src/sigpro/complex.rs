@@ -0,0 +1,79 @@
+//!
+//! A lightweight complex sample type, along with helpers to post process
+//! complex data (like FFT outputs) into magnitude and phase.
+//!
+//! HanishKVC, 2022
+//!
+
+use core::f32::consts::PI;
+
+use crate::fmath;
+
+#[allow(unused_imports)]
+use crate::alloc_prelude::*;
+
+
+/// A complex value with f32 real and imaginary parts
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Complex32 {
+    pub re: f32,
+    pub im: f32,
+}
+
+impl Complex32 {
+
+    pub fn new(re: f32, im: f32) -> Complex32 {
+        Complex32 { re, im }
+    }
+
+    /// The magnitude ie sqrt(re^2 + im^2)
+    pub fn abs(&self) -> f32 {
+        fmath::f32::hypot(self.re, self.im)
+    }
+
+    /// The phase angle in radians, in the range -PI to PI
+    pub fn arg(&self) -> f32 {
+        fmath::f32::atan2(self.im, self.re)
+    }
+
+}
+
+impl From<(f32, f32)> for Complex32 {
+    /// Create from a (re, im) tuple
+    fn from(reim: (f32, f32)) -> Self {
+        Complex32 { re: reim.0, im: reim.1 }
+    }
+}
+
+/// The magnitude of each of the passed complex samples
+pub fn magnitude(vdata: &[Complex32]) -> Vec<f32> {
+    vdata.iter().map(|c| c.abs()).collect()
+}
+
+/// The phase (in radians, -PI to PI) of each of the passed complex samples
+pub fn phase(vdata: &[Complex32]) -> Vec<f32> {
+    vdata.iter().map(|c| c.arg()).collect()
+}
+
+/// Unwrap the passed phase values (in radians), by adding the required multiple of 2*PI
+/// to each value, whenever the jump from the previous value is more than PI, so that
+/// the phase changes continuously rather than wrapping around at +-PI.
+pub fn unwrap_phase(vphase: &[f32]) -> Vec<f32> {
+    let mut vnew = Vec::with_capacity(vphase.len());
+    let mut offset = 0.0f32;
+    let mut prev = match vphase.first() {
+        Some(p) => *p,
+        None => return vnew,
+    };
+    for p in vphase {
+        let delta = *p - prev;
+        if delta > PI {
+            offset -= 2.0*PI*fmath::f64::floor(((delta + PI)/(2.0*PI)) as f64) as f32;
+        } else if delta < -PI {
+            offset += 2.0*PI*fmath::f64::floor(((PI - delta)/(2.0*PI)) as f64) as f32;
+        }
+        prev = *p;
+        vnew.push(*p + offset);
+    }
+    vnew
+}
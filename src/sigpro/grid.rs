@@ -0,0 +1,90 @@
+//!
+//! Helpers to work with 2D grids of numeric values, like heatmaps.
+//!
+//! A grid is maintained has a flat slice of f32 values in row major order,
+//! along with the width (ie number of columns) of the grid.
+//!
+//! HanishKVC, 2022
+//!
+
+#[allow(unused_imports)]
+use crate::alloc_prelude::*;
+
+
+/// How the cells near the edges of the grid, which dont have a full window
+/// around them, are handled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeMode {
+    /// Leave them as is, like sw_average does for the 1D case
+    Keep,
+    /// Average over the part of the window which is within the grid
+    Shrink,
+    /// Treat the cells beyond the edge has having the value of the nearest edge cell
+    Clamp,
+}
+
+/// The (backward, forward) extent of a centered window of the given size.
+/// A even window size favors the forward side, same has sw_average.
+fn window_extent(ww: usize) -> (usize, usize) {
+    let wwh = ww/2;
+    if ww.is_multiple_of(2) { (wwh.saturating_sub(1), wwh) } else { (wwh, wwh) }
+}
+
+/// 2D moving average over a kx (columns) by ky (rows) window centered on each cell.
+///
+/// The data is a flat row major slice, with width cells per row. A window size of 1
+/// along a axis means no smoothing along that axis.
+///
+pub fn moving_avg_2d(vdata: &[f32], width: usize, kx: usize, ky: usize, edge: EdgeMode) -> Result<Vec<f32>, String> {
+    if width == 0 || !vdata.len().is_multiple_of(width) {
+        return Err(format!("ERRR:DU:SigPro:Grid:MovingAvg2D:Data length {} not a multiple of width {}", vdata.len(), width));
+    }
+    if kx == 0 || ky == 0 {
+        return Err("ERRR:DU:SigPro:Grid:MovingAvg2D:Window size should be atleast 1".to_string());
+    }
+    let height = vdata.len()/width;
+    let (xb, xf) = window_extent(kx);
+    let (yb, yf) = window_extent(ky);
+    let mut vnew = Vec::with_capacity(vdata.len());
+    for r in 0..height {
+        for c in 0..width {
+            let full = (r >= yb) && (r + yf < height) && (c >= xb) && (c + xf < width);
+            if !full && edge == EdgeMode::Keep {
+                vnew.push(vdata[r*width + c]);
+                continue;
+            }
+            let mut sum = 0.0f32;
+            let mut cnt = 0usize;
+            for ir in (r as isize - yb as isize)..=((r + yf) as isize) {
+                for ic in (c as isize - xb as isize)..=((c + xf) as isize) {
+                    let inside = (0..height as isize).contains(&ir) && (0..width as isize).contains(&ic);
+                    if !inside && edge == EdgeMode::Shrink {
+                        continue;
+                    }
+                    let ir = ir.clamp(0, height as isize - 1) as usize;
+                    let ic = ic.clamp(0, width as isize - 1) as usize;
+                    sum += vdata[ir*width + ic];
+                    cnt += 1;
+                }
+            }
+            vnew.push(sum/(cnt as f32));
+        }
+    }
+    Ok(vnew)
+}
+
+/// 2D moving average over a grid maintained has a slice of rows, refer to moving_avg_2d.
+///
+/// All the rows should have the same length.
+pub fn moving_avg_2d_rows(vrows: &[Vec<f32>], kx: usize, ky: usize, edge: EdgeMode) -> Result<Vec<Vec<f32>>, String> {
+    let width = match vrows.first() {
+        Some(row) => row.len(),
+        None => return Ok(Vec::new()),
+    };
+    if vrows.iter().any(|row| row.len() != width) {
+        return Err("ERRR:DU:SigPro:Grid:MovingAvg2DRows:Rows of differing lengths".to_string());
+    }
+    let vflat: Vec<f32> = vrows.concat();
+    let vnew = moving_avg_2d(&vflat, width, kx, ky, edge)?;
+    Ok(vnew.chunks(width).map(|row| row.to_vec()).collect())
+}
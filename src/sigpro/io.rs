@@ -0,0 +1,134 @@
+//!
+//! Read/Write signal samples from/to files, so that they can be used with the
+//! sigpro logics. Currently PCM WAV files are supported, which are read has
+//! a single (mixed down) channel and written has mono.
+//!
+//! HanishKVC, 2022
+//!
+
+use std::path::Path;
+
+use crate::bytes::{ByteCursor, SliceReadExt};
+use crate::sigpro::samples;
+
+
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Parse the given WAV file contents, refer to read_wav. Multi channel data is mixed
+/// down to a single channel, by averaging the channels of each frame.
+pub fn parse_wav(buf: &[u8]) -> Result<(u32, Vec<f32>), String> {
+    if buf.len() < 12 || &buf[0..4] != b"RIFF" || &buf[8..12] != b"WAVE" {
+        return Err("ERRR:DU:SigPro:IO:ParseWav:Not a RIFF WAVE file".to_string());
+    }
+    let mut fmt: Option<(u16, u16, u32, u16)> = None;
+    let mut pos = 12;
+    while pos + 8 <= buf.len() {
+        let id = buf.get_bytes(pos, 4)?;
+        let size = buf.get_u32_le(pos+4)? as usize;
+        let body = &buf[pos+8..(pos+8).saturating_add(size).min(buf.len())];
+        if id == b"fmt " {
+            let mut format = body.get_u16_le(0)?;
+            let channels = body.get_u16_le(2)?;
+            let rate = body.get_u32_le(4)?;
+            let bits = body.get_u16_le(14)?;
+            if format == WAVE_FORMAT_EXTENSIBLE {
+                // The first 2 bytes of the sub format guid give the actual format
+                format = body.get_u16_le(24)?;
+            }
+            if format != WAVE_FORMAT_PCM {
+                return Err(format!("ERRR:DU:SigPro:IO:ParseWav:Unsupported format {}, only PCM supported", format));
+            }
+            fmt = Some((channels, bits, rate, format));
+        } else if id == b"data" {
+            let (channels, bits, rate, _) = match fmt {
+                Some(fmt) => fmt,
+                None => return Err("ERRR:DU:SigPro:IO:ParseWav:data chunk before fmt chunk".to_string()),
+            };
+            return Ok((rate, pcm_to_f32(body, channels, bits)?));
+        }
+        // Chunks are padded to even sizes
+        pos = pos.saturating_add(8 + size + (size % 2));
+    }
+    Err("ERRR:DU:SigPro:IO:ParseWav:No data chunk".to_string())
+}
+
+/// Convert the interleaved PCM samples to f32 in the range -1.0 to 1.0, averaging the channels.
+fn pcm_to_f32(body: &[u8], channels: u16, bits: u16) -> Result<Vec<f32>, String> {
+    let bytes = match bits {
+        8 | 16 | 24 => (bits/8) as usize,
+        _ => return Err(format!("ERRR:DU:SigPro:IO:ParseWav:Unsupported bits per sample {}", bits)),
+    };
+    if channels == 0 {
+        return Err("ERRR:DU:SigPro:IO:ParseWav:Zero channels".to_string());
+    }
+    let frame = bytes*(channels as usize);
+    let vsamples = body.chunks_exact(frame).map(|f| {
+        let sum: f32 = f.chunks_exact(bytes).map(|s| match bytes {
+            1 => samples::u8_to_f32(s[0]),
+            2 => samples::i16_to_f32(i16::from_le_bytes([s[0], s[1]])),
+            _ => samples::i24_bytes_to_f32([s[0], s[1], s[2]]),
+        }).sum();
+        sum/(channels as f32)
+    }).collect();
+    Ok(vsamples)
+}
+
+/// Read the given PCM WAV file (8 bit unsigned, 16 or 24 bit signed samples), returning its
+/// sample rate and the samples has f32 values in the range -1.0 to 1.0.
+///
+/// Multi channel files are mixed down to a single channel, by averaging the channels.
+pub fn read_wav<P: AsRef<Path>>(path: P) -> Result<(u32, Vec<f32>), String> {
+    let path = path.as_ref();
+    let buf = std::fs::read(path).map_err(|e| format!("ERRR:DU:SigPro:IO:ReadWav:{}:{}", path.display(), e))?;
+    parse_wav(&buf)
+}
+
+/// Generate the contents of a mono PCM WAV file, refer to write_wav.
+///
+/// The RIFF chunk sizes and the byte rate need to fit in u32, else a error is returned.
+pub fn wav_bytes(sample_rate: u32, vdata: &[f32], bits: u16) -> Result<Vec<u8>, String> {
+    if !matches!(bits, 8 | 16 | 24) {
+        return Err(format!("ERRR:DU:SigPro:IO:WavBytes:Unsupported bits per sample {}", bits));
+    }
+    let bytes = (bits/8) as u32;
+    let datalen = u32::try_from(vdata.len()).ok().and_then(|n| n.checked_mul(bytes));
+    let riffsize = datalen.and_then(|n| n.checked_add(36 + (n % 2)));
+    let (datalen, riffsize) = match (datalen, riffsize) {
+        (Some(datalen), Some(riffsize)) => (datalen, riffsize),
+        _ => return Err(format!("ERRR:DU:SigPro:IO:WavBytes:{} samples too many for a wav file", vdata.len())),
+    };
+    let byterate = sample_rate.checked_mul(bytes).ok_or_else(|| format!("ERRR:DU:SigPro:IO:WavBytes:Sample rate {} too large", sample_rate))?;
+    let mut bc = ByteCursor::with_capacity(44 + datalen as usize + 1);
+    bc.put_bytes(b"RIFF");
+    bc.put_u32_le(riffsize);
+    bc.put_bytes(b"WAVEfmt ");
+    bc.put_u32_le(16);
+    bc.put_u16_le(WAVE_FORMAT_PCM);
+    bc.put_u16_le(1);
+    bc.put_u32_le(sample_rate);
+    bc.put_u32_le(byterate);
+    bc.put_u16_le(bytes as u16);
+    bc.put_u16_le(bits);
+    bc.put_bytes(b"data");
+    bc.put_u32_le(datalen);
+    for d in vdata {
+        match bits {
+            8 => bc.put_u8(samples::f32_to_u8(*d)),
+            16 => bc.put_u16_le(samples::f32_to_i16(*d) as u16),
+            _ => bc.put_bytes(&samples::f32_to_i24_bytes(*d)),
+        }
+    }
+    if !datalen.is_multiple_of(2) {
+        bc.put_u8(0);
+    }
+    Ok(bc.into_inner())
+}
+
+/// Write the given samples (in the range -1.0 to 1.0, beyond which they are clipped) to the
+/// given file, has a mono PCM WAV file with the given sample rate and bits per sample (8, 16 or 24).
+pub fn write_wav<P: AsRef<Path>>(path: P, sample_rate: u32, vdata: &[f32], bits: u16) -> Result<(), String> {
+    let path = path.as_ref();
+    let buf = wav_bytes(sample_rate, vdata, bits)?;
+    std::fs::write(path, buf).map_err(|e| format!("ERRR:DU:SigPro:IO:WriteWav:{}:{}", path.display(), e))
+}
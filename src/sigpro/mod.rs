@@ -3,14 +3,87 @@
 //! HanishKVC, 2022
 //!
 
-use std::ops::{AddAssign, Div};
+use alloc::collections::VecDeque;
+use core::ops::{AddAssign, Div};
 
+use crate::fmath;
 
-/// Calculate the sum of the values in the passed vector of numeric values.
-pub fn vec_sum<T: AddAssign + Copy>(vdata: &Vec<T>) -> T {
+#[allow(unused_imports)]
+use crate::alloc_prelude::*;
+
+pub mod grid;
+pub mod complex;
+pub mod samples;
+#[cfg(feature = "wav")]
+pub mod io;
+#[cfg(feature = "plot")]
+pub mod plot;
+
+/// Number of independent accumulators used by the chunked (simd feature) loops.
+/// Keeping them independent allows the compiler to map them onto vector lanes.
+#[cfg(feature = "simd")]
+const SIMD_LANES: usize = 8;
+
+/// Sum the passed slice using SIMD_LANES independent accumulators.
+#[cfg(feature = "simd")]
+fn sum_chunked<T: AddAssign + From<u16> + Copy>(vdata: &[T]) -> T {
+    let mut lanes = [T::from(0u16); SIMD_LANES];
+    let chunks = vdata.chunks_exact(SIMD_LANES);
+    let rem = chunks.remainder();
+    for chunk in chunks {
+        for (l, v) in lanes.iter_mut().zip(chunk) {
+            *l += *v;
+        }
+    }
+    let mut d = T::from(0u16);
+    for l in lanes {
+        d += l;
+    }
+    for v in rem {
+        d += *v;
+    }
+    d
+}
+
+/// Dot product of the passed pair of f32 slices.
+///
+/// Only the overlapping part is used, if the lengths differ.
+///
+/// With the simd feature enabled, the products are accumulated in a lane chunked
+/// manner, which allows the compiler to vectorise the loop.
+///
+pub fn dot_f32(va: &[f32], vb: &[f32]) -> f32 {
+    #[cfg(feature = "simd")]
+    {
+        let len = va.len().min(vb.len());
+        let (va, vb) = (&va[..len], &vb[..len]);
+        let mut lanes = [0.0f32; SIMD_LANES];
+        let achunks = va.chunks_exact(SIMD_LANES);
+        let bchunks = vb.chunks_exact(SIMD_LANES);
+        let (arem, brem) = (achunks.remainder(), bchunks.remainder());
+        for (ca, cb) in achunks.zip(bchunks) {
+            for ((l, a), b) in lanes.iter_mut().zip(ca).zip(cb) {
+                *l += a * b;
+            }
+        }
+        let mut d: f32 = lanes.iter().sum();
+        for (a, b) in arem.iter().zip(brem) {
+            d += a * b;
+        }
+        d
+    }
+    #[cfg(not(feature = "simd"))]
+    {
+        va.iter().zip(vb).map(|(a, b)| a * b).sum()
+    }
+}
+
+
+/// Calculate the sum of the values in the passed slice/vector of numeric values.
+pub fn vec_sum<T: AddAssign + Copy>(vdata: &[T]) -> T {
     let mut d = vdata[0];
-    for i in 1..vdata.len() {
-        d += vdata[i];
+    for v in &vdata[1..] {
+        d += *v;
     }
     d
 }
@@ -24,18 +97,149 @@ pub fn vec_sum<T: AddAssign + Copy>(vdata: &Vec<T>) -> T {
 ///
 /// So it supports f32,f64,u32,i32,u16. But doesnt support u8,i8,i16.
 ///
-pub fn vec_avg<T: AddAssign + From<u16> + Div<Output = T> + Copy>(vdata: &Vec<T>) -> T {
-    let mut d = vdata[0];
-    for i in 1..vdata.len() {
-        d += vdata[i];
-    }
+/// With the simd feature enabled, the sum is accumulated in a lane chunked manner.
+///
+/// NOTE: Panics if the passed vector is empty, independent of the simd feature.
+///
+pub fn vec_avg<T: AddAssign + From<u16> + Div<Output = T> + Copy>(vdata: &[T]) -> T {
+    assert!(!vdata.is_empty(), "ERRR:DU:SigPro:VecAvg:Empty data");
+    #[cfg(feature = "simd")]
+    let d = sum_chunked(vdata);
+    #[cfg(not(feature = "simd"))]
+    let d = {
+        let mut d = vdata[0];
+        for v in &vdata[1..] {
+            d += *v;
+        }
+        d
+    };
     d/(vdata.len() as u16).into()
 }
 
+/// Find the smallest and largest values in the passed slice, if any.
+///
+/// NaN values (if any) dont take part in the comparisons, unless they are at the start.
+pub fn vec_minmax<T: PartialOrd + Copy>(vdata: &[T]) -> Option<(T, T)> {
+    let (first, rest) = vdata.split_first()?;
+    let mut mm = (*first, *first);
+    for v in rest {
+        if *v < mm.0 {
+            mm.0 = *v;
+        }
+        if *v > mm.1 {
+            mm.1 = *v;
+        }
+    }
+    Some(mm)
+}
+
+/// Find the median of the passed slice of numeric values, if any.
+///
+/// For even number of values, the average of the middle two values is returned.
+/// The passed slice is not modified, a sorted copy is used internally.
+pub fn vec_median<T: PartialOrd + Copy + Into<f64>>(vdata: &[T]) -> Option<f64> {
+    if vdata.is_empty() {
+        return None;
+    }
+    let mut vsorted = vdata.to_vec();
+    vsorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(core::cmp::Ordering::Equal));
+    let mid = vsorted.len()/2;
+    if vsorted.len().is_multiple_of(2) {
+        Some((vsorted[mid-1].into() + vsorted[mid].into())/2.0)
+    } else {
+        Some(vsorted[mid].into())
+    }
+}
+
+/// Find the RMS (root mean square) of the passed slice of numeric values, if any.
+pub fn vec_rms<T: Copy + Into<f64>>(vdata: &[T]) -> Option<f64> {
+    if vdata.is_empty() {
+        return None;
+    }
+    let sq: f64 = vdata.iter().map(|v| { let v: f64 = (*v).into(); v*v }).sum();
+    Some(fmath::f64::sqrt(sq/(vdata.len() as f64)))
+}
+
+/// Find the median absolute deviation (from the median) of the passed slice of numeric
+/// values, if any, which is a estimate of the spread that is robust to outliers.
+///
+/// NOTE: To use it has a consistent estimator of the standard deviation for normally
+/// distributed data, scale it by 1.4826.
+pub fn vec_mad<T: PartialOrd + Copy + Into<f64>>(vdata: &[T]) -> Option<f64> {
+    let median = vec_median(vdata)?;
+    let vdev: Vec<f64> = vdata.iter().map(|v| ((*v).into() - median).abs()).collect();
+    vec_median(&vdev)
+}
+
+/// The robust z-score of each of the passed values, ie 0.6745*(value - median)/MAD, which
+/// unlike the normal z-score is not skewed by the outliers it is used to find. Values with
+/// a robust z-score beyond 3.5 are commonly treated has outliers.
+///
+/// None if there are no values or if the MAD is 0 (ie more than half the values are same).
+pub fn robust_zscores<T: PartialOrd + Copy + Into<f64>>(vdata: &[T]) -> Option<Vec<f64>> {
+    let median = vec_median(vdata)?;
+    let mad = vec_mad(vdata)?;
+    if mad == 0.0 {
+        return None;
+    }
+    Some(vdata.iter().map(|v| 0.6745*((*v).into() - median)/mad).collect())
+}
+
+/// Allow the common stats to be found directly on slices (and so Vecs) of numeric values,
+/// like data.avg(), data.median(), ...
+///
+/// Supports the same types has vec_avg, ie f32, f64, u32, i32 and u16.
+/// Unlike the vec_ functions, a empty slice gives None, rather than panicing.
+pub trait SliceNumExt {
+    type Elem;
+    /// The average, got by summing has f64 and dividing by the length.
+    /// Unlike vec_avg, this works for slices longer than u16::MAX.
+    fn avg(&self) -> Option<f64>;
+    /// The median, as found by vec_median
+    fn median(&self) -> Option<f64>;
+    /// The (min, max), as found by vec_minmax
+    fn minmax(&self) -> Option<(Self::Elem, Self::Elem)>;
+    /// The root mean square, as found by vec_rms
+    fn rms(&self) -> Option<f64>;
+    /// The median absolute deviation, as found by vec_mad
+    fn mad(&self) -> Option<f64>;
+}
+
+impl<T> SliceNumExt for [T]
+where T: AddAssign + From<u16> + Div<Output = T> + PartialOrd + Into<f64> + Copy
+{
+    type Elem = T;
+
+    fn avg(&self) -> Option<f64> {
+        if self.is_empty() {
+            return None;
+        }
+        let sum: f64 = self.iter().map(|&v| v.into()).sum();
+        Some(sum/(self.len() as f64))
+    }
+
+    fn median(&self) -> Option<f64> {
+        vec_median(self)
+    }
+
+    fn minmax(&self) -> Option<(T, T)> {
+        vec_minmax(self)
+    }
+
+    fn rms(&self) -> Option<f64> {
+        vec_rms(self)
+    }
+
+    fn mad(&self) -> Option<f64> {
+        vec_mad(self)
+    }
+
+}
+
 /// Sliding window averaging over a given window size
 ///
-/// The data is expected to be a vector of tuple of usize,f32,
-/// inturn the f32 part will be averaged wrt/over specified sliding window size
+/// The data is expected to be a slice of f32 values, which will be averaged
+/// wrt/over specified sliding window size
 ///
 /// Data at either end, which doesnt have sufficient elements on either side for
 /// sliding window based averaging, is left, as is.
@@ -43,68 +247,85 @@ pub fn vec_avg<T: AddAssign + From<u16> + Div<Output = T> + Copy>(vdata: &Vec<T>
 /// NOTE: A even window width/size will favor forward side bit more than backword side.
 /// NOTE: This acts like a low pass filter to an extent.
 ///
-pub fn sw_average_f_of_xf<M: Copy>(vdata: &Vec<(M, f32)>, ww: usize) -> Vec<(M, f32)> {
+pub fn sw_average(vdata: &[f32], ww: usize) -> Vec<f32> {
     let wwh = ww/2;
     let iwwh = wwh as isize;
     let weight = 1.0/(ww as f32);
+    let vbtw: Vec<f32> = vdata.iter().map(|f| f*weight).collect();
     let mut vnew = Vec::new();
-    let mut vbtw = Vec::new();
-    for i in 0..vdata.len() {
-        vbtw.push(vdata[i].1*weight);
-    }
-    for i in 0..wwh {
-        vnew.push(vdata[i]);
-    }
-    let wsi;
-    let wei;
-    if (ww > 0) && (ww % 2 == 0) {
-        wsi = -iwwh+1;
-        wei = iwwh;
+    vnew.extend_from_slice(&vdata[..wwh]);
+    let wsi = if (ww > 0) && ww.is_multiple_of(2) {
+        -iwwh+1
     } else {
-        wsi = -iwwh;
-        wei = iwwh;
-    }
+        -iwwh
+    };
+    let wei = iwwh;
     for i in wwh..vbtw.len()-wwh {
         let mut d = 0.0;
         for j in wsi..=wei {
             let di = (i as isize + j) as usize;
             d += vbtw[di];
         }
-        vnew.push((vdata[i].0, d));
-    }
-    for i in (1..=wwh).rev() {
-        vnew.push(vdata[vdata.len()-i]);
+        vnew.push(d);
     }
+    vnew.extend_from_slice(&vdata[vdata.len()-wwh..]);
     vnew
 }
 
+/// Sliding window averaging over a given window size
+///
+/// The data is expected to be a vector of tuple of usize,f32,
+/// inturn the f32 part will be averaged wrt/over specified sliding window size
+///
+/// Data at either end, which doesnt have sufficient elements on either side for
+/// sliding window based averaging, is left, as is.
+///
+/// NOTE: A even window width/size will favor forward side bit more than backword side.
+/// NOTE: This acts like a low pass filter to an extent.
+///
+pub fn sw_average_f_of_xf<M: Copy>(vdata: &[(M, f32)], ww: usize) -> Vec<(M, f32)> {
+    let vf: Vec<f32> = vdata.iter().map(|x| x.1).collect();
+    let vavg = sw_average(&vf, ww);
+    vdata.iter().zip(vavg).map(|(x, f)| (x.0, f)).collect()
+}
+
 /// Sliding window cross-correlation of given data with given weights
 ///
-/// The data is expected to be a vector of tuple (AnyTypeSupportingCopy,f32),
-/// inturn the f32 part will be cross-correlated with passed weights.
+/// The data is expected to be a slice of f32 values, which will be
+/// cross-correlated with passed weights.
 ///
 /// Datas at either end, which dont have enough elements on their either side to
 /// apply given weights over them to find the cross-correlated values, is replaced
 /// with value on either end, which can be computed fully wrt given weights vector.
 ///
-pub fn sw_crosscorr_f_of_xf<M: Copy>(vdata: &Vec<(M, f32)>, vweights: &Vec<f32>) -> Vec<(M, f32)> {
+/// NOTE: Panics if the weights are not of odd length or if there is less data than
+/// weights, independent of the simd feature.
+///
+pub fn crosscorr_weighted(vdata: &[f32], vweights: &[f32]) -> Vec<f32> {
     let ww = vweights.len();
-    let wwh = (ww/2) as usize;
-    let iwwh = wwh as isize;
+    assert!(ww % 2 == 1, "ERRR:DU:SigPro:CrossCorrWeighted:Weights length {} should be odd", ww);
+    assert!(vdata.len() >= ww, "ERRR:DU:SigPro:CrossCorrWeighted:Too few samples {} for weights {}", vdata.len(), ww);
+    let wwh = ww/2;
     let mut vnew = Vec::new();
     // Initial placeholders
-    for i in 0..wwh {
-        vnew.push(vdata[i]);
-    }
+    vnew.extend_from_slice(&vdata[..wwh]);
     // CrossCorrelated data
+    #[cfg(feature = "simd")]
     for i in wwh..(vdata.len()-wwh) {
-        let mut d = 0.0;
-        for j in -iwwh..=iwwh {
-            let wi = (j + iwwh) as usize;
-            let di = (i as isize + j) as usize;
-            d += vdata[di].1 * vweights[wi];
+        vnew.push(dot_f32(&vdata[i-wwh..i-wwh+ww], vweights));
+    }
+    #[cfg(not(feature = "simd"))]
+    {
+        let iwwh = wwh as isize;
+        for i in wwh..(vdata.len()-wwh) {
+            let mut d = 0.0;
+            for j in -iwwh..=iwwh {
+                let wi = (j + iwwh) as usize;
+                let di = (i as isize + j) as usize;
+                d += vdata[di] * vweights[wi];
+            }
+            vnew.push(d);
         }
-        vnew.push((vdata[i].0, d));
     }
     // Extend data at begin
     for i in 0..wwh {
@@ -113,8 +334,1472 @@ pub fn sw_crosscorr_f_of_xf<M: Copy>(vdata: &Vec<(M, f32)>, vweights: &Vec<f32>)
     // Extend data at end.
     let di = vdata.len() - wwh - 1;
     for _i in (1..=wwh).rev() {
-        //eprintln!("DBUG:SdlX:CrossCorrWeighted:{:?}:{:?}:{:?}:{}",vdata, vweights, vnew, fi);
         vnew.push(vnew[di]);
     }
     vnew
 }
+
+/// Sliding window cross-correlation of given data with given weights
+///
+/// The data is expected to be a vector of tuple (AnyTypeSupportingCopy,f32),
+/// inturn the f32 part will be cross-correlated with passed weights.
+///
+/// Datas at either end, which dont have enough elements on their either side to
+/// apply given weights over them to find the cross-correlated values, is replaced
+/// with value on either end, which can be computed fully wrt given weights vector.
+///
+pub fn sw_crosscorr_f_of_xf<M: Copy>(vdata: &[(M, f32)], vweights: &[f32]) -> Vec<(M, f32)> {
+    let vf: Vec<f32> = vdata.iter().map(|x| x.1).collect();
+    let vcc = crosscorr_weighted(&vf, vweights);
+    vdata.iter().zip(vcc).map(|(x, f)| (x.0, f)).collect()
+}
+
+/// The result of a least-squares linear fit, ie f(x) = slope*x + intercept
+///
+/// r2 is the coefficient of determination, which indicates how well the
+/// fitted line explains the variation in the data (1.0 being a perfect fit).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinFit {
+    pub slope: f64,
+    pub intercept: f64,
+    pub r2: f64,
+}
+
+/// Least-squares linear fit of the given (x, f) data points.
+///
+/// Needs atleast 2 data points, with not all x values being the same.
+/// If all the f values are the same, the fit is perfect, so r2 is set to 1.0.
+///
+pub fn linfit(vdata: &[(f64, f64)]) -> Result<LinFit, String> {
+    if vdata.len() < 2 {
+        return Err(format!("ERRR:DU:SigPro:LinFit:Need atleast 2 data points, got {}", vdata.len()));
+    }
+    let n = vdata.len() as f64;
+    let xavg = vdata.iter().map(|d| d.0).sum::<f64>() / n;
+    let favg = vdata.iter().map(|d| d.1).sum::<f64>() / n;
+    let mut sxx = 0.0;
+    let mut sxf = 0.0;
+    let mut sff = 0.0;
+    for (x, f) in vdata {
+        let dx = x - xavg;
+        let df = f - favg;
+        sxx += dx*dx;
+        sxf += dx*df;
+        sff += df*df;
+    }
+    if sxx == 0.0 {
+        return Err("ERRR:DU:SigPro:LinFit:All x values are the same, cant fit a line".to_string());
+    }
+    let slope = sxf/sxx;
+    let intercept = favg - slope*xavg;
+    let r2 = if sff == 0.0 {
+        1.0
+    } else {
+        let mut sres = 0.0;
+        for (x, f) in vdata {
+            let e = f - (slope*x + intercept);
+            sres += e*e;
+        }
+        1.0 - sres/sff
+    };
+    Ok(LinFit { slope, intercept, r2 })
+}
+
+/// The result of fitting f = amplitude * exp(-x/tau) + offset, refer to fit_exp_decay
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExpFit {
+    pub amplitude: f64,
+    pub tau: f64,
+    pub offset: f64,
+}
+
+/// Fit log(sign*(f - offset)) = log(amplitude) - x/tau, using linfit, for the given offset.
+/// Returns the fit along with the sum of squared errors wrt the original data.
+fn fit_exp_for_offset(vdata: &[(f64, f32)], offset: f64, sign: f64) -> Option<(ExpFit, f64)> {
+    let vlog: Vec<(f64, f64)> = vdata.iter().map(|(x, f)| (*x, fmath::f64::ln(sign*(*f as f64 - offset)))).collect();
+    let lfit = linfit(&vlog).ok()?;
+    if lfit.slope.is_nan() || lfit.slope >= 0.0 {
+        return None;
+    }
+    let fit = ExpFit { amplitude: sign*fmath::f64::exp(lfit.intercept), tau: -1.0/lfit.slope, offset };
+    let sse = vdata.iter().map(|(x, f)| {
+        let e = *f as f64 - (fit.amplitude*fmath::f64::exp(-x/fit.tau) + fit.offset);
+        e*e
+    }).sum();
+    Some((fit, sse))
+}
+
+/// Fit a exponential decay f = amplitude * exp(-x/tau) + offset to the given (x, f) data,
+/// like the step response of a first order system.
+///
+/// For a given offset, the fit is a log-linear fit, and the offset itself is found by a
+/// golden section search, which minimises the squared error wrt the data. The data can
+/// either decay down (amplitude > 0) or rise up (amplitude < 0) towards the offset, which
+/// is decided based on whether the first f value is above or below the last one.
+///
+/// Needs atleast 3 data points, which are not all the same and which do decay.
+///
+pub fn fit_exp_decay(vdata: &[(f64, f32)]) -> Result<ExpFit, String> {
+    if vdata.len() < 3 {
+        return Err(format!("ERRR:DU:SigPro:FitExpDecay:Need atleast 3 data points, got {}", vdata.len()));
+    }
+    let fmin = vdata.iter().map(|d| d.1 as f64).fold(f64::INFINITY, f64::min);
+    let fmax = vdata.iter().map(|d| d.1 as f64).fold(f64::NEG_INFINITY, f64::max);
+    let span = fmax - fmin;
+    if span.is_nan() || span <= 0.0 {
+        return Err("ERRR:DU:SigPro:FitExpDecay:All f values are the same, nothing to fit".to_string());
+    }
+    let sign = if vdata[0].1 >= vdata[vdata.len()-1].1 { 1.0 } else { -1.0 };
+    // The offset is beyond the extreme towards which the data decays, map u in (0, 1) to it
+    let offset_of = |u: f64| if sign > 0.0 { fmin - span*u/(1.0-u) } else { fmax + span*u/(1.0-u) };
+    let sse_of = |u: f64| fit_exp_for_offset(vdata, offset_of(u), sign).map(|r| r.1).unwrap_or(f64::INFINITY);
+    let gr = (fmath::f64::sqrt(5.0) - 1.0)/2.0;
+    let (mut ua, mut ub) = (1e-9, 1.0 - 1e-9);
+    let mut uc = ub - gr*(ub - ua);
+    let mut ud = ua + gr*(ub - ua);
+    let (mut sc, mut sd) = (sse_of(uc), sse_of(ud));
+    for _ in 0..100 {
+        if sc < sd {
+            ub = ud;
+            ud = uc;
+            sd = sc;
+            uc = ub - gr*(ub - ua);
+            sc = sse_of(uc);
+        } else {
+            ua = uc;
+            uc = ud;
+            sc = sd;
+            ud = ua + gr*(ub - ua);
+            sd = sse_of(ud);
+        }
+    }
+    match fit_exp_for_offset(vdata, offset_of((ua + ub)/2.0), sign) {
+        Some((fit, _)) => Ok(fit),
+        None => Err("ERRR:DU:SigPro:FitExpDecay:Data doesnt seem to decay exponentially".to_string()),
+    }
+}
+
+/// How the samples falling within a bucket are reduced to a single sample
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Agg {
+    /// average of the x and f values in the bucket
+    Mean,
+    /// the sample with the smallest f value
+    Min,
+    /// the sample with the largest f value
+    Max,
+    /// the first sample (in the given order) in the bucket
+    First,
+    /// the last sample (in the given order) in the bucket
+    Last,
+}
+
+/// Downsample the given (x, f) data into atmost n_buckets samples.
+///
+/// The x range of the data is split into n_buckets equal width buckets and
+/// the samples in each bucket are reduced to a single sample as specified by agg.
+/// Empty buckets dont generate any sample, so one may get fewer samples.
+///
+/// The samples generated are ordered as per their buckets, ie along increasing x.
+///
+pub fn downsample_buckets(vdata: &[(f64, f32)], n_buckets: usize, agg: Agg) -> Vec<(f64, f32)> {
+    if vdata.is_empty() || (n_buckets == 0) {
+        return Vec::new();
+    }
+    let xmin = vdata.iter().map(|d| d.0).fold(f64::INFINITY, f64::min);
+    let xmax = vdata.iter().map(|d| d.0).fold(f64::NEG_INFINITY, f64::max);
+    let bw = (xmax - xmin)/(n_buckets as f64);
+    let mut vbuckets: Vec<Vec<(f64, f32)>> = vec![Vec::new(); n_buckets];
+    for d in vdata {
+        let mut bi = if bw > 0.0 {
+            ((d.0 - xmin)/bw) as usize
+        } else {
+            0
+        };
+        if bi >= n_buckets {
+            bi = n_buckets - 1;
+        }
+        vbuckets[bi].push(*d);
+    }
+    let mut vnew = Vec::new();
+    for vb in vbuckets.iter().filter(|vb| !vb.is_empty()) {
+        let d = match agg {
+            Agg::Mean => {
+                let n = vb.len() as f64;
+                let x = vb.iter().map(|d| d.0).sum::<f64>()/n;
+                let f = vb.iter().map(|d| d.1 as f64).sum::<f64>()/n;
+                (x, f as f32)
+            }
+            Agg::Min => *vb.iter().reduce(|a, b| if b.1 < a.1 { b } else { a }).unwrap(),
+            Agg::Max => *vb.iter().reduce(|a, b| if b.1 > a.1 { b } else { a }).unwrap(),
+            Agg::First => vb[0],
+            Agg::Last => vb[vb.len()-1],
+        };
+        vnew.push(d);
+    }
+    vnew
+}
+
+/// Clamp the values in the given series to be within lo and hi (inclusive),
+/// inturn returning the number of values which were clipped.
+///
+/// lo is expected to be <= hi. NaN values are left as is.
+///
+pub fn clamp_series_counted(vdata: &mut [f32], lo: f32, hi: f32) -> usize {
+    let mut clipped = 0;
+    for d in vdata.iter_mut() {
+        if *d < lo {
+            *d = lo;
+            clipped += 1;
+        } else if *d > hi {
+            *d = hi;
+            clipped += 1;
+        }
+    }
+    clipped
+}
+
+/// Clamp the values in the given series to be within lo and hi (inclusive).
+///
+/// lo is expected to be <= hi. NaN values are left as is.
+///
+pub fn clamp_series(vdata: &mut [f32], lo: f32, hi: f32) {
+    clamp_series_counted(vdata, lo, hi);
+}
+
+/// The clipping (saturation) found in a series, refer to detect_clipping
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ClipReport {
+    /// number of samples which are clipped
+    pub count: usize,
+    /// length of the longest run of consecutive clipped samples
+    pub longest_run: usize,
+    /// the index ranges of each run of consecutive clipped samples
+    pub ranges: Vec<core::ops::Range<usize>>,
+}
+
+/// Find the samples in the given series, which have hit the given full scale value,
+/// ie abs(sample) >= full_scale, which indicates that the signal got saturated
+/// when it was captured.
+///
+/// full_scale is expected to be positive. NaN values are not treated has clipped.
+///
+pub fn detect_clipping(vdata: &[f32], full_scale: f32) -> ClipReport {
+    let mut report = ClipReport::default();
+    let mut runstart = None;
+    for (i, d) in vdata.iter().enumerate() {
+        let clipped = d.abs() >= full_scale;
+        match (clipped, runstart) {
+            (true, None) => runstart = Some(i),
+            (false, Some(si)) => {
+                report.ranges.push(si..i);
+                runstart = None;
+            }
+            _ => (),
+        }
+    }
+    if let Some(si) = runstart {
+        report.ranges.push(si..vdata.len());
+    }
+    report.count = report.ranges.iter().map(|r| r.len()).sum();
+    report.longest_run = report.ranges.iter().map(|r| r.len()).max().unwrap_or(0);
+    report
+}
+
+/// How values are generated at the grid points of a uniform grid
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FillPolicy {
+    /// linearly interpolate between the samples on either side
+    Linear,
+    /// use the value of the nearest sample at or before the grid point
+    HoldLast,
+}
+
+/// Resample the given (x, f) data onto a uniformly spaced grid of step dt,
+/// starting at the first sample's x and going till the last sample's x.
+///
+/// The data is expected to be sorted in non-decreasing order of x.
+///
+pub fn to_uniform_grid(vdata: &[(f64, f32)], dt: f64, fill: FillPolicy) -> Result<Vec<(f64, f32)>, String> {
+    if dt <= 0.0 {
+        return Err(format!("ERRR:DU:SigPro:ToUniformGrid:Invalid step dt {}", dt));
+    }
+    if vdata.windows(2).any(|w| w[1].0 < w[0].0) {
+        return Err("ERRR:DU:SigPro:ToUniformGrid:Data not sorted wrt x".to_string());
+    }
+    let mut vnew = Vec::new();
+    if vdata.is_empty() {
+        return Ok(vnew);
+    }
+    let x0 = vdata[0].0;
+    let xn = vdata[vdata.len()-1].0;
+    let steps = fmath::f64::floor((xn - x0)/dt) as usize;
+    let mut j = 0;
+    for k in 0..=steps {
+        let t = x0 + (k as f64)*dt;
+        while (j+1 < vdata.len()) && (vdata[j+1].0 <= t) {
+            j += 1;
+        }
+        let (xj, fj) = vdata[j];
+        let f = match fill {
+            FillPolicy::HoldLast => fj,
+            FillPolicy::Linear => {
+                if j+1 < vdata.len() {
+                    let (xk, fk) = vdata[j+1];
+                    let r = (t - xj)/(xk - xj);
+                    fj + ((fk - fj) as f64 * r) as f32
+                } else {
+                    fj
+                }
+            }
+        };
+        vnew.push((t, f));
+    }
+    Ok(vnew)
+}
+
+/// Sliding window RMS (root mean square) over a given window size
+///
+/// The window is centered around each sample in the same way as sw_average.
+/// Data at either end, which doesnt have sufficient elements on either side,
+/// uses the rms of the part of the window which is available.
+///
+pub fn sw_rms(vdata: &[f32], ww: usize) -> Vec<f32> {
+    let ww = ww.max(1);
+    let wwh = ww/2;
+    let wsh = if ww.is_multiple_of(2) { wwh - 1 } else { wwh };
+    let mut vsq = Vec::with_capacity(vdata.len()+1);
+    vsq.push(0.0f64);
+    for d in vdata {
+        let sq = (*d as f64) * (*d as f64);
+        vsq.push(vsq[vsq.len()-1] + sq);
+    }
+    let mut vnew = Vec::with_capacity(vdata.len());
+    for i in 0..vdata.len() {
+        let si = i.saturating_sub(wsh);
+        let ei = (i + wwh + 1).min(vdata.len());
+        let d = (vsq[ei] - vsq[si])/((ei - si) as f64);
+        vnew.push(fmath::f64::sqrt(d.max(0.0)) as f32);
+    }
+    vnew
+}
+
+/// Sliding window RMS (root mean square) over a given window size
+///
+/// The data is expected to be a vector of tuple (AnyTypeSupportingCopy,f32),
+/// inturn the rms of the f32 part is found, as done by sw_rms.
+///
+pub fn sw_rms_f_of_xf<M: Copy>(vdata: &[(M, f32)], ww: usize) -> Vec<(M, f32)> {
+    let vf: Vec<f32> = vdata.iter().map(|x| x.1).collect();
+    let vrms = sw_rms(&vf, ww);
+    vdata.iter().zip(vrms).map(|(x, f)| (x.0, f)).collect()
+}
+
+/// Pearson correlation of the given pair of series
+fn pearson(va: &[f32], vb: &[f32]) -> f32 {
+    let n = va.len() as f64;
+    let ma = va.iter().map(|a| *a as f64).sum::<f64>()/n;
+    let mb = vb.iter().map(|b| *b as f64).sum::<f64>()/n;
+    let (mut sab, mut saa, mut sbb) = (0.0f64, 0.0f64, 0.0f64);
+    for (a, b) in va.iter().zip(vb) {
+        let da = *a as f64 - ma;
+        let db = *b as f64 - mb;
+        sab += da*db;
+        saa += da*da;
+        sbb += db*db;
+    }
+    (sab/fmath::f64::sqrt(saa*sbb)) as f32
+}
+
+/// Sliding window (rolling) Pearson correlation between the given pair of series.
+///
+/// The window is centered around each sample in the same way as sw_rms, and the
+/// correlation of the a and b samples within the window is found. Data at either end,
+/// which doesnt have sufficient elements on either side, uses the part of the window
+/// which is available.
+///
+/// Only the overlapping part is used, if the lengths differ.
+/// If either series is constant within a window, the correlation is undefined, ie NaN.
+///
+pub fn sw_correlation(va: &[f32], vb: &[f32], ww: usize) -> Vec<f32> {
+    let len = va.len().min(vb.len());
+    let ww = ww.max(1);
+    let wwh = ww/2;
+    let wsh = if ww.is_multiple_of(2) { wwh.saturating_sub(1) } else { wwh };
+    let mut vnew = Vec::with_capacity(len);
+    for i in 0..len {
+        let si = i.saturating_sub(wsh);
+        let ei = (i + wwh + 1).min(len);
+        vnew.push(pearson(&va[si..ei], &vb[si..ei]));
+    }
+    vnew
+}
+
+/// A attack/release envelope follower, which tracks the amplitude envelope
+/// of a stream of samples, one sample at a time.
+///
+/// The envelope rises towards a larger amplitude using the attack coefficient,
+/// and decays towards a smaller amplitude using the release coefficient.
+#[derive(Debug, Clone)]
+pub struct EnvelopeFollower {
+    attack: f32,
+    release: f32,
+    env: f32,
+}
+
+impl EnvelopeFollower {
+
+    /// Create a envelope follower, with the attack and release time constants
+    /// specified in terms of number of samples.
+    /// A time constant of 0 (or less) makes the envelope follow immidiately.
+    pub fn new(attack_samples: f32, release_samples: f32) -> EnvelopeFollower {
+        let coef = |samples: f32| {
+            if samples <= 0.0 {
+                0.0
+            } else {
+                fmath::f32::exp(-1.0/samples)
+            }
+        };
+        EnvelopeFollower {
+            attack: coef(attack_samples),
+            release: coef(release_samples),
+            env: 0.0,
+        }
+    }
+
+    /// Update the envelope with the given sample and return the current envelope
+    pub fn push(&mut self, sample: f32) -> f32 {
+        let amp = sample.abs();
+        let coef = if amp > self.env { self.attack } else { self.release };
+        self.env = amp + coef*(self.env - amp);
+        self.env
+    }
+
+    /// Get the current envelope
+    pub fn envelope(&self) -> f32 {
+        self.env
+    }
+
+    /// Reset the envelope back to 0
+    pub fn reset(&mut self) {
+        self.env = 0.0;
+    }
+
+}
+
+/// Find the power (mean of squares) of the given data
+fn power(vdata: &[f32]) -> f64 {
+    vdata.iter().map(|d| (*d as f64)*(*d as f64)).sum::<f64>()/(vdata.len() as f64)
+}
+
+/// Signal to noise ratio in dB, given the signal and noise samples.
+///
+/// If the noise has no power, it returns positive infinity.
+///
+pub fn snr(vsignal: &[f32], vnoise: &[f32]) -> Result<f32, String> {
+    if vsignal.is_empty() || vnoise.is_empty() {
+        return Err("ERRR:DU:SigPro:Snr:Empty signal or noise".to_string());
+    }
+    Ok((10.0*fmath::f64::log10(power(vsignal)/power(vnoise))) as f32)
+}
+
+/// Signal to noise ratio in dB, of the given measured samples wrt the given
+/// reference (ie ideal) samples. The difference between them is treated as noise.
+///
+pub fn snr_wrt_reference(vmeasured: &[f32], vreference: &[f32]) -> Result<f32, String> {
+    if vmeasured.len() != vreference.len() {
+        return Err(format!("ERRR:DU:SigPro:SnrWrtRef:Length mismatch {} vs {}", vmeasured.len(), vreference.len()));
+    }
+    let vnoise: Vec<f32> = vmeasured.iter().zip(vreference).map(|(m, r)| m - r).collect();
+    snr(vreference, &vnoise)
+}
+
+/// Amplitude of the given frequency component in the data, found using a
+/// single bin DFT. The freq is in cycles per sample.
+fn dft_amplitude(vdata: &[f32], freq: f64) -> f64 {
+    let w = 2.0*core::f64::consts::PI*freq;
+    let mut re = 0.0;
+    let mut im = 0.0;
+    for (n, d) in vdata.iter().enumerate() {
+        let a = w*(n as f64);
+        re += (*d as f64)*fmath::f64::cos(a);
+        im -= (*d as f64)*fmath::f64::sin(a);
+    }
+    2.0*fmath::f64::sqrt(re*re + im*im)/(vdata.len() as f64)
+}
+
+/// A basic estimate of the total harmonic distortion of the given data, as a ratio
+/// (and not percentage), ie rms of the harmonics amplitudes / fundamental amplitude.
+///
+/// The fundamental frequency is specified in cycles per sample (ie freq/samplerate).
+/// The 2nd to (harmonics+1)th harmonics, which are below nyquist, are considered.
+///
+/// NOTE: For a accurate estimate, the data should contain a integral number of
+/// cycles of the fundamental.
+///
+pub fn thd(vdata: &[f32], fundamental: f64, harmonics: usize) -> Result<f32, String> {
+    if vdata.is_empty() || (fundamental <= 0.0) || (fundamental >= 0.5) {
+        return Err(format!("ERRR:DU:SigPro:Thd:Empty data or invalid fundamental {}", fundamental));
+    }
+    let a1 = dft_amplitude(vdata, fundamental);
+    if a1 == 0.0 {
+        return Err("ERRR:DU:SigPro:Thd:No fundamental component in data".to_string());
+    }
+    let mut hsq = 0.0;
+    for h in 2..=harmonics+1 {
+        let hfreq = fundamental*(h as f64);
+        if hfreq >= 0.5 {
+            break;
+        }
+        let ah = dft_amplitude(vdata, hfreq);
+        hsq += ah*ah;
+    }
+    Ok((fmath::f64::sqrt(hsq)/a1) as f32)
+}
+
+/// How samples of the second series are matched to samples of the first series
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JoinMode {
+    /// use the nearest sample (in x) of the second series
+    Nearest,
+    /// linearly interpolate between the samples of the second series on either side
+    Interpolate,
+}
+
+/// Time align the given pair of (x, f) series, producing (xa, fa, fb) samples,
+/// with each sample of series a being matched with a value from series b.
+///
+/// Only b samples within tolerance (in x) of the a sample are used for matching.
+/// For Interpolate, b samples on either side (or a exact match) are needed.
+/// The a samples which cant be matched are dropped.
+///
+/// Both series are expected to be sorted in non-decreasing order of x.
+///
+pub fn join_series(va: &[(f64, f32)], vb: &[(f64, f32)], tolerance: f64, mode: JoinMode) -> Result<Vec<(f64, f32, f32)>, String> {
+    if va.windows(2).any(|w| w[1].0 < w[0].0) || vb.windows(2).any(|w| w[1].0 < w[0].0) {
+        return Err("ERRR:DU:SigPro:JoinSeries:Series not sorted wrt x".to_string());
+    }
+    let mut vnew = Vec::new();
+    if vb.is_empty() {
+        return Ok(vnew);
+    }
+    // k tracks the number of b samples at or before the current a sample
+    let mut k = 0;
+    for (xa, fa) in va {
+        while (k < vb.len()) && (vb[k].0 <= *xa) {
+            k += 1;
+        }
+        let oprev = if k > 0 { Some(vb[k-1]) } else { None };
+        let onext = vb.get(k).copied();
+        let ofb = match mode {
+            JoinMode::Nearest => {
+                let dp = oprev.map(|p| (*xa - p.0, p.1));
+                let dn = onext.map(|n| (n.0 - *xa, n.1));
+                let near = match (dp, dn) {
+                    (Some(dp), Some(dn)) => if dn.0 < dp.0 { Some(dn) } else { Some(dp) },
+                    (dp, dn) => dp.or(dn),
+                };
+                near.filter(|d| d.0 <= tolerance).map(|d| d.1)
+            }
+            JoinMode::Interpolate => {
+                match (oprev, onext) {
+                    (Some(p), _) if p.0 == *xa => Some(p.1),
+                    (Some(p), Some(n)) if (*xa - p.0 <= tolerance) && (n.0 - *xa <= tolerance) => {
+                        let r = (*xa - p.0)/(n.0 - p.0);
+                        Some(p.1 + ((n.1 - p.1) as f64 * r) as f32)
+                    }
+                    _ => None,
+                }
+            }
+        };
+        if let Some(fb) = ofb {
+            vnew.push((*xa, *fa, fb));
+        }
+    }
+    Ok(vnew)
+}
+
+/// Sliding window extrema using a monotonic deque, so that its O(n) overall,
+/// independent of the window size. The window is centered around each sample
+/// in the same way as sw_average, and is truncated at either end of the data.
+///
+/// keep_first(a, b) should return true, if a should be preferred over b.
+fn sw_extrema(vdata: &[f32], ww: usize, keep_first: fn(f32, f32) -> bool) -> Vec<f32> {
+    let ww = ww.max(1);
+    let wwh = ww/2;
+    let wsh = if ww.is_multiple_of(2) { wwh - 1 } else { wwh };
+    let mut vnew = Vec::with_capacity(vdata.len());
+    let mut dq: VecDeque<usize> = VecDeque::new();
+    let mut next = 0;
+    for i in 0..vdata.len() {
+        let ei = (i + wwh).min(vdata.len() - 1);
+        while next <= ei {
+            while let Some(&bi) = dq.back() {
+                if keep_first(vdata[bi], vdata[next]) {
+                    break;
+                }
+                dq.pop_back();
+            }
+            dq.push_back(next);
+            next += 1;
+        }
+        let si = i.saturating_sub(wsh);
+        while dq[0] < si {
+            dq.pop_front();
+        }
+        vnew.push(vdata[dq[0]]);
+    }
+    vnew
+}
+
+/// Sliding window minimum over a given window size
+///
+/// The data is expected to be a vector of tuple (AnyTypeSupportingCopy,f32),
+/// inturn the minimum of the f32 part over the window around each sample is found.
+/// The window is centered in the same way as sw_average, and is truncated at
+/// either end of the data.
+///
+pub fn sw_min_f_of_xf<M: Copy>(vdata: &[(M, f32)], ww: usize) -> Vec<(M, f32)> {
+    let vf: Vec<f32> = vdata.iter().map(|x| x.1).collect();
+    let vmin = sw_extrema(&vf, ww, |a, b| a < b);
+    vdata.iter().zip(vmin).map(|(x, f)| (x.0, f)).collect()
+}
+
+/// Sliding window maximum over a given window size
+///
+/// The data is expected to be a vector of tuple (AnyTypeSupportingCopy,f32),
+/// inturn the maximum of the f32 part over the window around each sample is found.
+/// The window is centered in the same way as sw_average, and is truncated at
+/// either end of the data.
+///
+pub fn sw_max_f_of_xf<M: Copy>(vdata: &[(M, f32)], ww: usize) -> Vec<(M, f32)> {
+    let vf: Vec<f32> = vdata.iter().map(|x| x.1).collect();
+    let vmax = sw_extrema(&vf, ww, |a, b| a > b);
+    vdata.iter().zip(vmax).map(|(x, f)| (x.0, f)).collect()
+}
+
+/// A simple scalar Kalman filter, for smoothing a noisy stream of measurements
+/// of a (mostly) constant or slowly varying value, one sample at a time.
+///
+/// The first measurement is used as is, to initialise the estimate.
+#[derive(Debug, Clone)]
+pub struct Kalman1D {
+    /// process noise variance, ie how much the true value can drift per step
+    q: f32,
+    /// measurement noise variance
+    r: f32,
+    /// current estimate and its error variance
+    x: Option<f32>,
+    p: f32,
+}
+
+impl Kalman1D {
+
+    /// Create a filter with the given process and measurement noise variances
+    pub fn new(process_noise: f32, measurement_noise: f32) -> Kalman1D {
+        Kalman1D {
+            q: process_noise,
+            r: measurement_noise,
+            x: None,
+            p: 0.0,
+        }
+    }
+
+    /// Update the filter with the given measurement and return the new estimate
+    pub fn push(&mut self, measurement: f32) -> f32 {
+        let x = match self.x {
+            None => {
+                self.p = self.r;
+                measurement
+            }
+            Some(x) => {
+                let p = self.p + self.q;
+                let k = if (p + self.r) > 0.0 { p/(p + self.r) } else { 1.0 };
+                self.p = (1.0 - k)*p;
+                x + k*(measurement - x)
+            }
+        };
+        self.x = Some(x);
+        x
+    }
+
+    /// Get the current estimate, if any measurement has been pushed
+    pub fn estimate(&self) -> Option<f32> {
+        self.x
+    }
+
+    /// Forget the current estimate
+    pub fn reset(&mut self) {
+        self.x = None;
+        self.p = 0.0;
+    }
+
+}
+
+/// A moving average over the last W samples, one sample at a time, with the window
+/// held in a fixed size array, so that no heap allocation is involved.
+///
+/// Till W samples have been pushed, its the average of the samples pushed so far.
+#[derive(Debug, Clone)]
+pub struct MovingAverageConst<const W: usize> {
+    window: [f32; W],
+    /// position in the window where the next sample goes
+    pos: usize,
+    count: usize,
+    sum: f32,
+}
+
+impl<const W: usize> Default for MovingAverageConst<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize> MovingAverageConst<W> {
+
+    /// A const fn, so that the filter can be created in a static or const context
+    pub const fn new() -> Self {
+        const { assert!(W > 0, "MovingAverageConst window should be atleast 1") };
+        MovingAverageConst { window: [0.0; W], pos: 0, count: 0, sum: 0.0 }
+    }
+
+    /// Add the given sample and return the current average
+    pub fn push(&mut self, sample: f32) -> f32 {
+        if self.count == W {
+            self.sum -= self.window[self.pos];
+        } else {
+            self.count += 1;
+        }
+        self.window[self.pos] = sample;
+        self.sum += sample;
+        self.pos = (self.pos + 1) % W;
+        // Recalculate the sum once every window, so that float errors dont accumulate
+        if self.pos == 0 {
+            self.sum = self.window.iter().sum();
+        }
+        self.sum/(self.count as f32)
+    }
+
+    /// The current average, if any sample has been pushed
+    pub fn average(&self) -> Option<f32> {
+        if self.count == 0 {
+            return None;
+        }
+        Some(self.sum/(self.count as f32))
+    }
+
+    /// Forget the samples pushed so far
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+}
+
+/// A moving average over the last w samples, one sample at a time, which is the
+/// streaming counterpart of sw_average. Unlike MovingAverageConst, the window size
+/// is decided at runtime.
+///
+/// Nothing is returned till w samples have been pushed, ie till the window is full.
+#[derive(Debug, Clone)]
+pub struct MovingAverage {
+    window: VecDeque<f32>,
+    ww: usize,
+    sum: f64,
+}
+
+impl MovingAverage {
+
+    /// Create a moving average over the given window size, which is treated has 1 if 0
+    pub fn new(ww: usize) -> MovingAverage {
+        let ww = ww.max(1);
+        MovingAverage { window: VecDeque::with_capacity(ww), ww, sum: 0.0 }
+    }
+
+    /// Add the given sample and return the average over the window, once it is full
+    pub fn push(&mut self, sample: f32) -> Option<f32> {
+        if self.window.len() == self.ww {
+            self.sum -= self.window.pop_front().unwrap_or(0.0) as f64;
+        }
+        self.window.push_back(sample);
+        self.sum += sample as f64;
+        if self.window.len() < self.ww {
+            return None;
+        }
+        Some((self.sum/(self.ww as f64)) as f32)
+    }
+
+    /// Forget the samples pushed so far
+    pub fn reset(&mut self) {
+        self.window.clear();
+        self.sum = 0.0;
+    }
+
+}
+
+/// A FIR filter, which applies the given weights to the last weights.len() samples,
+/// one sample at a time, which is the streaming counterpart of crosscorr_weighted.
+/// `weights[0]` applies to the oldest sample within the window, same has crosscorr_weighted.
+///
+/// Nothing is returned till weights.len() samples have been pushed, ie till the window is full.
+#[derive(Debug, Clone)]
+pub struct FirFilter {
+    weights: Vec<f32>,
+    window: VecDeque<f32>,
+}
+
+impl FirFilter {
+
+    /// Create a filter using the given weights, which should have atleast 1 weight
+    pub fn new(weights: &[f32]) -> Result<FirFilter, String> {
+        if weights.is_empty() {
+            return Err("ERRR:DU:SigPro:FirFilter:Need atleast 1 weight".to_string());
+        }
+        Ok(FirFilter { weights: weights.to_vec(), window: VecDeque::with_capacity(weights.len()) })
+    }
+
+    /// Add the given sample and return the filtered value, once the window is full
+    pub fn push(&mut self, sample: f32) -> Option<f32> {
+        if self.window.len() == self.weights.len() {
+            self.window.pop_front();
+        }
+        self.window.push_back(sample);
+        if self.window.len() < self.weights.len() {
+            return None;
+        }
+        Some(dot_f32(self.window.make_contiguous(), &self.weights))
+    }
+
+    /// Forget the samples pushed so far
+    pub fn reset(&mut self) {
+        self.window.clear();
+    }
+
+}
+
+///
+/// Maintain the most recent capacity number of (timestamp, value) samples of a endless
+/// stream, along with the rolling mean, min and max over them, each of which is O(1)
+/// (amortized wrt min and max) to update on push and to query.
+///
+/// NaN values are ignored wrt min and max, but will make the mean NaN, while they are
+/// within the window. Similarly infinities make the mean infinite (or NaN, if both signs
+/// are present), only while they are within the window.
+///
+#[derive(Debug, Clone)]
+pub struct RingSeries {
+    samples: VecDeque<(f64, f32)>,
+    capacity: usize,
+    /// The sum of the finite values, the non finite ones are counted seperately, so
+    /// that they dont stick around in the sum after they leave the window.
+    sum: f64,
+    nnans: usize,
+    npinfs: usize,
+    nninfs: usize,
+    /// The sequence number of the next sample
+    seq: u64,
+    /// The candidate (seq, value) mins in increasing seq order, with increasing values
+    vmins: VecDeque<(u64, f32)>,
+    /// The candidate (seq, value) maxs in increasing seq order, with decreasing values
+    vmaxs: VecDeque<(u64, f32)>,
+}
+
+impl RingSeries {
+
+    /// Create a ring of the given capacity, which is treated has 1 if 0
+    pub fn new(capacity: usize) -> RingSeries {
+        let capacity = capacity.max(1);
+        RingSeries { samples: VecDeque::with_capacity(capacity), capacity, sum: 0.0, nnans: 0, npinfs: 0, nninfs: 0, seq: 0, vmins: VecDeque::new(), vmaxs: VecDeque::new() }
+    }
+
+    /// Account the given value wrt the sum (or the non finite counts), adding or removing it
+    fn account(&mut self, value: f32, add: bool) {
+        let count = if value.is_nan() {
+            &mut self.nnans
+        } else if value == f32::INFINITY {
+            &mut self.npinfs
+        } else if value == f32::NEG_INFINITY {
+            &mut self.nninfs
+        } else {
+            self.sum += if add { value as f64 } else { -(value as f64) };
+            return;
+        };
+        if add { *count += 1 } else { *count -= 1 }
+    }
+
+    /// Add the given sample, dropping the oldest sample if the ring is full
+    pub fn push(&mut self, timestamp: f64, value: f32) {
+        if self.samples.len() == self.capacity {
+            if let Some((_, oldv)) = self.samples.pop_front() {
+                self.account(oldv, false);
+            }
+        }
+        let seq = self.seq;
+        self.seq += 1;
+        self.samples.push_back((timestamp, value));
+        self.account(value, true);
+        if !value.is_nan() {
+            while self.vmins.back().is_some_and(|(_, v)| *v >= value) {
+                self.vmins.pop_back();
+            }
+            self.vmins.push_back((seq, value));
+            while self.vmaxs.back().is_some_and(|(_, v)| *v <= value) {
+                self.vmaxs.pop_back();
+            }
+            self.vmaxs.push_back((seq, value));
+        }
+        let oldest = self.seq - self.samples.len() as u64;
+        while self.vmins.front().is_some_and(|(s, _)| *s < oldest) {
+            self.vmins.pop_front();
+        }
+        while self.vmaxs.front().is_some_and(|(s, _)| *s < oldest) {
+            self.vmaxs.pop_front();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The mean of the values in the ring
+    pub fn mean(&self) -> Option<f32> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        if (self.nnans > 0) || (self.npinfs > 0 && self.nninfs > 0) {
+            return Some(f32::NAN);
+        }
+        if self.npinfs > 0 {
+            return Some(f32::INFINITY);
+        }
+        if self.nninfs > 0 {
+            return Some(f32::NEG_INFINITY);
+        }
+        Some((self.sum/self.samples.len() as f64) as f32)
+    }
+
+    /// The min of the (non NaN) values in the ring
+    pub fn min(&self) -> Option<f32> {
+        self.vmins.front().map(|(_, v)| *v)
+    }
+
+    /// The max of the (non NaN) values in the ring
+    pub fn max(&self) -> Option<f32> {
+        self.vmaxs.front().map(|(_, v)| *v)
+    }
+
+    /// The oldest sample in the ring
+    pub fn oldest(&self) -> Option<(f64, f32)> {
+        self.samples.front().copied()
+    }
+
+    /// The latest sample in the ring
+    pub fn latest(&self) -> Option<(f64, f32)> {
+        self.samples.back().copied()
+    }
+
+    /// The time elapsed since the latest sample, wrt the given current time
+    pub fn age(&self, now: f64) -> Option<f64> {
+        self.latest().map(|(ts, _)| now - ts)
+    }
+
+    /// The time covered by the samples in the ring, ie latest - oldest timestamp
+    pub fn span(&self) -> Option<f64> {
+        Some(self.latest()?.0 - self.oldest()?.0)
+    }
+
+    /// Iterate over the samples, from the oldest to the latest
+    pub fn iter(&self) -> impl Iterator<Item = &(f64, f32)> + '_ {
+        self.samples.iter()
+    }
+
+    /// Forget the samples pushed so far
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.vmins.clear();
+        self.vmaxs.clear();
+        self.sum = 0.0;
+        self.nnans = 0;
+        self.npinfs = 0;
+        self.nninfs = 0;
+    }
+
+}
+
+///
+/// Estimate the given percentile (like P95) of a endless stream of values, without storing
+/// the values, using the P-Square algorithm of Jain and Chlamtac. It tracks 5 markers, whose
+/// heights are adjusted (using piecewise parabolic interpolation) has the values arrive,
+/// with the middle marker tracking the percentile.
+///
+/// Till 5 values have been pushed, the estimate is the nearest rank percentile of them.
+/// NaN values are ignored.
+///
+#[derive(Debug, Clone)]
+pub struct RunningPercentile {
+    p: f64,
+    count: usize,
+    /// marker heights
+    heights: [f64; 5],
+    /// actual marker positions (1 based)
+    positions: [f64; 5],
+    /// desired marker positions
+    desired: [f64; 5],
+    /// increments of the desired marker positions, for each value
+    increments: [f64; 5],
+}
+
+impl RunningPercentile {
+
+    /// The percentile is specified has a fraction p, ex 0.95 for P95, which should be in (0, 1)
+    pub fn new(p: f64) -> Result<RunningPercentile, String> {
+        if p.is_nan() || p <= 0.0 || p >= 1.0 {
+            return Err(format!("ERRR:DU:SigPro:RunningPercentile:Percentile fraction {} not in (0, 1)", p));
+        }
+        Ok(RunningPercentile {
+            p,
+            count: 0,
+            heights: [0.0; 5],
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired: [1.0, 1.0 + 2.0*p, 1.0 + 4.0*p, 3.0 + 2.0*p, 5.0],
+            increments: [0.0, p/2.0, p, (1.0 + p)/2.0, 1.0],
+        })
+    }
+
+    /// The number of (non NaN) values pushed so far
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn push(&mut self, value: f32) {
+        if value.is_nan() {
+            return;
+        }
+        let x = value as f64;
+        if self.count < 5 {
+            self.heights[self.count] = x;
+            self.count += 1;
+            if self.count == 5 {
+                self.heights.sort_by(|a, b| a.total_cmp(b));
+            }
+            return;
+        }
+        self.count += 1;
+        let q = &mut self.heights;
+        let k = if x < q[0] {
+            q[0] = x;
+            0
+        } else if x >= q[4] {
+            q[4] = x;
+            3
+        } else {
+            (0..4).rev().find(|i| q[*i] <= x).unwrap_or(0)
+        };
+        for i in k+1..5 {
+            self.positions[i] += 1.0;
+        }
+        for i in 0..5 {
+            self.desired[i] += self.increments[i];
+        }
+        for i in 1..4 {
+            let d = self.desired[i] - self.positions[i];
+            let n = &self.positions;
+            if (d >= 1.0 && n[i+1] - n[i] > 1.0) || (d <= -1.0 && n[i-1] - n[i] < -1.0) {
+                let d = d.signum();
+                let qp = self.parabolic(i, d);
+                let q = &mut self.heights;
+                if q[i-1] < qp && qp < q[i+1] {
+                    q[i] = qp;
+                } else {
+                    let j = if d > 0.0 { i + 1 } else { i - 1 };
+                    q[i] += d*(q[j] - q[i])/(self.positions[j] - self.positions[i]);
+                }
+                self.positions[i] += d;
+            }
+        }
+    }
+
+    /// The piecewise parabolic prediction of the height of marker i, moved by d (+1 or -1)
+    fn parabolic(&self, i: usize, d: f64) -> f64 {
+        let (q, n) = (&self.heights, &self.positions);
+        q[i] + d/(n[i+1] - n[i-1]) * ((n[i] - n[i-1] + d)*(q[i+1] - q[i])/(n[i+1] - n[i])
+            + (n[i+1] - n[i] - d)*(q[i] - q[i-1])/(n[i] - n[i-1]))
+    }
+
+    /// The current estimate of the percentile, if any value has been pushed
+    pub fn estimate(&self) -> Option<f32> {
+        if self.count == 0 {
+            return None;
+        }
+        if self.count < 5 {
+            let mut vsorted = [0.0f64; 5];
+            vsorted[..self.count].copy_from_slice(&self.heights[..self.count]);
+            vsorted[..self.count].sort_by(|a, b| a.total_cmp(b));
+            let rank = fmath::f64::ceil(self.p*self.count as f64) as usize;
+            return Some(vsorted[rank.clamp(1, self.count) - 1] as f32);
+        }
+        Some(self.heights[2] as f32)
+    }
+
+    /// Forget the values pushed so far
+    pub fn reset(&mut self) {
+        if let Ok(fresh) = RunningPercentile::new(self.p) {
+            *self = fresh;
+        }
+    }
+
+}
+
+///
+/// Split the given interleaved multi channel data (ie frames of one sample per channel)
+/// into a series per channel. A trailing partial frame is dropped.
+///
+/// 0 channels gives no series.
+///
+pub fn deinterleave(vdata: &[f32], channels: usize) -> Vec<Vec<f32>> {
+    if channels == 0 {
+        return Vec::new();
+    }
+    let nframes = vdata.len()/channels;
+    (0..channels).map(|ch| vdata.iter().skip(ch).step_by(channels).take(nframes).copied().collect()).collect()
+}
+
+/// Merge the given per channel series into interleaved frames, the series should all
+/// be of the same length.
+pub fn interleave<S: AsRef<[f32]>>(vchannels: &[S]) -> Result<Vec<f32>, String> {
+    let nframes = vchannels.first().map(|c| c.as_ref().len()).unwrap_or(0);
+    if let Some(ch) = vchannels.iter().position(|c| c.as_ref().len() != nframes) {
+        return Err(format!("ERRR:DU:SigPro:Interleave:Channel {} length {} differs from {}", ch, vchannels[ch].as_ref().len(), nframes));
+    }
+    let mut vout = Vec::with_capacity(nframes*vchannels.len());
+    for i in 0..nframes {
+        vout.extend(vchannels.iter().map(|c| c.as_ref()[i]));
+    }
+    Ok(vout)
+}
+
+///
+/// Apply the given 1D op (like sw_average or a closure using FirFilter) to each channel of
+/// the given interleaved data independently, returning the results interleaved again.
+///
+/// op is called with the channel index and its series, and should return series of the
+/// same length for all the channels (a trailing partial frame is dropped, refer deinterleave).
+///
+pub fn map_channels<F>(vdata: &[f32], channels: usize, mut op: F) -> Result<Vec<f32>, String>
+where
+    F: FnMut(usize, &[f32]) -> Vec<f32>,
+{
+    let vchannels: Vec<Vec<f32>> = deinterleave(vdata, channels).iter().enumerate().map(|(ch, c)| op(ch, c)).collect();
+    interleave(&vchannels)
+}
+
+/// Process the samples from the given input, chunk_size samples at a time, so that large
+/// captures (ex read from a file) can be processed in bounded memory.
+///
+/// * op is called with each chunk of samples, the last chunk may be smaller. Any state
+///   wrt the processing across chunks is maintained by op, ex by using the streaming
+///   filters like MovingAverage or FirFilter. If op returns a error, the processing stops
+///   and the error is returned.
+/// * progress is called after each chunk, with the number of samples processed so far and
+///   the total number of samples (0 if the input doesnt know its length). It should return
+///   false to cancel the processing.
+///
+/// Returns the number of samples processed, which will be less than the total, if cancelled.
+///
+pub fn process_chunked<I, F, P>(input: I, chunk_size: usize, mut op: F, mut progress: P) -> Result<usize, String>
+where
+    I: IntoIterator<Item = f32>,
+    F: FnMut(&[f32]) -> Result<(), String>,
+    P: FnMut(usize, usize) -> bool,
+{
+    if chunk_size == 0 {
+        return Err("ERRR:DU:SigPro:ProcessChunked:Chunk size should be atleast 1".to_string());
+    }
+    let mut input = input.into_iter();
+    let total = match input.size_hint() {
+        (lower, Some(upper)) if lower == upper => upper,
+        _ => 0,
+    };
+    let mut vchunk = Vec::with_capacity(chunk_size);
+    let mut done = 0;
+    loop {
+        vchunk.clear();
+        vchunk.extend(input.by_ref().take(chunk_size));
+        if vchunk.is_empty() {
+            break;
+        }
+        op(&vchunk)?;
+        done += vchunk.len();
+        if !progress(done, total) {
+            break;
+        }
+    }
+    Ok(done)
+}
+
+/// Reduce the given data into the given number of columns, by averaging the samples
+/// which fall into each column. NaN samples are ignored, and a column with only NaN
+/// samples gives NaN.
+fn columns_average(vdata: &[f32], width: usize) -> Vec<f32> {
+    let width = width.min(vdata.len());
+    let mut vcols = Vec::with_capacity(width);
+    for c in 0..width {
+        let si = c*vdata.len()/width;
+        let ei = (c+1)*vdata.len()/width;
+        let (sum, cnt) = vdata[si..ei].iter().filter(|d| !d.is_nan()).fold((0.0f64, 0usize), |(s, n), d| (s + *d as f64, n + 1));
+        vcols.push(if cnt == 0 { f32::NAN } else { (sum/(cnt as f64)) as f32 });
+    }
+    vcols
+}
+
+/// Render the given series has a single line sparkline (using the unicode block chars)
+/// of atmost width chars, for a quick look at the data in the terminal.
+///
+/// If there are more samples than width, the samples are averaged into width columns.
+/// NaN samples are shown has a space.
+pub fn render_sparkline(vdata: &[f32], width: usize) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let vcols = columns_average(vdata, width);
+    let (fmin, fmax) = vcols.iter().filter(|d| !d.is_nan()).fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), d| (lo.min(*d), hi.max(*d)));
+    let span = fmax - fmin;
+    vcols.iter().map(|d| {
+        if d.is_nan() {
+            ' '
+        } else if span > 0.0 {
+            BARS[(((d - fmin)/span)*7.0 + 0.5) as usize]
+        } else {
+            BARS[3]
+        }
+    }).collect()
+}
+
+/// Render the given (x, f) series has a simple ascii plot of width columns and height rows,
+/// with the f range marked on the left and the x range marked below the plot.
+///
+/// The x range is split into width columns, and the average of the f values of the samples
+/// falling into each column is plotted has a * in the corresponding row.
+pub fn render_plot(vdata: &[(f64, f32)], width: usize, height: usize) -> String {
+    let width = width.max(1);
+    let height = height.max(2);
+    let (xmin, xmax) = vdata.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), d| (lo.min(d.0), hi.max(d.0)));
+    let mut vsum = vec![(0.0f64, 0usize); width];
+    for (x, f) in vdata {
+        if f.is_nan() {
+            continue;
+        }
+        let c = if xmax > xmin { (((x - xmin)/(xmax - xmin))*(width as f64)) as usize } else { 0 };
+        let c = c.min(width-1);
+        vsum[c].0 += *f as f64;
+        vsum[c].1 += 1;
+    }
+    let vcols: Vec<Option<f64>> = vsum.iter().map(|(s, n)| if *n > 0 { Some(s/(*n as f64)) } else { None }).collect();
+    let (fmin, fmax) = vcols.iter().flatten().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), d| (lo.min(*d), hi.max(*d)));
+    let mut vrows = vec![vec![' '; width]; height];
+    for (c, f) in vcols.iter().enumerate() {
+        if let Some(f) = f {
+            let r = if fmax > fmin { (((f - fmin)/(fmax - fmin))*((height-1) as f64) + 0.5) as usize } else { 0 };
+            vrows[height-1-r][c] = '*';
+        }
+    }
+    let mut outs = String::new();
+    for (i, row) in vrows.iter().enumerate() {
+        let label = match i {
+            0 if fmax.is_finite() => format!("{:>10.3}", fmax),
+            _ if i == height-1 && fmin.is_finite() => format!("{:>10.3}", fmin),
+            _ => " ".repeat(10),
+        };
+        outs.push_str(&format!("{} |{}\n", label, row.iter().collect::<String>()));
+    }
+    outs.push_str(&format!("{} +{}\n", " ".repeat(10), "-".repeat(width)));
+    if xmax.is_finite() {
+        let sxmin = format!("{:.3}", xmin);
+        let sxmax = format!("{:.3}", xmax);
+        let gap = (width + 2).saturating_sub(sxmin.len() + sxmax.len()).max(1);
+        outs.push_str(&format!("{} {}{}{}\n", " ".repeat(10), sxmin, " ".repeat(gap), sxmax));
+    }
+    outs
+}
+
+/// Control how (x, f) series are exported by export_csv and export_json
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportOptions {
+    /// name of the x column/field
+    pub x_name: String,
+    /// name of the f column/field
+    pub f_name: String,
+    /// number of digits after the decimal point, None uses the shortest form which round trips
+    pub precision: Option<usize>,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions { x_name: "x".to_string(), f_name: "f".to_string(), precision: None }
+    }
+}
+
+impl ExportOptions {
+
+    #[cfg(feature = "std")]
+    fn fmt_value<T: core::fmt::Display>(&self, val: T) -> String {
+        match self.precision {
+            Some(p) => format!("{:.*}", p, val),
+            None => format!("{}", val),
+        }
+    }
+
+}
+
+/// Write the given (x, f) series has csv to the given writer, with a header line
+/// having the column names, followed by a x,f line for each sample.
+#[cfg(feature = "std")]
+pub fn export_csv<W: std::io::Write>(mut writer: W, vdata: &[(f64, f32)], opts: &ExportOptions) -> Result<(), String> {
+    let mut sout = format!("{},{}\n", opts.x_name, opts.f_name);
+    for (x, f) in vdata {
+        sout.push_str(&format!("{},{}\n", opts.fmt_value(x), opts.fmt_value(f)));
+    }
+    writer.write_all(sout.as_bytes()).map_err(|e| format!("ERRR:DU:SigPro:ExportCsv:{}", e))
+}
+
+/// Write the given (x, f) series has a json array of {"x": .., "f": ..} objects to the
+/// given writer, with the field names has specified. Non finite values are written has null.
+#[cfg(feature = "std")]
+pub fn export_json<W: std::io::Write>(mut writer: W, vdata: &[(f64, f32)], opts: &ExportOptions) -> Result<(), String> {
+    let jstr = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+    let (xname, fname) = (jstr(&opts.x_name), jstr(&opts.f_name));
+    let jnum = |v: f64, sv: String| if v.is_finite() { sv } else { "null".to_string() };
+    let mut sout = String::from("[");
+    for (i, (x, f)) in vdata.iter().enumerate() {
+        if i > 0 {
+            sout.push(',');
+        }
+        sout.push_str(&format!("\n  {{\"{}\": {}, \"{}\": {}}}", xname, jnum(*x, opts.fmt_value(x)), fname, jnum(*f as f64, opts.fmt_value(f))));
+    }
+    sout.push_str("\n]\n");
+    writer.write_all(sout.as_bytes()).map_err(|e| format!("ERRR:DU:SigPro:ExportJson:{}", e))
+}
+
+/// The direction of a threshold crossing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeKind {
+    Rise,
+    Fall,
+}
+
+/// A threshold crossing event, at the given x (ie timestamp)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Event {
+    pub x: f64,
+    pub kind: EdgeKind,
+}
+
+/// Detect threshold crossings in the given (x, f) data, using hysteresis to
+/// suppress chatter around the threshold.
+///
+/// A Rise event is generated, when f reaches high (>=), after having been low.
+/// A Fall event is generated, when f reaches low (<=), after having been high.
+/// The x of the sample at which the threshold is reached, is used for the event.
+///
+/// The signal is assumed to be low initially, unless the first sample is >= high.
+///
+pub fn detect_crossings(vdata: &[(f64, f32)], high: f32, low: f32) -> Vec<Event> {
+    let mut vevents = Vec::new();
+    let mut is_high = match vdata.first() {
+        Some(d) => d.1 >= high,
+        None => return vevents,
+    };
+    for (x, f) in vdata {
+        if !is_high && (*f >= high) {
+            is_high = true;
+            vevents.push(Event { x: *x, kind: EdgeKind::Rise });
+        } else if is_high && (*f <= low) {
+            is_high = false;
+            vevents.push(Event { x: *x, kind: EdgeKind::Fall });
+        }
+    }
+    vevents
+}
+
+/// How missing samples (None or NaN) in the data are handled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MissingPolicy {
+    /// drop the missing samples, NOTE: this changes the number of samples
+    Skip,
+    /// fail, if any sample is missing
+    Error,
+    /// use the previous available sample's value, leading missing samples
+    /// use the first available sample's value
+    FillPrevious,
+    /// use the given value
+    FillValue(f32),
+}
+
+/// Convert the given data into optional samples, with NaN values treated as missing
+pub fn missing_from_nan(vdata: &[f32]) -> Vec<Option<f32>> {
+    vdata.iter().map(|d| if d.is_nan() { None } else { Some(*d) }).collect()
+}
+
+/// Resolve the missing samples (None or NaN) in the given data, as per the given policy
+pub fn resolve_missing(vdata: &[Option<f32>], policy: MissingPolicy) -> Result<Vec<f32>, String> {
+    let vdata: Vec<Option<f32>> = vdata.iter().map(|d| d.filter(|f| !f.is_nan())).collect();
+    match policy {
+        MissingPolicy::Skip => Ok(vdata.iter().flatten().copied().collect()),
+        MissingPolicy::Error => {
+            match vdata.iter().position(|d| d.is_none()) {
+                Some(i) => Err(format!("ERRR:DU:SigPro:ResolveMissing:Sample {} is missing", i)),
+                None => Ok(vdata.iter().flatten().copied().collect()),
+            }
+        }
+        MissingPolicy::FillPrevious => {
+            let first = match vdata.iter().flatten().next() {
+                Some(f) => *f,
+                None => return Err("ERRR:DU:SigPro:ResolveMissing:All samples are missing".to_string()),
+            };
+            let mut prev = first;
+            Ok(vdata.iter().map(|d| {
+                prev = d.unwrap_or(prev);
+                prev
+            }).collect())
+        }
+        MissingPolicy::FillValue(v) => Ok(vdata.iter().map(|d| d.unwrap_or(v)).collect()),
+    }
+}
+
+/// Average of the given data, after handling missing samples as per the given policy
+pub fn vec_avg_missing(vdata: &[Option<f32>], policy: MissingPolicy) -> Result<f32, String> {
+    let vf = resolve_missing(vdata, policy)?;
+    if vf.is_empty() {
+        return Err("ERRR:DU:SigPro:VecAvgMissing:No samples available".to_string());
+    }
+    Ok(vf.iter().sum::<f32>()/(vf.len() as f32))
+}
+
+/// sw_average of the given data, after handling missing samples as per the given policy
+pub fn sw_average_missing(vdata: &[Option<f32>], ww: usize, policy: MissingPolicy) -> Result<Vec<f32>, String> {
+    if ww == 0 {
+        return Err("ERRR:DU:SigPro:SwAverageMissing:Window size should be atleast 1".to_string());
+    }
+    let vf = resolve_missing(vdata, policy)?;
+    if vf.len() < ww {
+        return Err(format!("ERRR:DU:SigPro:SwAverageMissing:Too few samples {} for window {}", vf.len(), ww));
+    }
+    Ok(sw_average(&vf, ww))
+}
+
+/// crosscorr_weighted of the given data, after handling missing samples as per the given policy
+pub fn crosscorr_weighted_missing(vdata: &[Option<f32>], vweights: &[f32], policy: MissingPolicy) -> Result<Vec<f32>, String> {
+    if vweights.len() % 2 != 1 {
+        return Err(format!("ERRR:DU:SigPro:CrossCorrMissing:Weights length {} should be odd", vweights.len()));
+    }
+    let vf = resolve_missing(vdata, policy)?;
+    if vf.len() < vweights.len() {
+        return Err(format!("ERRR:DU:SigPro:CrossCorrMissing:Too few samples {} for weights {}", vf.len(), vweights.len()));
+    }
+    Ok(crosscorr_weighted(&vf, vweights))
+}
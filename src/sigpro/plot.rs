@@ -0,0 +1,84 @@
+//!
+//! Render line charts of (x, f) series has PNG or SVG files (using plotters),
+//! for a quick look at the data in a browser/image viewer.
+//!
+//! HanishKVC, 2022
+//!
+
+use std::path::Path;
+use plotters::prelude::*;
+use plotters::coord::Shift;
+
+
+/// Control how line_chart renders the chart
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartOptions {
+    pub title: String,
+    /// size of the image in pixels
+    pub width: u32,
+    pub height: u32,
+    /// (r, g, b) colors used for the series, in a round robin manner
+    pub colors: Vec<(u8, u8, u8)>,
+}
+
+impl Default for ChartOptions {
+    fn default() -> Self {
+        ChartOptions {
+            title: String::new(),
+            width: 800,
+            height: 480,
+            colors: vec![(0x1f, 0x77, 0xb4), (0xff, 0x7f, 0x0e), (0x2c, 0xa0, 0x2c), (0xd6, 0x27, 0x28), (0x94, 0x67, 0xbd)],
+        }
+    }
+}
+
+/// Draw the chart onto the given drawing area, wrt the given x and f ranges
+fn draw_chart<DB: DrawingBackend>(root: DrawingArea<DB, Shift>, vseries: &[(&str, &[(f64, f32)])], opts: &ChartOptions, xrange: (f64, f64), frange: (f64, f64)) -> Result<(), String> {
+    let err = |e: &dyn std::fmt::Display| format!("ERRR:DU:SigPro:Plot:LineChart:{}", e);
+    root.fill(&WHITE).map_err(|e| err(&e))?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(&opts.title, ("sans-serif", 20))
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(60)
+        .build_cartesian_2d(xrange.0..xrange.1, frange.0..frange.1)
+        .map_err(|e| err(&e))?;
+    chart.configure_mesh().draw().map_err(|e| err(&e))?;
+    for (i, (name, vdata)) in vseries.iter().enumerate() {
+        let (r, g, b) = if opts.colors.is_empty() { (0, 0, 0) } else { opts.colors[i % opts.colors.len()] };
+        let color = RGBColor(r, g, b);
+        let vpoints = vdata.iter().filter(|(x, f)| x.is_finite() && f.is_finite()).map(|(x, f)| (*x, *f as f64));
+        chart.draw_series(LineSeries::new(vpoints, color.stroke_width(2)))
+            .map_err(|e| err(&e))?
+            .label(*name)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+    chart.configure_series_labels().background_style(WHITE).border_style(BLACK).draw().map_err(|e| err(&e))?;
+    root.present().map_err(|e| err(&e))
+}
+
+/// Render a line chart of the given named (x, f) series into the given png or svg file,
+/// the format being decided by the extension of the path.
+///
+/// All the series share the same axes, whose ranges are decided by the data. Non finite
+/// values are skipped. PNG output needs a sans-serif font to be available on the system
+/// (found through fontconfig), for the title, labels and legend.
+pub fn line_chart<P: AsRef<Path>>(path: P, vseries: &[(&str, &[(f64, f32)])], opts: &ChartOptions) -> Result<(), String> {
+    let path = path.as_ref();
+    let vpoints = vseries.iter().flat_map(|s| s.1.iter()).filter(|(x, f)| x.is_finite() && f.is_finite());
+    let (xmin, xmax, fmin, fmax) = vpoints.fold((f64::INFINITY, f64::NEG_INFINITY, f64::INFINITY, f64::NEG_INFINITY), |r, (x, f)| {
+        (r.0.min(*x), r.1.max(*x), r.2.min(*f as f64), r.3.max(*f as f64))
+    });
+    if !xmin.is_finite() {
+        return Err("ERRR:DU:SigPro:Plot:LineChart:No finite data points to plot".to_string());
+    }
+    let xrange = if xmax > xmin { (xmin, xmax) } else { (xmin - 0.5, xmax + 0.5) };
+    let frange = if fmax > fmin { (fmin, fmax) } else { (fmin - 0.5, fmax + 0.5) };
+    let size = (opts.width.max(200), opts.height.max(150));
+    let ext = path.extension().map(|e| e.to_string_lossy().to_ascii_lowercase()).unwrap_or_default();
+    match ext.as_str() {
+        "svg" => draw_chart(SVGBackend::new(path, size).into_drawing_area(), vseries, opts, xrange, frange),
+        "png" => draw_chart(BitMapBackend::new(path, size).into_drawing_area(), vseries, opts, xrange, frange),
+        _ => Err(format!("ERRR:DU:SigPro:Plot:LineChart:{}:Only png or svg output is supported", path.display())),
+    }
+}
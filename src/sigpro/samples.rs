@@ -0,0 +1,52 @@
+//!
+//! Conversions btw the PCM sample formats (8 bit unsigned, 16 and 24 bit signed)
+//! and f32 values in the range -1.0 to 1.0, used by the float processing logics.
+//!
+//! The integer full scale maps to 1.0, ie i16 -32768 maps to -1.0, while 32767 maps
+//! to slightly less than 1.0. In the other direction, values beyond -1.0 to 1.0 are
+//! clipped, NaN is treated has 0.0 and the result is rounded to the nearest int.
+//!
+//! HanishKVC, 2022
+//!
+
+use crate::fmath;
+
+
+/// The full scale of 16 and 24 bit samples
+const I16_SCALE: f32 = 32768.0;
+const I24_SCALE: f32 = 8388608.0;
+
+/// Scale the given value to the given full scale, with clipping and rounding
+fn scale_clip(val: f32, scale: f32) -> f32 {
+    let val = if val.is_nan() { 0.0 } else { val.clamp(-1.0, 1.0) };
+    fmath::f32::round(val*scale).min(scale - 1.0)
+}
+
+/// 8 bit unsigned sample (with 128 being 0) to f32
+pub fn u8_to_f32(sample: u8) -> f32 {
+    (sample as f32 - 128.0)/128.0
+}
+
+/// f32 to 8 bit unsigned sample (with 128 being 0)
+pub fn f32_to_u8(val: f32) -> u8 {
+    (scale_clip(val, 128.0) + 128.0) as u8
+}
+
+pub fn i16_to_f32(sample: i16) -> f32 {
+    sample as f32/I16_SCALE
+}
+
+pub fn f32_to_i16(val: f32) -> i16 {
+    scale_clip(val, I16_SCALE) as i16
+}
+
+/// 24 bit signed sample, given has its 3 bytes in little endian order, to f32
+pub fn i24_bytes_to_f32(bytes: [u8; 3]) -> f32 {
+    (i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8) as f32/I24_SCALE
+}
+
+/// f32 to 24 bit signed sample, has its 3 bytes in little endian order
+pub fn f32_to_i24_bytes(val: f32) -> [u8; 3] {
+    let b = (scale_clip(val, I24_SCALE) as i32).to_le_bytes();
+    [b[0], b[1], b[2]]
+}
@@ -3,34 +3,298 @@
 //! HanishKVC, 2022
 //!
 
-use crate::variant::Variant;
+use crate::variant::{self, Variant};
+use crate::error::DataUtilsError;
 use crate::hex;
+use crate::integer;
 use crate::sigpro;
+use crate::text;
+use crate::pack;
+use crate::bytes;
+use crate::bytes::bignum;
+use crate::bytes::ecc::{self, EccStatus};
+use crate::checksum;
+use crate::hash;
+use crate::net;
+use crate::flags::FlagSet;
+use crate::rng::{RandSource, SplitMix64};
 
 
+///
+/// Assert that the given float slices are of the same length, and that the
+/// corresponding elements are within eps of each other.
+///
+pub fn assert_vec_close(vgot: &[f32], vexp: &[f32], eps: f32) {
+    assert_eq!(vgot.len(), vexp.len(), "ERRR:DU:TestLib:AssertVecClose:Length mismatch {:?} vs {:?}", vgot, vexp);
+    for (i, (g, e)) in vgot.iter().zip(vexp.iter()).enumerate() {
+        assert!((g - e).abs() <= eps, "ERRR:DU:TestLib:AssertVecClose:Mismatch at {}, {} vs {} (eps {}):{:?} vs {:?}", i, g, e, eps, vgot, vexp);
+    }
+}
+
+/// Assert that the given buffer matches the given hex string (case insensitive)
+pub fn assert_buf_eq_hex(buf: &[u8], shex: &str) {
+    let vexp = hex::vu8_from_hex(shex).unwrap();
+    assert_eq!(buf, vexp.as_slice(), "ERRR:DU:TestLib:AssertBufEqHex:{} vs {}", hex::hex_from_vu8(buf), shex);
+}
+
+pub fn test_asserthelpers() {
+    assert_vec_close(&[1.0, 2.0], &[1.0005, 1.9995], 1e-3);
+    assert_buf_eq_hex(&[0xAB, 0x01], "ab01");
+    assert!(std::panic::catch_unwind(|| assert_vec_close(&[1.0, 2.0], &[1.0, 2.1], 1e-3)).is_err());
+    assert!(std::panic::catch_unwind(|| assert_vec_close(&[1.0], &[1.0, 2.0], 1e-3)).is_err());
+    assert!(std::panic::catch_unwind(|| assert_buf_eq_hex(&[0xAB, 0x01], "ab02")).is_err());
+}
+
+///
+/// The random source to use in tests, so that any failures can be reproduced
+/// by using the same seed. Pass it to the generators below, or to any of the
+/// randomised logics of the crate which take a RandSource.
+///
+pub fn seeded_rng(seed: u64) -> SplitMix64 {
+    SplitMix64::new(seed)
+}
+
+/// Generate a random byte buffer, of length 0 to maxlen (both inclusive)
+pub fn gen_bytes(rng: &mut impl RandSource, maxlen: usize) -> Vec<u8> {
+    let len = rng.below(maxlen+1);
+    (0..len).map(|_| rng.next_u64() as u8).collect()
+}
+
+/// Generate a random (mixed case) hex string, of 0 to maxbytes bytes worth of hex digits
+pub fn gen_hex(rng: &mut impl RandSource, maxbytes: usize) -> String {
+    let hexchars = b"0123456789abcdefABCDEF";
+    let len = rng.below(maxbytes+1)*2;
+    (0..len).map(|_| hexchars[rng.below(hexchars.len())] as char).collect()
+}
+
+/// Generate a random printable ascii string, of length 0 to maxlen (both inclusive)
+pub fn gen_string(rng: &mut impl RandSource, maxlen: usize) -> String {
+    let len = rng.below(maxlen+1);
+    (0..len).map(|_| (0x20 + rng.below(0x5F)) as u8 as char).collect()
+}
+
+/// Generate a random Int, Str or Buf Variant
+pub fn gen_variant(rng: &mut impl RandSource) -> Variant {
+    match rng.below(3) {
+        0 => Variant::IntValue(match rng.below(4) {
+            0 => 0,
+            1 => isize::MIN,
+            2 => isize::MAX,
+            _ => rng.next_u64() as isize,
+        }),
+        1 => Variant::StrValue(gen_string(rng, 32)),
+        _ => Variant::BufValue(gen_bytes(rng, 32)),
+    }
+}
+
+/// The literal form of the given variant, which Variant::from(&str) should map back to the same variant
+fn variant_literal(v: &Variant) -> String {
+    match v {
+        Variant::StrValue(sval) => format!("\"{}\"", sval),
+        Variant::BufValue(bval) => format!("$0x{}", hex::hex_from_vu8(bval)),
+        _ => v.to_string(),
+    }
+}
+
+/// Property style round trip checks over randomly generated data, for the given seed
+pub fn check_roundtrips(seed: u64, count: usize) {
+    let mut rng = seeded_rng(seed);
+    for _ in 0..count {
+        let vbuf = gen_bytes(&mut rng, 64);
+        let shex = hex::hex_from_vu8(&vbuf);
+        assert_eq!(shex.len(), vbuf.len()*2);
+        assert_eq!(hex::vu8_from_hex(&shex), Ok(vbuf.clone()), "seed {}", seed);
+        let shex = gen_hex(&mut rng, 64);
+        let vbuf = hex::vu8_from_hex(&shex).unwrap();
+        assert_eq!(hex::hex_from_vu8(&vbuf), shex.to_uppercase(), "seed {}", seed);
+        let mut shexodd = shex.clone();
+        shexodd.push('a');
+        assert!(hex::vu8_from_hex(&shexodd).is_err());
+        let v = gen_variant(&mut rng);
+        let sliteral = variant_literal(&v);
+        let vback = Variant::from(sliteral.as_str());
+        assert_eq!(vback.get_bufvu8(), v.get_bufvu8(), "seed {}:{}", seed, sliteral);
+        assert_eq!(vback.get_string(), v.get_string(), "seed {}:{}", seed, sliteral);
+        if let Variant::IntValue(ival) = v {
+            assert_eq!(Variant::BufValue(v.get_bufvu8()).get_isize(), Ok(ival));
+        }
+    }
+}
+
+pub fn test_proproundtrip() {
+    let mut rng = seeded_rng(1);
+    let vfirst: Vec<u64> = (0..4).map(|_| rng.next_u64()).collect();
+    let mut rng = seeded_rng(1);
+    let vagain: Vec<u64> = (0..4).map(|_| rng.next_u64()).collect();
+    du_log!(info, "TEST:PropRoundTrip:Rng:{:X?}", vfirst);
+    assert_eq!(vfirst, vagain);
+    for seed in [0, 1, 0x1234, u64::MAX] {
+        check_roundtrips(seed, 200);
+    }
+    assert_eq!(hex::vu8_from_hex(""), Ok(vec![]));
+}
+
+/// Env variable, which if set to 1, makes assert_matches_golden (re)generate the golden files
+pub const GOLDEN_UPDATE_ENV: &str = "DU_UPDATE_GOLDEN";
+
+///
+/// Assert that the given data matches the contents of the golden file
+/// `tests/golden/<name>` (relative to the crate root).
+///
+/// If the env variable DU_UPDATE_GOLDEN is set to 1, the golden file is
+/// (re)generated from the given data, instead of comparing.
+///
+pub fn assert_matches_golden<D: AsRef<[u8]>>(name: &str, data: D) {
+    let data = data.as_ref();
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(name);
+    if std::env::var(GOLDEN_UPDATE_ENV).is_ok_and(|v| v == "1") {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, data).unwrap();
+        du_log!(info, "INFO:DU:TestLib:AssertMatchesGolden:Updated {}", path.display());
+        return;
+    }
+    let vexp = match std::fs::read(&path) {
+        Ok(vexp) => vexp,
+        Err(e) => panic!("ERRR:DU:TestLib:AssertMatchesGolden:Reading {}:{}, run with {}=1 to generate it", path.display(), e, GOLDEN_UPDATE_ENV),
+    };
+    if vexp == data {
+        return;
+    }
+    let pos = vexp.iter().zip(data.iter()).position(|(a, b)| a != b).unwrap_or(vexp.len().min(data.len()));
+    match (std::str::from_utf8(&vexp), std::str::from_utf8(data)) {
+        (Ok(sexp), Ok(sgot)) => {
+            let line = sexp[..pos.min(sexp.len())].matches('\n').count() + 1;
+            panic!("ERRR:DU:TestLib:AssertMatchesGolden:{}:Mismatch at line {}, expected [{}] got [{}]",
+                name, line, sexp.lines().nth(line-1).unwrap_or(""), sgot.lines().nth(line-1).unwrap_or(""));
+        }
+        _ => panic!("ERRR:DU:TestLib:AssertMatchesGolden:{}:Mismatch at byte offset {}, expected {} bytes got {} bytes",
+                name, pos, vexp.len(), data.len()),
+    }
+}
+
+pub fn test_golden() {
+    let vf: Vec<f32> = (0..64).map(|i| ((i as f32) * 0.3).sin() * 10.0 + if i % 7 == 0 { 4.0 } else { 0.0 }).collect();
+    let mut sout = String::new();
+    for ww in [3, 4, 7] {
+        let vavg = sigpro::sw_average(&vf, ww);
+        sout.push_str(&format!("sw_average:{}:{}\n", ww, vavg.iter().map(|f| format!("{:.4}", f)).collect::<Vec<String>>().join(",")));
+    }
+    let vcc = sigpro::crosscorr_weighted(&vf, &[0.25, 0.5, 0.25]);
+    sout.push_str(&format!("crosscorr_weighted:{}\n", vcc.iter().map(|f| format!("{:.4}", f)).collect::<Vec<String>>().join(",")));
+    assert_matches_golden("sigpro_filters.txt", &sout);
+    assert_matches_golden("bytes_cobs.bin", bytes::cobs_encode(&(0..300).map(|i| (i % 5) as u8).collect::<Vec<u8>>()));
+    if std::env::var(GOLDEN_UPDATE_ENV).is_err() {
+        assert!(std::panic::catch_unwind(|| assert_matches_golden("sigpro_filters.txt", "sw_average:3:0.0\n")).is_err());
+        assert!(std::panic::catch_unwind(|| assert_matches_golden("__does_not_exist__", "")).is_err());
+    }
+}
+
+///
+/// Load a series of (x, f) values from the given csv file, like those under tests/data.
+///
+/// Each line should have 2 comma seperated fields, empty lines and lines starting
+/// with # are skipped, has is a header line (a first line which is not numeric).
+///
+pub fn load_xf_csv<P: AsRef<std::path::Path>>(path: P) -> Result<Vec<(f64, f32)>, String> {
+    let path = path.as_ref();
+    let sdata = std::fs::read_to_string(path).map_err(|e| format!("ERRR:DU:TestLib:LoadXfCsv:{}:{}", path.display(), e))?;
+    let mut vout = Vec::new();
+    let mut header_allowed = true;
+    for (i, line) in sdata.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let vfields = text::split_csv(line, ',');
+        if vfields.len() != 2 {
+            return Err(format!("ERRR:DU:TestLib:LoadXfCsv:{}:{}:Expected 2 fields, got {}", path.display(), i+1, vfields.len()));
+        }
+        let x = vfields[0].trim().parse::<f64>();
+        let f = vfields[1].trim().parse::<f32>();
+        match (x, f) {
+            (Ok(x), Ok(f)) => vout.push((x, f)),
+            _ if header_allowed => (),
+            _ => return Err(format!("ERRR:DU:TestLib:LoadXfCsv:{}:{}:Invalid values [{}]", path.display(), i+1, line)),
+        }
+        header_allowed = false;
+    }
+    Ok(vout)
+}
+
+/// Save the given series of (x, f) values into a csv file, with a x,f header line
+pub fn save_xf_csv<P: AsRef<std::path::Path>>(path: P, vdata: &[(f64, f32)]) -> Result<(), String> {
+    let path = path.as_ref();
+    let file = std::fs::File::create(path).map_err(|e| format!("ERRR:DU:TestLib:SaveXfCsv:{}:{}", path.display(), e))?;
+    sigpro::export_csv(std::io::BufWriter::new(file), vdata, &sigpro::ExportOptions::default())
+}
+
+pub fn test_xfcsv() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/trace_sine_noise.csv");
+    let vtrace = load_xf_csv(&path).unwrap();
+    du_log!(info, "TEST:XfCsv:{}:{} points:{:?}", path.display(), vtrace.len(), &vtrace[..3]);
+    assert_eq!(vtrace.len(), 500);
+    assert_eq!(vtrace[1], (0.01, 0.6751));
+    let vsmooth = sigpro::sw_average_f_of_xf(&vtrace, 5);
+    assert_eq!(vsmooth.len(), vtrace.len());
+    let vdown = sigpro::downsample_buckets(&vtrace, 10, sigpro::Agg::Max);
+    assert_eq!(vdown.len(), 10);
+    let tmpfile = std::env::temp_dir().join(format!("du_test_xfcsv_{}.csv", std::process::id()));
+    save_xf_csv(&tmpfile, &vtrace).unwrap();
+    let vback = load_xf_csv(&tmpfile).unwrap();
+    std::fs::remove_file(&tmpfile).unwrap();
+    assert_eq!(vback, vtrace);
+    std::fs::write(&tmpfile, "x,f\n1,2\nbad,3\n").unwrap();
+    assert!(load_xf_csv(&tmpfile).is_err());
+    std::fs::write(&tmpfile, "1,2,3\n").unwrap();
+    assert!(load_xf_csv(&tmpfile).is_err());
+    std::fs::remove_file(&tmpfile).unwrap();
+    assert!(load_xf_csv(&tmpfile).is_err());
+}
+
 pub fn test_variant() {
     let ivar = Variant::IntValue(5);
     let svar = Variant::StrValue("666".to_string());
     let bvar = Variant::BufValue(Vec::from("\x07\x00\x00\x00\x00\x00\x00\x00"));
-    print!("TEST:Variant:Int:Int[{}]:String[{}]:Buf[{:?}]\n", ivar.get_isize().unwrap(), ivar.get_string(), ivar.get_bufvu8());
-    print!("TEST:Variant:Str:Int[{}]:String[{}]:Buf[{:?}]\n", svar.get_isize().unwrap(), svar.get_string(), svar.get_bufvu8());
-    print!("TEST:Variant:Buf:Int[{}]:String[{}]:Buf[{:?}]\n", bvar.get_isize().unwrap(), bvar.get_string(), bvar.get_bufvu8());
+    du_log!(info, "TEST:Variant:Int:Int[{}]:String[{}]:Buf[{:?}]", ivar.get_isize().unwrap(), ivar.get_string(), ivar.get_bufvu8());
+    du_log!(info, "TEST:Variant:Str:Int[{}]:String[{}]:Buf[{:?}]", svar.get_isize().unwrap(), svar.get_string(), svar.get_bufvu8());
+    du_log!(info, "TEST:Variant:Buf:Int[{}]:String[{}]:Buf[{:?}]", bvar.get_isize().unwrap(), bvar.get_string(), bvar.get_bufvu8());
+    assert_eq!(ivar.get_isize(), Ok(5));
+    assert_eq!(ivar.get_string(), "5");
+    assert_eq!(ivar.get_bufvu8(), 5isize.to_ne_bytes().to_vec());
+    assert_eq!(svar.get_isize(), Ok(666));
+    assert_eq!(svar.get_string(), "666");
+    assert_eq!(svar.get_bufvu8(), b"666".to_vec());
+    assert_eq!(bvar.get_isize(), Ok(isize::from_ne_bytes([7, 0, 0, 0, 0, 0, 0, 0])));
+    assert_eq!(bvar.get_string(), "0700000000000000");
     let ivar = Variant::from("     123  ");
     let svar = Variant::from("  \" 456 but a string \"             ");
     let bvar = Variant::from("  $0x1122334455 ");
     let tvar = Variant::from("    __TIME__STAMP__ ");
-    print!("TEST:Variant:UsingFrom:IntV[{}]:StrV[{}]:BufV[{}]:TSV[{}]\n", ivar, svar, bvar, tvar);
+    du_log!(info, "TEST:Variant:UsingFrom:IntV[{}]:StrV[{}]:BufV[{}]:TSV[{}]", ivar, svar, bvar, tvar);
+    assert_eq!(ivar.to_string(), "123");
+    assert_eq!(svar.to_string(), " 456 but a string ");
+    assert_buf_eq_hex(&bvar.get_bufvu8(), "1122334455");
+    assert!(matches!(tvar, Variant::XTimeStamp));
+    assert!(tvar.get_isize().unwrap() > 0);
     let mut isvar = Variant::IntValue(123);
     let isorig = isvar.clone();
     isvar.set_string("Set a int variant to string variant");
-    print!("TEST:Variant:Set:Initial[{}]:Set[{}]\n", isorig, isvar);
+    du_log!(info, "TEST:Variant:Set:Initial[{}]:Set[{}]", isorig, isvar);
+    assert_eq!(isorig.to_string(), "123");
+    assert!(matches!(isvar, Variant::StrValue(_)));
+    assert_eq!(isvar.to_string(), "Set a int variant to string variant");
 }
 
 pub fn test_bufhex() {
     let mut vbuf = hex::vu8_from_hex("001122eeff00").unwrap();
     vbuf[0] = 99;
     let shex = hex::hex_from_vu8(&vbuf);
-    print!("TEST:BufHex:vbuf[{:?}], shex[{}]\n", vbuf, shex);
+    du_log!(info, "TEST:BufHex:vbuf[{:?}], shex[{}]", vbuf, shex);
+    assert_eq!(vbuf, vec![99, 0x11, 0x22, 0xEE, 0xFF, 0x00]);
+    assert_eq!(shex, "631122EEFF00");
+    assert_buf_eq_hex(&vbuf, "631122eeff00");
+    assert!(hex::vu8_from_hex("123").is_err());
+    assert!(hex::vu8_from_hex("12zz").is_err());
 }
 
 pub fn test_vecavg() {
@@ -39,25 +303,2263 @@ pub fn test_vecavg() {
     let vtd13 = vec![1i32,2,3,4,5];
     let vtd21 = vec![1.1f32,2.1,3.1,4.1,5.1];
     let vtd22 = vec![1.1f64,2.1,3.1,4.1,5.1];
-    eprintln!("TEST:VecAvg:{:?}:{}", vtd11, sigpro::vec_avg(&vtd11));
-    eprintln!("TEST:VecAvg:{:?}:{}", vtd12, sigpro::vec_avg(&vtd12));
-    eprintln!("TEST:VecAvg:{:?}:{}", vtd13, sigpro::vec_avg(&vtd13));
-    eprintln!("TEST:VecAvg:{:?}:{}", vtd21, sigpro::vec_avg(&vtd21));
-    eprintln!("TEST:VecAvg:{:?}:{}", vtd22, sigpro::vec_avg(&vtd22));
+    du_log!(info, "TEST:VecAvg:{:?}:{}", vtd11, sigpro::vec_avg(&vtd11));
+    du_log!(info, "TEST:VecAvg:{:?}:{}", vtd12, sigpro::vec_avg(&vtd12));
+    du_log!(info, "TEST:VecAvg:{:?}:{}", vtd13, sigpro::vec_avg(&vtd13));
+    du_log!(info, "TEST:VecAvg:{:?}:{}", vtd21, sigpro::vec_avg(&vtd21));
+    du_log!(info, "TEST:VecAvg:{:?}:{}", vtd22, sigpro::vec_avg(&vtd22));
+    assert_eq!(sigpro::vec_avg(&vtd11), 3);
+    assert_eq!(sigpro::vec_avg(&vtd12), 3);
+    assert_eq!(sigpro::vec_avg(&vtd13), 3);
+    assert!((sigpro::vec_avg(&vtd21) - 3.1).abs() < 1e-5);
+    assert!((sigpro::vec_avg(&vtd22) - 3.1).abs() < 1e-9);
+    // same results/panics with and without the simd feature
+    let vtd31: Vec<f64> = (1..=21).map(|x| x as f64).collect();
+    assert_eq!(sigpro::vec_avg(&vtd31), 11.0);
+    assert_eq!(sigpro::vec_avg(&(1..=21).collect::<Vec<i32>>()), 11);
+    assert!(std::panic::catch_unwind(|| sigpro::vec_avg::<f32>(&[])).is_err());
+    assert!(std::panic::catch_unwind(|| sigpro::vec_avg::<i32>(&[])).is_err());
+}
+
+pub fn test_dotf32() {
+    let va: Vec<f32> = (0..21).map(|x| x as f32).collect();
+    let vb: Vec<f32> = (0..21).map(|x| (x % 3) as f32).collect();
+    let dref: f32 = va.iter().zip(&vb).map(|(a, b)| a * b).sum();
+    let dgot = sigpro::dot_f32(&va, &vb);
+    du_log!(info, "TEST:DotF32:{:?}:{:?}:{}:{}", va, vb, dref, dgot);
+    assert!((dref - dgot).abs() < 1e-3);
+    assert_eq!(sigpro::dot_f32(&va[..3], &vb), 0.0*0.0 + 1.0*1.0 + 2.0*2.0);
 }
 
 pub fn test_lowpassavg() {
     let vtd1 = vec![(0,0.0),(0,1.0),(0,2.0), (1,3.0),(1,4.0),(1,5.0), (2,6.0),(2,7.0),(2,8.0),(2,9.0)];
-    eprintln!("TEST:LowPassAvg:{}:{:?}:{:?}", 0, vtd1, sigpro::sw_average_f_of_xf(&vtd1, 0));
-    eprintln!("TEST:LowPassAvg:{}:{:?}:{:?}", 1, vtd1, sigpro::sw_average_f_of_xf(&vtd1, 1));
-    eprintln!("TEST:LowPassAvg:{}:{:?}:{:?}", 2, vtd1, sigpro::sw_average_f_of_xf(&vtd1, 2));
-    eprintln!("TEST:LowPassAvg:{}:{:?}:{:?}", 3, vtd1, sigpro::sw_average_f_of_xf(&vtd1, 3));
+    let vf: Vec<f32> = vtd1.iter().map(|x| x.1).collect();
+    let vexp2 = [0.0, 1.5, 2.5, 3.5, 4.5, 5.5, 6.5, 7.5, 8.5, 9.0];
+    for ww in 1..4 {
+        let vavg = sigpro::sw_average_f_of_xf(&vtd1, ww);
+        du_log!(info, "TEST:LowPassAvg:{}:{:?}:{:?}", ww, vtd1, vavg);
+        let vgot: Vec<f32> = vavg.iter().map(|x| x.1).collect();
+        // A linear ramp is retained has is by odd window sizes
+        let vexp = if ww == 2 { &vexp2[..] } else { &vf[..] };
+        assert_vec_close(&vgot, vexp, 1e-5);
+        assert!(vavg.iter().zip(vtd1.iter()).all(|(a, b)| a.0 == b.0));
+    }
+    let vgot: Vec<f32> = sigpro::sw_average_f_of_xf(&[(0, 3.0), (1, 0.0), (2, 0.0), (3, 3.0)], 3).iter().map(|x| x.1).collect();
+    assert_vec_close(&vgot, &[3.0, 1.0, 1.0, 3.0], 1e-5);
 }
 
 pub fn test_crosscorr() {
     let vweights1 = vec![0.2,0.6,0.2];
     let vweights2 = vec![0.1,0.8,0.1];
     let vtd1 = vec![(0,0.0),(0,1.0),(0,2.0), (1,3.0),(1,4.0),(1,5.0), (2,6.0),(2,7.0),(2,8.0),(2,9.0)];
-    eprintln!("TEST:CrossCorr:{:?}:{:?}", vtd1, sigpro::sw_crosscorr_f_of_xf(&vtd1, &vweights1));
-    eprintln!("TEST:CrossCorr:{:?}:{:?}", vtd1, sigpro::sw_crosscorr_f_of_xf(&vtd1, &vweights2));
+    let vexp = [1.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 8.0];
+    for vweights in [&vweights1, &vweights2] {
+        let vcc = sigpro::sw_crosscorr_f_of_xf(&vtd1, vweights);
+        du_log!(info, "TEST:CrossCorr:{:?}:{:?}", vtd1, vcc);
+        let vgot: Vec<f32> = vcc.iter().map(|x| x.1).collect();
+        assert_vec_close(&vgot, &vexp, 1e-5);
+    }
+    let vgot: Vec<f32> = sigpro::sw_crosscorr_f_of_xf(&[(0, 0.0), (1, 10.0), (2, 0.0), (3, 0.0)], &vweights1).iter().map(|x| x.1).collect();
+    assert_vec_close(&vgot, &[6.0, 6.0, 2.0, 2.0], 1e-5);
+    // same results/panics with and without the simd feature
+    let vf: Vec<f32> = (0..20).map(|x| (x % 4) as f32).collect();
+    let vw = [0.1, 0.2, 0.4, 0.2, 0.1];
+    let vcc = sigpro::crosscorr_weighted(&vf, &vw);
+    let vref: Vec<f32> = (2..18).map(|i| (0..5).map(|j| vf[i-2+j]*vw[j]).sum()).collect();
+    assert_vec_close(&vcc[2..18], &vref, 1e-5);
+    assert_eq!((vcc[0], vcc[19]), (vcc[2], vcc[17]));
+    assert!(std::panic::catch_unwind(|| sigpro::crosscorr_weighted(&[1.0, 2.0, 3.0, 4.0], &[0.5, 0.5])).is_err());
+    assert!(std::panic::catch_unwind(|| sigpro::crosscorr_weighted(&[], &[])).is_err());
+    assert!(std::panic::catch_unwind(|| sigpro::crosscorr_weighted(&[1.0], &[0.2, 0.6, 0.2])).is_err());
+}
+
+pub fn test_plainslice() {
+    let vweights = vec![0.2,0.6,0.2];
+    let vtd1 = vec![(0,0.0),(0,1.0),(0,2.0), (1,3.0),(1,4.0),(1,5.0), (2,6.0),(2,7.0),(2,8.0),(2,9.0)];
+    let vf: Vec<f32> = vtd1.iter().map(|x| x.1).collect();
+    for ww in 1..4 {
+        let vavg = sigpro::sw_average(&vf, ww);
+        du_log!(info, "TEST:PlainSlice:SwAverage:{}:{:?}:{:?}", ww, vf, vavg);
+        let vref: Vec<f32> = sigpro::sw_average_f_of_xf(&vtd1, ww).iter().map(|x| x.1).collect();
+        assert_eq!(vavg, vref);
+    }
+    let vcc = sigpro::crosscorr_weighted(&vf, &vweights);
+    du_log!(info, "TEST:PlainSlice:CrossCorr:{:?}:{:?}", vf, vcc);
+    let vref: Vec<f32> = sigpro::sw_crosscorr_f_of_xf(&vtd1, &vweights).iter().map(|x| x.1).collect();
+    assert_eq!(vcc, vref);
+}
+
+pub fn test_linfit() {
+    let vtd1 = vec![(0.0,1.0),(1.0,3.0),(2.0,5.0),(3.0,7.0)];
+    let vtd2 = vec![(0.0,1.0),(1.0,2.0),(2.0,1.0),(3.0,2.0)];
+    let lf1 = sigpro::linfit(&vtd1).unwrap();
+    let lf2 = sigpro::linfit(&vtd2).unwrap();
+    du_log!(info, "TEST:LinFit:{:?}:{:?}", vtd1, lf1);
+    du_log!(info, "TEST:LinFit:{:?}:{:?}", vtd2, lf2);
+    assert!((lf1.slope - 2.0).abs() < 1e-9 && (lf1.intercept - 1.0).abs() < 1e-9 && (lf1.r2 - 1.0).abs() < 1e-9);
+    assert!((lf2.slope - 0.2).abs() < 1e-9 && (lf2.r2 - 0.2).abs() < 1e-9);
+    du_log!(info, "TEST:LinFit:Err:{:?}", sigpro::linfit(&[(1.0,1.0),(1.0,2.0)]));
+    assert!(sigpro::linfit(&[(1.0,1.0)]).is_err());
+    assert!(sigpro::linfit(&[(1.0,1.0),(1.0,2.0)]).is_err());
+}
+
+pub fn test_downsample() {
+    let vtd1: Vec<(f64, f32)> = (0..10).map(|i| (i as f64, ((i*7)%5) as f32)).collect();
+    for agg in [sigpro::Agg::Mean, sigpro::Agg::Min, sigpro::Agg::Max, sigpro::Agg::First, sigpro::Agg::Last] {
+        let vds = sigpro::downsample_buckets(&vtd1, 3, agg);
+        du_log!(info, "TEST:DownSample:{:?}:{:?}:{:?}", agg, vtd1, vds);
+        assert_eq!(vds.len(), 3);
+    }
+    assert_eq!(sigpro::downsample_buckets(&vtd1, 3, sigpro::Agg::First), vec![(0.0,0.0),(3.0,1.0),(6.0,2.0)]);
+    assert_eq!(sigpro::downsample_buckets(&vtd1, 3, sigpro::Agg::Max), vec![(2.0,4.0),(4.0,3.0),(7.0,4.0)]);
+    assert_eq!(sigpro::downsample_buckets(&vtd1, 20, sigpro::Agg::Last).len(), 10);
+    assert!(sigpro::downsample_buckets(&vtd1, 0, sigpro::Agg::Last).is_empty());
+}
+
+pub fn test_clampseries() {
+    let mut vtd1 = vec![-3.0, -1.0, 0.0, 0.5, 1.0, 2.0, f32::NAN];
+    let vorig = vtd1.clone();
+    let clipped = sigpro::clamp_series_counted(&mut vtd1, -1.0, 1.0);
+    du_log!(info, "TEST:ClampSeries:{:?}:{:?}:{}", vorig, vtd1, clipped);
+    assert_eq!(clipped, 2);
+    assert_eq!(vtd1[..6], [-1.0, -1.0, 0.0, 0.5, 1.0, 1.0]);
+    assert!(vtd1[6].is_nan());
+    let mut vtd2 = vec![10.0, 20.0, 30.0];
+    sigpro::clamp_series(&mut vtd2, 15.0, 25.0);
+    du_log!(info, "TEST:ClampSeries:{:?}", vtd2);
+    assert_eq!(vtd2, vec![15.0, 20.0, 25.0]);
+}
+
+pub fn test_uniformgrid() {
+    let vtd1 = vec![(0.0,0.0),(0.4,4.0),(1.0,10.0),(2.5,10.0)];
+    let vlin = sigpro::to_uniform_grid(&vtd1, 0.5, sigpro::FillPolicy::Linear).unwrap();
+    let vhold = sigpro::to_uniform_grid(&vtd1, 0.5, sigpro::FillPolicy::HoldLast).unwrap();
+    du_log!(info, "TEST:UniformGrid:Linear:{:?}:{:?}", vtd1, vlin);
+    du_log!(info, "TEST:UniformGrid:HoldLast:{:?}:{:?}", vtd1, vhold);
+    assert_eq!(vlin, vec![(0.0,0.0),(0.5,5.0),(1.0,10.0),(1.5,10.0),(2.0,10.0),(2.5,10.0)]);
+    assert_eq!(vhold, vec![(0.0,0.0),(0.5,4.0),(1.0,10.0),(1.5,10.0),(2.0,10.0),(2.5,10.0)]);
+    assert!(sigpro::to_uniform_grid(&vtd1, 0.0, sigpro::FillPolicy::Linear).is_err());
+    assert!(sigpro::to_uniform_grid(&[(1.0,0.0),(0.0,0.0)], 0.1, sigpro::FillPolicy::Linear).is_err());
+}
+
+pub fn test_rmsenvelope() {
+    let vtd1 = vec![(0,3.0),(1,-3.0),(2,3.0),(3,-4.0),(4,4.0),(5,-4.0)];
+    let vrms = sigpro::sw_rms_f_of_xf(&vtd1, 3);
+    du_log!(info, "TEST:SwRms:{:?}:{:?}", vtd1, vrms);
+    assert_eq!(vrms[0], (0, 3.0));
+    assert_eq!(vrms[5], (5, 4.0));
+    assert!((vrms[2].1 - (34.0f32/3.0).sqrt()).abs() < 1e-5);
+    let mut envf = sigpro::EnvelopeFollower::new(0.0, 10.0);
+    let venv: Vec<f32> = [1.0, -2.0, 0.0, 0.0, 0.0].iter().map(|x| envf.push(*x)).collect();
+    du_log!(info, "TEST:EnvelopeFollower:{:?}", venv);
+    assert_eq!(venv[0..2], [1.0, 2.0]);
+    assert!((venv[2] < 2.0) && (venv[3] < venv[2]) && (venv[4] > 0.0));
+    envf.reset();
+    assert_eq!(envf.envelope(), 0.0);
+}
+
+pub fn test_snrthd() {
+    let vref: Vec<f32> = (0..64).map(|i| (2.0*std::f32::consts::PI*(i as f32)/16.0).sin()).collect();
+    let vnoisy: Vec<f32> = vref.iter().enumerate().map(|(i, d)| d + if i % 2 == 0 { 0.1 } else { -0.1 }).collect();
+    let snr = sigpro::snr_wrt_reference(&vnoisy, &vref).unwrap();
+    du_log!(info, "TEST:Snr:{}", snr);
+    assert!((snr - 16.9897).abs() < 1e-3);
+    assert!(sigpro::snr_wrt_reference(&vnoisy, &vref[1..]).is_err());
+    let vdist: Vec<f32> = (0..64).map(|i| {
+        let a = 2.0*std::f32::consts::PI*(i as f32)/16.0;
+        a.sin() + 0.1*(2.0*a).sin() + 0.05*(3.0*a).sin()
+    }).collect();
+    let thd = sigpro::thd(&vdist, 1.0/16.0, 4).unwrap();
+    du_log!(info, "TEST:Thd:{}", thd);
+    assert!((thd - (0.0125f32).sqrt()).abs() < 1e-4);
+    assert!(sigpro::thd(&vdist, 0.0, 4).is_err());
+}
+
+pub fn test_joinseries() {
+    let va = vec![(0.0,1.0),(1.0,2.0),(2.1,3.0),(5.0,4.0)];
+    let vb = vec![(0.1,10.0),(2.0,20.0),(2.4,24.0)];
+    let vnear = sigpro::join_series(&va, &vb, 0.5, sigpro::JoinMode::Nearest).unwrap();
+    let vinterp = sigpro::join_series(&va, &vb, 1.0, sigpro::JoinMode::Interpolate).unwrap();
+    du_log!(info, "TEST:JoinSeries:Nearest:{:?}:{:?}:{:?}", va, vb, vnear);
+    du_log!(info, "TEST:JoinSeries:Interpolate:{:?}:{:?}:{:?}", va, vb, vinterp);
+    assert_eq!(vnear, vec![(0.0,1.0,10.0),(2.1,3.0,20.0)]);
+    assert_eq!(vinterp.len(), 2);
+    assert_eq!((vinterp[1].0, vinterp[1].1), (2.1, 3.0));
+    assert!((vinterp[0].2 - (10.0 + 10.0*0.9/1.9)).abs() < 1e-4);
+    assert!((vinterp[1].2 - 21.0).abs() < 1e-4);
+    assert!(sigpro::join_series(&vb, &va[1..], 1.0, sigpro::JoinMode::Nearest).unwrap().len() == 3);
+    assert!(sigpro::join_series(&[(1.0,0.0),(0.0,0.0)], &vb, 1.0, sigpro::JoinMode::Nearest).is_err());
+}
+
+pub fn test_swminmax() {
+    let vtd1: Vec<(usize, f32)> = [5.0, 1.0, 4.0, 2.0, 8.0, 3.0, 3.0, 7.0, 0.0, 6.0].iter().cloned().enumerate().collect();
+    for ww in 1..5 {
+        let vmin = sigpro::sw_min_f_of_xf(&vtd1, ww);
+        let vmax = sigpro::sw_max_f_of_xf(&vtd1, ww);
+        du_log!(info, "TEST:SwMinMax:{}:{:?}:{:?}:{:?}", ww, vtd1, vmin, vmax);
+        // Check against a naive per window scan
+        let wwh = ww/2;
+        let wsh = if ww % 2 == 0 { wwh - 1 } else { wwh };
+        for i in 0..vtd1.len() {
+            let vw = &vtd1[i.saturating_sub(wsh)..(i+wwh+1).min(vtd1.len())];
+            let fmin = vw.iter().map(|x| x.1).fold(f32::INFINITY, f32::min);
+            let fmax = vw.iter().map(|x| x.1).fold(f32::NEG_INFINITY, f32::max);
+            assert_eq!(vmin[i], (i, fmin));
+            assert_eq!(vmax[i], (i, fmax));
+        }
+    }
+}
+
+pub fn test_kalman1d() {
+    let vmeas = vec![10.0, 12.0, 8.0, 11.0, 9.0, 10.5, 9.5, 10.0];
+    let mut kf = sigpro::Kalman1D::new(0.001, 1.0);
+    let vest: Vec<f32> = vmeas.iter().map(|m| kf.push(*m)).collect();
+    du_log!(info, "TEST:Kalman1D:{:?}:{:?}", vmeas, vest);
+    assert_eq!(vest[0], 10.0);
+    assert!((vest[vest.len()-1] - 10.0).abs() < 0.2);
+    assert!((vest[1] - 10.0).abs() < (12.0 - 10.0));
+    kf.reset();
+    assert_eq!(kf.estimate(), None);
+}
+
+pub fn test_crossings() {
+    let vtd1: Vec<(f64, f32)> = [0.0, 0.6, 0.4, 0.6, 1.0, 0.5, 0.9, 0.3, 0.1, 0.8].iter().enumerate().map(|(i, f)| (i as f64, *f)).collect();
+    let vevents = sigpro::detect_crossings(&vtd1, 0.8, 0.2);
+    du_log!(info, "TEST:Crossings:{:?}:{:?}", vtd1, vevents);
+    assert_eq!(vevents, vec![
+        sigpro::Event { x: 4.0, kind: sigpro::EdgeKind::Rise },
+        sigpro::Event { x: 8.0, kind: sigpro::EdgeKind::Fall },
+        sigpro::Event { x: 9.0, kind: sigpro::EdgeKind::Rise },
+    ]);
+    assert!(sigpro::detect_crossings(&vtd1[4..7], 0.8, 0.2).is_empty());
+}
+
+pub fn test_missing() {
+    let vtd1 = sigpro::missing_from_nan(&[f32::NAN, 2.0, f32::NAN, 4.0, 6.0]);
+    du_log!(info, "TEST:Missing:{:?}", vtd1);
+    assert_eq!(vtd1, vec![None, Some(2.0), None, Some(4.0), Some(6.0)]);
+    assert_eq!(sigpro::resolve_missing(&vtd1, sigpro::MissingPolicy::Skip).unwrap(), vec![2.0, 4.0, 6.0]);
+    assert_eq!(sigpro::resolve_missing(&vtd1, sigpro::MissingPolicy::FillPrevious).unwrap(), vec![2.0, 2.0, 2.0, 4.0, 6.0]);
+    assert_eq!(sigpro::resolve_missing(&vtd1, sigpro::MissingPolicy::FillValue(0.0)).unwrap(), vec![0.0, 2.0, 0.0, 4.0, 6.0]);
+    assert!(sigpro::resolve_missing(&vtd1, sigpro::MissingPolicy::Error).is_err());
+    assert!(sigpro::resolve_missing(&[None, Some(f32::NAN)], sigpro::MissingPolicy::FillPrevious).is_err());
+    let avg = sigpro::vec_avg_missing(&vtd1, sigpro::MissingPolicy::Skip).unwrap();
+    let vavg = sigpro::sw_average_missing(&vtd1, 3, sigpro::MissingPolicy::FillValue(0.0)).unwrap();
+    let vcc = sigpro::crosscorr_weighted_missing(&vtd1, &[0.5, 0.5, 0.0], sigpro::MissingPolicy::FillPrevious).unwrap();
+    du_log!(info, "TEST:Missing:Avg[{}]:SwAvg[{:?}]:CrossCorr[{:?}]", avg, vavg, vcc);
+    assert_eq!(avg, 4.0);
+    assert!(sigpro::sw_average_missing(&vtd1, 4, sigpro::MissingPolicy::Skip).is_err());
+    assert!(sigpro::sw_average_missing(&vtd1, 0, sigpro::MissingPolicy::Skip).is_err());
+    assert!(sigpro::crosscorr_weighted_missing(&vtd1, &[0.5, 0.5], sigpro::MissingPolicy::Skip).is_err());
+    assert!(sigpro::crosscorr_weighted_missing(&[], &[], sigpro::MissingPolicy::Skip).is_err());
+}
+
+pub fn test_nexttoken() {
+    let sline = "  abc   \"a b\\tc\\\"d\\x41\\u{263A}\"  123 \"\" ";
+    let mut vtoks = Vec::new();
+    let mut srem = sline.to_string();
+    loop {
+        let (tok, rem) = text::next_token(&srem);
+        if tok.is_empty() {
+            break;
+        }
+        vtoks.push(tok);
+        srem = rem;
+    }
+    du_log!(info, "TEST:NextToken:[{}]:{:?}", sline, vtoks);
+    assert_eq!(vtoks, vec!["abc", "\"a b\tc\"dA\u{263A}\"", "123", "\"\""]);
+    let scleaned = text::remove_extra_whitespaces(sline);
+    du_log!(info, "TEST:RemoveExtraWhitespaces:[{}]:[{}]", sline, scleaned);
+    assert_eq!(scleaned, "abc \"a b\\tc\\\"d\\x41\\u{263A}\" 123 \"\"");
+}
+
+pub fn test_tokenizer() {
+    let sline = " add  \"a\\\"b\"  -12 0x1F 1.5e3 x1 ";
+    let vtoks: Vec<text::Token> = text::Tokenizer::new(sline).collect();
+    for tok in &vtoks {
+        du_log!(info, "TEST:Tokenizer:[{}]:{:?}:[{}]", sline, tok, &sline[tok.span.clone()]);
+    }
+    let vkinds: Vec<text::TokenKind> = vtoks.iter().map(|t| t.kind).collect();
+    use text::TokenKind::*;
+    assert_eq!(vkinds, vec![Word, Quoted, Number, Number, Number, Word]);
+    assert_eq!(vtoks[1].text, "a\"b");
+    assert_eq!(&sline[vtoks[1].span.clone()], "\"a\\\"b\"");
+    assert_eq!(vtoks[5].span, 29..31);
+}
+
+pub fn test_tokenizerconfig() {
+    let sline = "cmd,'a,b' ,`c d`,e\\,f,,^x41";
+    let tcfg = text::TokenizerConfig {
+        quote_chars: vec!['\'', '`'],
+        escape_char: Some('^'),
+        escape_outside_quotes: true,
+        delimiters: vec![','],
+        brackets: Vec::new(),
+        ..Default::default()
+    };
+    let vtoks: Vec<String> = text::Tokenizer::with_config(sline, tcfg).map(|t| t.text).collect();
+    du_log!(info, "TEST:TokenizerConfig:[{}]:{:?}", sline, vtoks);
+    assert_eq!(vtoks, vec!["cmd", "a,b", " ", "c d", "e\\", "f", "A"]);
+}
+
+pub fn test_tokenizergroups() {
+    let sline = "set v [1, (2 3), \"x ] y\"] call(a, {b c}) [unterminated (x y";
+    let vtoks: Vec<text::Token> = text::Tokenizer::new(sline).collect();
+    let vtexts: Vec<&str> = vtoks.iter().map(|t| t.text.as_str()).collect();
+    du_log!(info, "TEST:TokenizerGroups:[{}]:{:?}", sline, vtexts);
+    assert_eq!(vtexts, vec!["set", "v", "[1, (2 3), \"x ] y\"]", "call(a, {b c})", "[unterminated (x y"]);
+    assert_eq!(vtoks[2].kind, text::TokenKind::Group);
+    assert_eq!(vtoks[3].kind, text::TokenKind::Word);
+}
+
+pub fn test_splitcsv() {
+    let sline = "a,\"b,c\",,\"d \"\"e\"\"\",f\"g,\"h";
+    let vfields = text::split_csv(sline, ',');
+    du_log!(info, "TEST:SplitCsv:[{}]:{:?}", sline, vfields);
+    assert_eq!(vfields, vec!["a", "b,c", "", "d \"e\"", "f\"g", "h"]);
+    assert_eq!(text::split_csv("", ';'), vec![""]);
+    assert_eq!(text::split_csv("1;2;", ';'), vec!["1", "2", ""]);
+}
+
+pub fn test_substitute() {
+    let resolver = |name: &str| match name {
+        "user" => Some("hkvc".to_string()),
+        "len" => Some(16.to_string()),
+        _ => None,
+    };
+    let stmpl = "hello ${user}, len=${len} cost $$5 $x";
+    let sout = text::substitute(stmpl, resolver).unwrap();
+    du_log!(info, "TEST:Substitute:[{}]:[{}]", stmpl, sout);
+    assert_eq!(sout, "hello hkvc, len=16 cost $5 $x");
+    assert!(text::substitute("${unknown}", resolver).is_err());
+    assert!(text::substitute("abc ${user", resolver).is_err());
+}
+
+pub fn test_tokenerror() {
+    let vtests = vec![
+        ("abc \"unterminated", Some(text::TokenError::UnterminatedQuote { start_col: 4 })),
+        ("  \"bad \\q escape\"", Some(text::TokenError::BadEscape { col: 7 })),
+        ("\"trailing \\", Some(text::TokenError::BadEscape { col: 10 })),
+        ("x [1, (2 3]", Some(text::TokenError::UnterminatedGroup { start_col: 2 })),
+        ("[1, \"a]", Some(text::TokenError::UnterminatedQuote { start_col: 4 })),
+        ("\"ok \\\" \\x41\" rest", None),
+    ];
+    for (sin, exp) in vtests {
+        let mut toker = text::Tokenizer::new(sin);
+        let got = loop {
+            match toker.next_checked() {
+                Ok(Some(_)) => continue,
+                Ok(None) => break None,
+                Err(err) => break Some(err),
+            }
+        };
+        du_log!(info, "TEST:TokenError:[{}]:{:?}", sin, got);
+        if let Some(err) = &got {
+            du_log!(info, "TEST:TokenError:{}", err);
+        }
+        assert_eq!(got, exp);
+    }
+    let got = text::next_token_checked("\"ok \\\" \\x41\" rest");
+    assert_eq!(got, Ok(("\"ok \" A\"".to_string(), " rest".to_string())));
+    assert!(text::next_token_checked("\"unterminated").is_err());
+    let (tok, _) = text::next_token("\"unterminated \\q");
+    assert_eq!(tok, "\"unterminated q\"");
+}
+
+pub fn test_nexttokenref() {
+    let sline = "  abc \"a \\\"b\\x41\" [1 2]  0x10 ";
+    let mut vtoks = Vec::new();
+    let mut srem = sline;
+    loop {
+        let (tok, rem) = text::next_token_ref(srem).unwrap();
+        if tok.is_empty() {
+            break;
+        }
+        vtoks.push(tok);
+        srem = rem;
+    }
+    du_log!(info, "TEST:NextTokenRef:[{}]:{:?}", sline, vtoks);
+    assert_eq!(vtoks, vec!["abc", "\"a \\\"b\\x41\"", "[1 2]", "0x10"]);
+    assert!(text::next_token_ref("\"unterminated").is_err());
+    assert_eq!(text::next_token_ref("   "), Ok(("", "")));
+}
+
+pub fn test_linecontinuation() {
+    let vlines = vec!["set a \\", "  1 2 \\", "3", "path c:\\\\", "last \\"];
+    let vjoined = text::join_continued_lines(&vlines);
+    du_log!(info, "TEST:JoinContinuedLines:{:?}:{:?}", vlines, vjoined);
+    assert_eq!(vjoined, vec!["set a   1 2 3", "path c:\\\\", "last "]);
+    let sin = "cmd \"long \\\nstring\" ar\\\ng \\\n  last";
+    let vtoks: Vec<String> = text::Tokenizer::new(sin).map(|t| t.text).collect();
+    du_log!(info, "TEST:TokenizerContinuation:[{}]:{:?}", sin, vtoks);
+    assert_eq!(vtoks, vec!["cmd", "long string", "arg", "last"]);
+}
+
+pub fn test_extractbalanced() {
+    let sin = "call foo(a, \"b,c)\", (1,2)) rest";
+    let (inner, rest) = text::extract_balanced(sin, '(', ')').unwrap();
+    du_log!(info, "TEST:ExtractBalanced:[{}]:[{}]:[{}]", sin, inner, rest);
+    assert_eq!(inner, "a, \"b,c)\", (1,2)");
+    assert_eq!(rest, " rest");
+    assert_eq!(text::extract_balanced("[[x]]", '[', ']'), Ok(("[x]", "")));
+    assert!(text::extract_balanced("foo(a, (b)", '(', ')').is_err());
+    assert!(text::extract_balanced("foo", '(', ')').is_err());
+}
+
+pub fn test_escapestr() {
+    let vtests = vec!["plain", "", "a\"b\\c", "line1\nline2\ttab\r", "\x00\x07\x1b\u{7f}\u{85}", "uni \u{263A} \u{2028}"];
+    for sraw in vtests {
+        let sesc = text::escape_str(sraw);
+        let sback = text::unescape_str(&sesc).unwrap();
+        du_log!(info, "TEST:EscapeStr:{:?}:[{}]:{:?}", sraw, sesc, sback);
+        assert_eq!(sraw, sback);
+        let (tok, _) = text::next_token_checked(&sesc).unwrap();
+        assert_eq!(tok, format!("\"{}\"", sraw));
+    }
+    assert_eq!(text::escape_str("a\x01\"\n"), "\"a\\x01\\\"\\n\"");
+    assert_eq!(text::unescape_str("no quotes \\x41"), Ok("no quotes A".to_string()));
+    assert_eq!(text::unescape_str("\"bad \\q\""), Err(text::TokenError::BadEscape { col: 5 }));
+    assert!(text::unescape_str("\"unterminated").is_err());
+    assert!(text::unescape_str("\"\\u{+41}\"").is_err());
+    assert_eq!(text::unescape_str("\"\\u{41}\""), Ok("A".to_string()));
+    assert!(text::unescape_str("\"escaped quote\\\"").is_err());
+}
+
+pub fn test_preprocessor() {
+    let tdir = std::env::temp_dir().join(format!("datautilsk-pp-{}", std::process::id()));
+    std::fs::create_dir_all(tdir.join("sub")).unwrap();
+    std::fs::write(tdir.join("sub/inc.txt"), "!define COUNT 3\ninc COUNT\n").unwrap();
+    std::fs::write(tdir.join("main.txt"), "!include sub/inc.txt\n!ifdef COUNT\nloop COUNT \"COUNT\" \\\n  COUNTER\n!else\nnever\n!endif\n!ifndef DEBUG\nrelease\n!endif\n").unwrap();
+    std::fs::write(tdir.join("loop.txt"), "!include loop.txt\n").unwrap();
+    let mut pp = text::Preprocessor::new();
+    let vlines = pp.process_file(tdir.join("main.txt")).unwrap();
+    du_log!(info, "TEST:Preprocessor:{:?}", vlines);
+    assert_eq!(vlines, vec!["inc 3", "loop 3 \"COUNT\"   COUNTER", "release"]);
+    let mut pp = text::Preprocessor::new();
+    pp.define("DEBUG", "1");
+    let vlines = pp.process_lines(&["!ifdef DEBUG", "dbg DEBUG", "!endif"], &tdir).unwrap();
+    assert_eq!(vlines, vec!["dbg 1"]);
+    assert!(pp.process_lines(&["!ifdef X"], &tdir).is_err());
+    assert!(pp.process_lines(&["!endif"], &tdir).is_err());
+    assert!(pp.process_lines(&["!bogus"], &tdir).is_err());
+    // line numbers in errors are wrt the source lines, even after continued lines
+    let err = pp.process_lines(&["a \\", "b \\", "c", "!bogus"], &tdir).unwrap_err();
+    assert!(err.contains(":4:"), "{}", err);
+    let err = pp.process_lines(&["x", "!bogus \\", "more"], &tdir).unwrap_err();
+    assert!(err.contains(":2:"), "{}", err);
+    let err = text::Preprocessor::new().process_file(tdir.join("loop.txt")).unwrap_err();
+    du_log!(info, "TEST:Preprocessor:{}", err);
+    std::fs::remove_dir_all(&tdir).unwrap();
+}
+
+pub fn test_pack() {
+    let sfmt = "<u16 u32 4s >u16 i8 2x !i32";
+    let vvals = vec![Variant::IntValue(0x1234), Variant::IntValue(0x11223344), Variant::from("\"ab\""), Variant::IntValue(0x5566), Variant::IntValue(-2), Variant::IntValue(-3)];
+    let vbuf = pack::pack(sfmt, &vvals).unwrap();
+    du_log!(info, "TEST:Pack:[{}]:{:?}:{}", sfmt, vvals, hex::hex_from_vu8(&vbuf));
+    assert_eq!(hex::hex_from_vu8(&vbuf), "341244332211616200005566FE0000FFFFFFFD");
+    assert_eq!(pack::calcsize(sfmt), Ok(vbuf.len()));
+    let vback = pack::unpack(sfmt, &vbuf).unwrap();
+    du_log!(info, "TEST:Unpack:[{}]:{:?}", sfmt, vback);
+    let vints: Vec<isize> = vback.iter().map(|v| v.get_isize().unwrap_or(0)).collect();
+    assert_eq!(vints, vec![0x1234, 0x11223344, 0, 0x5566, -2, -3]);
+    assert_eq!(vback[2].get_bufvu8(), vec![0x61, 0x62, 0, 0]);
+    assert!(pack::pack("u8", &[Variant::IntValue(256)]).is_err());
+    assert!(pack::pack("i8", &[Variant::IntValue(-129)]).is_err());
+    assert!(pack::pack("u8 u8", &[Variant::IntValue(1)]).is_err());
+    assert!(pack::pack("2s", &[Variant::from("\"abc\"")]).is_err());
+    assert!(pack::unpack("u32", &[1, 2, 3]).is_err());
+    assert!(pack::unpack("u24", &[1, 2, 3]).is_err());
+    assert!(pack::calcsize("é").is_err());
+    assert!(pack::calcsize("<u16 2é").is_err());
+    assert!(pack::calcsize("4é s").is_err());
+    assert_eq!(pack::calcsize("2x 3s"), Ok(5));
+    // huge counts give errors, rather than overflow/allocation panics
+    assert!(pack::calcsize("18446744073709551615s u8").is_err());
+    assert!(pack::unpack("u8 18446744073709551615s", &[1, 2]).is_err());
+    assert!(pack::unpack_into::<1>("u8 18446744073709551615x", &[1, 2]).is_err());
+    assert!(pack::unpack_into::<1>("18446744073709551615x u8", &[1, 2]).is_err());
+    assert!(pack::pack("9223372036854775807x u8", &[Variant::IntValue(1)]).is_err());
+}
+
+pub fn test_bytecursor() {
+    let mut bc = bytes::ByteCursor::new();
+    bc.put_u8(0x01);
+    bc.put_u16_le(0x0302);
+    bc.put_u32_be(0x04050607);
+    bc.put_u64_le(0x0F0E0D0C0B0A0908);
+    bc.put_bytes(b"xy");
+    du_log!(info, "TEST:ByteCursor:{}:{}", bc.position(), hex::hex_from_vu8(bc.as_slice()));
+    assert_eq!(hex::hex_from_vu8(bc.as_slice()), "0102030405060708090A0B0C0D0E0F7879");
+    bc.seek(1).unwrap();
+    bc.put_u16_be(0xAABB);
+    assert_eq!(bc.len(), 17);
+    bc.seek(0).unwrap();
+    assert_eq!(bc.get_u8(), Ok(1));
+    assert_eq!(bc.get_u16_le(), Ok(0xBBAA));
+    assert_eq!(bc.get_u32_be(), Ok(0x04050607));
+    assert_eq!(bc.get_u64_le(), Ok(0x0F0E0D0C0B0A0908));
+    assert_eq!(bc.remaining(), 2);
+    assert!(bc.get_u32_le().is_err());
+    assert_eq!(bc.position(), 15);
+    assert_eq!(bc.get_bytes(2), Ok(&b"xy"[..]));
+    assert!(bc.seek(18).is_err());
+}
+
+pub fn test_checksums() {
+    du_log!(info, "TEST:Checksum:Adler32:{:08X}:Fletcher16:{:04X}:Fletcher32:{:08X}", checksum::adler32(b"Wikipedia"), checksum::fletcher16(b"abcde"), checksum::fletcher32(b"abcde"));
+    assert_eq!(checksum::adler32(b"Wikipedia"), 0x11E60398);
+    assert_eq!(checksum::adler32(b""), 1);
+    assert_eq!(checksum::fletcher16(b"abcde"), 0xC8F0);
+    assert_eq!(checksum::fletcher16(b"abcdef"), 0x2057);
+    assert_eq!(checksum::fletcher32(b"abcde"), 0xF04FC729);
+    assert_eq!(checksum::fletcher32(b"abcdef"), 0x56502D2A);
+    // Incremental with odd splits should match one-shot
+    let vdata: Vec<u8> = (0..20000u32).map(|i| (i * 7 % 251) as u8).collect();
+    let mut ad = checksum::Adler32::new();
+    let mut f16 = checksum::Fletcher16::new();
+    let mut f32 = checksum::Fletcher32::new();
+    for chunk in vdata.chunks(333) {
+        let (c1, c2) = chunk.split_at(1);
+        for c in [c1, c2] {
+            ad.update(c);
+            f16.update(c);
+            f32.update(c);
+        }
+    }
+    assert_eq!(ad.value(), checksum::adler32(&vdata));
+    assert_eq!(f16.value(), checksum::fletcher16(&vdata));
+    assert_eq!(f32.value(), checksum::fletcher32(&vdata));
+}
+
+pub fn test_hashes() {
+    let sdata = b"Nobody inspects the spammish repetition";
+    du_log!(info, "TEST:Hash:Fnv1a32:{:08X}:Fnv1a64:{:016X}:XXH64:{:016X}", hash::fnv1a_32(sdata), hash::fnv1a_64(sdata), hash::xxh64(sdata, 0));
+    assert_eq!(hash::fnv1a_32(b""), 0x811C9DC5);
+    assert_eq!(hash::fnv1a_32(b"a"), 0xE40C292C);
+    assert_eq!(hash::fnv1a_64(b"a"), 0xAF63DC4C8601EC8C);
+    assert_eq!(hash::xxh64(b"", 0), 0xEF46DB3751D8E999);
+    assert_eq!(hash::xxh64(b"abc", 0), 0x44BC2CF5AD770999);
+    assert_eq!(hash::xxh64(sdata, 0), 0xFBCEA83C8A378BF1);
+    assert_ne!(hash::xxh64(sdata, 1), hash::xxh64(sdata, 0));
+}
+
+pub fn test_bytesdiff() {
+    let va = b"ABCDEFGHIJ";
+    let vb = b"ABxDEFyzIJKL";
+    let vdiffs = bytes::diff(va, vb);
+    du_log!(info, "TEST:BytesDiff:{:?}", vdiffs);
+    assert_eq!(vdiffs, vec![
+        bytes::DiffRange { offset: 2, offset_b: 2, len_a: 1, len_b: 1 },
+        bytes::DiffRange { offset: 6, offset_b: 6, len_a: 2, len_b: 2 },
+        bytes::DiffRange { offset: 10, offset_b: 10, len_a: 0, len_b: 2 },
+    ]);
+    assert!(bytes::diff(va, va).is_empty());
+    let va = b"HEADERpayload-TAIL-END";
+    let vb = b"HEADER++payload-TL-END";
+    let vdiffs = bytes::diff_aligned(va, vb, 4);
+    du_log!(info, "TEST:BytesDiffAligned:{:?}", vdiffs);
+    assert_eq!(vdiffs, vec![
+        bytes::DiffRange { offset: 6, offset_b: 6, len_a: 0, len_b: 2 },
+        bytes::DiffRange { offset: 15, offset_b: 17, len_a: 2, len_b: 0 },
+    ]);
+    assert_eq!(bytes::diff_aligned(b"AB", b"ABCD", 1), vec![bytes::DiffRange { offset: 2, offset_b: 2, len_a: 0, len_b: 2 }]);
+}
+
+pub fn test_applypatches() {
+    let mut vbuf = b"0123456789".to_vec();
+    let vpatches = vec![
+        bytes::Patch { offset: 8, replace_len: 2, data: b"XYZ".to_vec() },
+        bytes::Patch { offset: 0, replace_len: 0, data: b"<<".to_vec() },
+        bytes::Patch { offset: 2, replace_len: 3, data: b"a".to_vec() },
+    ];
+    bytes::apply_patches(&mut vbuf, &vpatches).unwrap();
+    du_log!(info, "TEST:ApplyPatches:{:?}:{}", vpatches, String::from_utf8_lossy(&vbuf));
+    assert_eq!(vbuf, b"<<01a567XYZ".to_vec());
+    let mut vbuf = b"0123".to_vec();
+    let voverlap = vec![bytes::Patch { offset: 0, replace_len: 2, data: vec![] }, bytes::Patch { offset: 1, replace_len: 1, data: vec![] }];
+    assert!(bytes::apply_patches(&mut vbuf, &voverlap).is_err());
+    assert!(bytes::apply_patches(&mut vbuf, &[bytes::Patch { offset: 3, replace_len: 2, data: vec![] }]).is_err());
+    assert!(bytes::apply_patches(&mut vbuf, &[bytes::Patch { offset: 1, replace_len: usize::MAX, data: vec![] }]).is_err());
+    assert_eq!(vbuf, b"0123".to_vec());
+    bytes::apply_patches(&mut vbuf, &[bytes::Patch { offset: 4, replace_len: 0, data: b"4".to_vec() }]).unwrap();
+    assert_eq!(vbuf, b"01234".to_vec());
+}
+
+pub fn test_sliceext() {
+    use bytes::{SliceReadExt, SliceWriteExt};
+    let mut abuf = [0u8; 16];
+    abuf.put_u8(0, 0x01).unwrap();
+    abuf.put_u16_le(1, 0x0302).unwrap();
+    abuf.put_u32_be(3, 0x04050607).unwrap();
+    abuf.put_u64_be(7, 0x08090A0B0C0D0E0F).unwrap();
+    abuf.put_bytes(15, &[0x10]).unwrap();
+    du_log!(info, "TEST:SliceExt:{:?}", abuf);
+    assert_eq!(abuf.to_vec(), (1u8..=16).collect::<Vec<u8>>());
+    assert!(abuf.put_u16_be(15, 0).is_err());
+    let sbuf: &[u8] = &abuf;
+    assert_eq!(sbuf.get_u16_be(0), Ok(0x0102));
+    assert_eq!(sbuf.get_u32_le(4), Ok(0x08070605));
+    assert_eq!(sbuf.get_u64_le(8), Ok(0x100F0E0D0C0B0A09));
+    assert_eq!(sbuf.get_bytes(14, 2), Ok(&[15u8, 16][..]));
+    assert!(sbuf.get_u32_be(13).is_err());
+    assert!(sbuf.get_bytes(usize::MAX, 2).is_err());
+}
+
+pub fn test_frames() {
+    use bytes::frames::{self, LenPrefix};
+    let vframes = vec![b"hello".to_vec(), Vec::new(), vec![0xAA; 300]];
+    for prefix in [LenPrefix::U16BE, LenPrefix::U32LE, LenPrefix::Varint] {
+        let vstream = frames::encode_frames(&vframes, prefix).unwrap();
+        du_log!(info, "TEST:Frames:{:?}:{}", prefix, hex::hex_from_vu8(&vstream[..8]));
+        let mut fd = frames::FrameDecoder::new(prefix);
+        let mut vgot = Vec::new();
+        for chunk in vstream.chunks(7) {
+            fd.feed(chunk);
+            while let Some(frame) = fd.next_frame().unwrap() {
+                vgot.push(frame);
+            }
+        }
+        assert_eq!(vgot, vframes);
+        assert_eq!(fd.pending(), 0);
+    }
+    assert!(frames::encode_frames(&vframes, LenPrefix::U8).is_err());
+    let vstream = frames::encode_frames(&[vec![1; 200]], LenPrefix::Varint).unwrap();
+    assert_eq!(vstream[..2], [0xC8, 0x01]);
+    let mut fd = frames::FrameDecoder::new(LenPrefix::Varint);
+    fd.feed(&[0xFF; 11]);
+    assert!(fd.next_frame().is_err());
+    // hostile length prefixes
+    let mut fd = frames::FrameDecoder::new(LenPrefix::Varint);
+    fd.feed(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01, 0x00]);
+    assert!(fd.next_frame().is_err());
+    let mut fd = frames::FrameDecoder::new(LenPrefix::Varint);
+    fd.feed(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02]);
+    assert!(fd.next_frame().is_err());
+    let mut fd = frames::FrameDecoder::new(LenPrefix::U32LE).with_max_len(1024);
+    fd.feed(&[0xFF, 0xFF, 0xFF, 0x7F, 0x00]);
+    assert!(fd.next_frame().is_err());
+    let mut fd = frames::FrameDecoder::new(LenPrefix::U16BE).with_max_len(4);
+    fd.feed(&[0x00, 0x02, 0xAB, 0xCD]);
+    assert_eq!(fd.next_frame(), Ok(Some(vec![0xAB, 0xCD])));
+}
+
+pub fn test_rle() {
+    let mut vdata = b"abcc".to_vec();
+    vdata.extend([0u8; 600]);
+    vdata.extend([0xFF, 0x01, 0xFF, 0xFF, b'z', b'z', b'z', b'z']);
+    let venc = bytes::rle_encode(&vdata);
+    du_log!(info, "TEST:Rle:{}:{}", vdata.len(), hex::hex_from_vu8(&venc));
+    assert_eq!(hex::hex_from_vu8(&venc), "61626363FFFF00FFFF00FF5A00FF01FF01FF02FFFF047A");
+    assert_eq!(bytes::rle_decode(&venc), Ok(vdata));
+    assert_eq!(bytes::rle_encode(b"plain"), b"plain".to_vec());
+    assert!(bytes::rle_decode(&[0x61, 0xFF, 0x02]).is_err());
+    assert!(bytes::rle_decode(&[0xFF, 0x00, 0x61]).is_err());
+    assert_eq!(bytes::rle_decode(&[]), Ok(vec![]));
+}
+
+pub fn test_bytetransforms() {
+    let vorig = b"Hello, World".to_vec();
+    let mut vdata = vorig.clone();
+    bytes::xor_with(&mut vdata, &[0x55, 0xAA]);
+    bytes::rotl_per_byte(&mut vdata, 3);
+    bytes::add_mod256(&mut vdata, 200);
+    du_log!(info, "TEST:ByteTransforms:{}:{}", hex::hex_from_vu8(&vorig), hex::hex_from_vu8(&vdata));
+    bytes::add_mod256(&mut vdata, 200u8.wrapping_neg());
+    bytes::rotr_per_byte(&mut vdata, 3);
+    bytes::xor_with(&mut vdata, &[0x55, 0xAA]);
+    assert_eq!(vdata, vorig);
+    let mut vone = vec![0x81u8, 0x0F];
+    bytes::rotl_per_byte(&mut vone, 1);
+    assert_eq!(vone, vec![0x03, 0x1E]);
+    bytes::xor_with(&mut vone, &[]);
+    assert_eq!(vone, vec![0x03, 0x1E]);
+    bytes::add_mod256(&mut vone, 0xFE);
+    assert_eq!(vone, vec![0x01, 0x1C]);
+}
+
+pub fn test_cobs() {
+    let vtests: Vec<(Vec<u8>, Vec<u8>)> = vec![
+        (vec![], vec![0x01]),
+        (vec![0x00], vec![0x01, 0x01]),
+        (vec![0x00, 0x00], vec![0x01, 0x01, 0x01]),
+        (vec![0x11, 0x22, 0x00, 0x33], vec![0x03, 0x11, 0x22, 0x02, 0x33]),
+        (vec![0x11, 0x00, 0x00, 0x00], vec![0x02, 0x11, 0x01, 0x01, 0x01]),
+    ];
+    for (vdata, vexp) in vtests {
+        let venc = bytes::cobs_encode(&vdata);
+        du_log!(info, "TEST:Cobs:{}:{}", hex::hex_from_vu8(&vdata), hex::hex_from_vu8(&venc));
+        assert_eq!(venc, vexp);
+        assert_eq!(bytes::cobs_decode(&venc), Ok(vdata));
+    }
+    let vlong: Vec<u8> = (1..=255u8).collect();
+    let venc = bytes::cobs_encode(&vlong);
+    assert_eq!(venc.len(), 257);
+    assert_eq!(venc[0], 0xFF);
+    assert_eq!(venc[255], 0x02);
+    assert!(!venc.contains(&0));
+    assert_eq!(bytes::cobs_decode(&venc), Ok(vlong));
+    // the 254 and 255 byte examples from the cobs paper/wiki
+    let vlong: Vec<u8> = (1..=254u8).collect();
+    let venc = bytes::cobs_encode(&vlong);
+    assert_eq!(venc.len(), 255);
+    assert_eq!(venc[0], 0xFF);
+    assert_eq!(&venc[1..], &vlong[..]);
+    assert_eq!(bytes::cobs_decode(&venc), Ok(vlong));
+    let vlong: Vec<u8> = (0..=254u8).collect();
+    let venc = bytes::cobs_encode(&vlong);
+    assert_eq!(venc.len(), 256);
+    assert_eq!(&venc[..2], &[0x01, 0xFF]);
+    assert_eq!(&venc[2..], &vlong[1..]);
+    assert_eq!(bytes::cobs_decode(&venc), Ok(vlong));
+    let vlong: Vec<u8> = (2..=255u8).chain([0]).collect();
+    let venc = bytes::cobs_encode(&vlong);
+    assert_eq!(venc.len(), 257);
+    assert_eq!(&venc[255..], &[0x01, 0x01]);
+    assert_eq!(bytes::cobs_decode(&venc), Ok(vlong));
+    let vlong: Vec<u8> = (0..1000).map(|i| (i % 7) as u8).collect();
+    assert_eq!(bytes::cobs_decode(&bytes::cobs_encode(&vlong)), Ok(vlong));
+    assert!(bytes::cobs_decode(&[0x03, 0x11]).is_err());
+    assert!(bytes::cobs_decode(&[0x03, 0x11, 0x00]).is_err());
+}
+
+pub fn test_bignum() {
+    let mut vbuf = vec![0x00, 0x00, 0xFF, 0xFF];
+    assert!(!bignum::increment(&mut vbuf));
+    assert_eq!(vbuf, vec![0x00, 0x01, 0x00, 0x00]);
+    let mut vbuf = vec![0x00; 12];
+    assert!(!bignum::add_u64(&mut vbuf, u64::MAX));
+    assert!(!bignum::add_u64(&mut vbuf, u64::MAX));
+    du_log!(info, "TEST:BigNum:{}", hex::hex_from_vu8(&vbuf));
+    assert_eq!(vbuf, vec![0, 0, 0, 1, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE]);
+    let mut vbuf = vec![0xFF; 3];
+    assert!(bignum::increment(&mut vbuf));
+    assert_eq!(vbuf, vec![0x00; 3]);
+    let mut vbuf = vec![0x01, 0x02];
+    assert!(bignum::add_u64(&mut vbuf, 0x10000));
+    assert_eq!(vbuf, vec![0x01, 0x02]);
+    let mut vbuf: Vec<u8> = vec![];
+    assert!(!bignum::add_u64(&mut vbuf, 0));
+    assert!(bignum::increment(&mut vbuf));
+    assert_eq!(bignum::compare(&[0x00, 0x01, 0x00], &[0x01, 0x00]), std::cmp::Ordering::Equal);
+    assert_eq!(bignum::compare(&[0x02], &[0x01, 0x00]), std::cmp::Ordering::Less);
+    assert_eq!(bignum::compare(&[0x01, 0x01], &[0x00, 0x01, 0x00]), std::cmp::Ordering::Greater);
+    assert_eq!(bignum::compare(&[], &[0x00]), std::cmp::Ordering::Equal);
+}
+
+pub fn test_net() {
+    let mac = net::mac_from_str("aa:BB:cc:01:2:ff").unwrap();
+    du_log!(info, "TEST:Net:Mac:{:?}", mac);
+    assert_eq!(mac, [0xAA, 0xBB, 0xCC, 0x01, 0x02, 0xFF]);
+    assert_eq!(net::mac_to_string(&mac), "aa:bb:cc:01:02:ff");
+    assert_eq!(net::mac_from_str("00-11-22-33-44-55"), Ok([0x00, 0x11, 0x22, 0x33, 0x44, 0x55]));
+    assert!(net::mac_from_str("aa:bb:cc:dd:ee").is_err());
+    assert!(net::mac_from_str("aa:bb:cc:dd:ee:ff:00").is_err());
+    assert!(net::mac_from_str("aa:bb:cc:dd:ee:fff").is_err());
+    assert!(net::mac_from_str("aa:bb:cc:dd:ee:gg").is_err());
+    assert!(net::mac_from_str("aa:bb:cc:dd:ee:+f").is_err());
+    assert!(net::mac_from_str("+a:bb:cc:dd:ee:ff").is_err());
+    let ip4 = net::ipv4_from_str("192.168.1.20").unwrap();
+    assert_eq!(ip4, [192, 168, 1, 20]);
+    assert_eq!(net::ipv4_to_string(&ip4), "192.168.1.20");
+    assert!(net::ipv4_from_str("192.168.1.256").is_err());
+    let ip6 = net::ipv6_from_str("2001:db8::1").unwrap();
+    du_log!(info, "TEST:Net:Ipv6:{}", hex::hex_from_vu8(ip6));
+    assert_eq!(ip6[..4], [0x20, 0x01, 0x0d, 0xb8]);
+    assert_eq!(ip6[15], 1);
+    assert_eq!(net::ipv6_to_string(&ip6), "2001:db8::1");
+    assert!(net::ipv6_from_str("2001:db8:::1").is_err());
+}
+
+pub fn test_printable() {
+    let vdata = b"Hi\\there\x00\x7F\xFF\n!".to_vec();
+    let sprint = bytes::to_printable(&vdata);
+    du_log!(info, "TEST:Printable:{}", sprint);
+    assert_eq!(sprint, "Hi\\\\there\\x00\\x7F\\xFF\\x0A!");
+    assert_eq!(bytes::from_printable(&sprint), Ok(vdata));
+    let vall: Vec<u8> = (0..=255u8).collect();
+    assert_eq!(bytes::from_printable(&bytes::to_printable(&vall)), Ok(vall));
+    assert_eq!(bytes::from_printable("ab\\x4a"), Ok(b"abJ".to_vec()));
+    assert!(bytes::from_printable("ab\\").is_err());
+    assert!(bytes::from_printable("ab\\x4").is_err());
+    assert!(bytes::from_printable("ab\\xZZ").is_err());
+    assert!(bytes::from_printable("\\x+a").is_err());
+    assert!(bytes::from_printable("ab\\n").is_err());
+}
+
+pub fn test_flagset() {
+    let vdefs = [("FIN", 0), ("SYN", 1), ("RST", 2), ("PSH", 3), ("ACK", 4), ("URG", 5)];
+    let mut fs = FlagSet::new(&vdefs, 8).unwrap();
+    assert_eq!(fs.to_string(), "0");
+    fs.parse("ACK|SYN").unwrap();
+    du_log!(info, "TEST:FlagSet:{}:0x{:X}", fs, fs.value());
+    assert_eq!(fs.value(), 0x12);
+    assert_eq!(fs.to_string(), "SYN|ACK");
+    assert_eq!(fs.names(), vec!["SYN", "ACK"]);
+    assert_eq!(fs.is_set("ACK"), Ok(true));
+    assert_eq!(fs.is_set("FIN"), Ok(false));
+    fs.clear("SYN").unwrap();
+    fs.set("FIN").unwrap();
+    assert_eq!(fs.to_string(), "FIN|ACK");
+    fs.set_value(0xC1).unwrap();
+    assert_eq!(fs.to_string(), "FIN|0xC0");
+    fs.parse(" RST | 0x80 ").unwrap();
+    assert_eq!(fs.value(), 0x84);
+    fs.parse("").unwrap();
+    assert_eq!(fs.value(), 0);
+    assert!(fs.parse("ACK|ECE").is_err());
+    assert!(fs.parse("ACK||SYN").is_err());
+    assert!(fs.set_value(0x100).is_err());
+    assert!(fs.parse("256").is_err());
+    assert!(fs.parse("0x+1").is_err());
+    assert!(fs.set("XYZ").is_err());
+    assert!(FlagSet::new(&[("A", 8)], 8).is_err());
+    assert!(FlagSet::new(&[("A", 0), ("B", 0)], 8).is_err());
+    assert!(FlagSet::new(&[("A|B", 0)], 8).is_err());
+    let mut fs = FlagSet::new(&[("TOP", 63)], 64).unwrap();
+    fs.set("TOP").unwrap();
+    assert_eq!(fs.value(), 1u64 << 63);
+}
+
+pub fn test_bitreverse() {
+    let mut vdata = vec![0x12, 0xA5, 0x0F];
+    bytes::swap_nibbles(&mut vdata);
+    assert_eq!(vdata, vec![0x21, 0x5A, 0xF0]);
+    let mut vdata = vec![0x01, 0x80, 0x0F, 0x35];
+    bytes::reverse_bits_per_byte(&mut vdata);
+    assert_eq!(vdata, vec![0x80, 0x01, 0xF0, 0xAC]);
+    let vorig = vec![0x01, 0x02, 0xC0];
+    let mut vdata = vorig.clone();
+    bytes::reverse_bits(&mut vdata);
+    du_log!(info, "TEST:BitReverse:{}:{}", hex::hex_from_vu8(&vorig), hex::hex_from_vu8(&vdata));
+    assert_eq!(vdata, vec![0x03, 0x40, 0x80]);
+    bytes::reverse_bits(&mut vdata);
+    assert_eq!(vdata, vorig);
+}
+
+pub fn test_morton() {
+    assert_eq!(integer::interleave2(0b11, 0b00), 0b0101);
+    assert_eq!(integer::interleave2(0b00, 0b11), 0b1010);
+    assert_eq!(integer::interleave2(5, 9), 0b10010011);
+    assert_eq!(integer::interleave2(u32::MAX, 0), 0x5555555555555555);
+    assert_eq!(integer::interleave2(u32::MAX, u32::MAX), u64::MAX);
+    for (x, y) in [(0, 0), (1, 2), (0x12345678, 0x9ABCDEF0), (u32::MAX, 7)] {
+        let key = integer::interleave2(x, y);
+        du_log!(info, "TEST:Morton:{}:{}:0x{:X}", x, y, key);
+        assert_eq!(integer::deinterleave2(key), (x, y));
+    }
+    assert_eq!(integer::interleave3(1, 1, 1), 0b111);
+    assert_eq!(integer::interleave3(2, 0, 1), 0b1100);
+    assert_eq!(integer::interleave3(u32::MAX, u32::MAX, u32::MAX), (1u128 << 96) - 1);
+    for (x, y, z) in [(0, 0, 0), (3, 5, 7), (0xDEADBEEF, 0x12345678, u32::MAX)] {
+        assert_eq!(integer::deinterleave3(integer::interleave3(x, y, z)), (x, y, z));
+    }
+}
+
+pub fn test_hammingdistance() {
+    let hd = bytes::hamming_distance(&[0x00, 0xFF, 0x0F], &[0x01, 0xFF, 0xF0]);
+    du_log!(info, "TEST:HammingDistance:{:?}", hd);
+    assert_eq!(hd, Ok(9));
+    assert_eq!(bytes::hamming_distance(&[], &[]), Ok(0));
+    assert!(bytes::hamming_distance(&[0x00], &[0x00, 0x00]).is_err());
+    assert_eq!(bytes::hamming_distance_padded(&[0x01], &[0x00, 0xFF, 0x03], 0x00), 11);
+    assert_eq!(bytes::hamming_distance_padded(&[0x00, 0xFF, 0x03], &[0x01], 0xFF), 7);
+    assert_eq!(bytes::hamming_distance_padded(b"abc", b"abc", 0), 0);
+}
+
+pub fn test_ecc() {
+    assert_eq!(ecc::hamming74_encode(0b1011), 0b1010101);
+    for nibble in 0..16u8 {
+        let code = ecc::hamming74_encode(nibble);
+        assert_eq!(ecc::hamming74_decode(code), (nibble, EccStatus::Clean));
+        for b in 0..7 {
+            assert_eq!(ecc::hamming74_decode(code ^ (1 << b)), (nibble, EccStatus::Corrected));
+        }
+        let code = ecc::hamming84_encode(nibble);
+        assert_eq!(code.count_ones() % 2, 0);
+        assert_eq!(ecc::hamming84_decode(code), (nibble, EccStatus::Clean));
+        for b1 in 0..8 {
+            assert_eq!(ecc::hamming84_decode(code ^ (1 << b1)), (nibble, EccStatus::Corrected));
+            for b2 in (b1+1)..8 {
+                assert_eq!(ecc::hamming84_decode(code ^ (1 << b1) ^ (1 << b2)).1, EccStatus::Uncorrectable);
+            }
+        }
+    }
+    let vdata = b"SECDED".to_vec();
+    let mut vcode = ecc::secded_encode(&vdata);
+    du_log!(info, "TEST:Ecc:{}:{}", hex::hex_from_vu8(&vdata), hex::hex_from_vu8(&vcode));
+    assert_eq!(vcode.len(), 12);
+    assert_eq!(ecc::secded_decode(&vcode), Ok((vdata.clone(), 0)));
+    vcode[0] ^= 0x04;
+    vcode[7] ^= 0x80;
+    assert_eq!(ecc::secded_decode(&vcode), Ok((vdata.clone(), 2)));
+    vcode[3] ^= 0x21;
+    assert!(ecc::secded_decode(&vcode).is_err());
+    assert!(ecc::secded_decode(&vcode[..3]).is_err());
+}
+
+pub fn test_entropy() {
+    assert_eq!(bytes::entropy(&[]), 0.0);
+    assert_eq!(bytes::entropy(&[7; 100]), 0.0);
+    assert!((bytes::entropy(b"abab") - 1.0).abs() < 1e-9);
+    let vall: Vec<u8> = (0..=255u8).collect();
+    let ent = bytes::entropy(&vall);
+    du_log!(info, "TEST:Entropy:All:{}", ent);
+    assert!((ent - 8.0).abs() < 1e-9);
+    let mut vdata = vec![0u8; 64];
+    vdata.extend((0..64).map(|i| (i * 37 + 11) as u8));
+    vdata.extend(vec![0u8; 64]);
+    let vent = bytes::sw_entropy(&vdata, 32);
+    du_log!(info, "TEST:Entropy:Sliding:{:?}", &vent[60..70]);
+    assert_eq!(vent.len(), vdata.len()-32+1);
+    for (i, e) in vent.iter().enumerate() {
+        assert!((e - bytes::entropy(&vdata[i..i+32])).abs() < 1e-9);
+    }
+    assert_eq!(vent[0], 0.0);
+    assert!((vent[64] - 5.0).abs() < 1e-9);
+    assert!(bytes::sw_entropy(&vdata, 0).is_empty());
+    assert!(bytes::sw_entropy(&vdata[..10], 11).is_empty());
+}
+
+pub fn test_bytehistogram() {
+    let hist = bytes::byte_histogram(b"hello");
+    assert_eq!(hist[b'l' as usize], 2);
+    assert_eq!(hist[b'h' as usize], 1);
+    assert_eq!(hist.iter().sum::<u64>(), 5);
+    let vuniform: Vec<u8> = (0..256*16).map(|i| i as u8).collect();
+    assert_eq!(bytes::chi_square_uniformity(&vuniform), 0.0);
+    let mut vrand = vec![0u8; 65536];
+    seeded_rng(0x12345678).fill_bytes(&mut vrand);
+    let chirand = bytes::chi_square_uniformity(&vrand);
+    let chitext = bytes::chi_square_uniformity(&b"The quick brown fox jumps over the lazy dog. ".repeat(100));
+    du_log!(info, "TEST:ByteHistogram:ChiSquare:Rand:{}:Text:{}", chirand, chitext);
+    assert!(chirand > 150.0 && chirand < 400.0);
+    assert!(chitext > 10000.0);
+    assert_eq!(bytes::chi_square_uniformity(&[]), 0.0);
+}
+
+pub fn test_levenshtein() {
+    let vtests = [("kitten", "sitting", 3), ("", "abc", 3), ("abc", "", 3), ("flaw", "lawn", 2), ("same", "same", 0), ("naïve", "naive", 1)];
+    for (a, b, d) in vtests {
+        let dist = text::levenshtein(a, b, usize::MAX);
+        du_log!(info, "TEST:Levenshtein:{}:{}:{}", a, b, dist);
+        assert_eq!(dist, d);
+    }
+    assert_eq!(text::levenshtein("kitten", "sitting", 3), 3);
+    assert_eq!(text::levenshtein("kitten", "sitting", 2), 3);
+    assert_eq!(text::levenshtein("kitten", "sitting", 0), 1);
+    assert_eq!(text::levenshtein("a", "abcdef", 2), 3);
+    assert_eq!(text::levenshtein_slice("naïve".as_bytes(), b"naive", usize::MAX), 2);
+    assert_eq!(text::levenshtein_slice(&[0u8, 1, 2, 3], &[0u8, 2, 3, 4], usize::MAX), 2);
+    assert_eq!(text::levenshtein_slice(&[1, 2, 3], &[1, 2, 3], 0), 0);
+}
+
+pub fn test_seededrng() {
+    let mut rng1 = seeded_rng(42);
+    let mut rng2 = seeded_rng(42);
+    let mut rng3 = seeded_rng(43);
+    let (a, b, c) = (rng1.next_u64(), rng2.next_u64(), rng3.next_u64());
+    du_log!(info, "TEST:SeededRng:{:X}:{:X}:{:X}", a, b, c);
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    let mut vbuf1 = vec![0u8; 13];
+    let mut vbuf2 = vec![0u8; 13];
+    rng1.fill_bytes(&mut vbuf1);
+    rng2.fill_bytes(&mut vbuf2);
+    assert_eq!(vbuf1, vbuf2);
+    assert!(vbuf1.iter().any(|&x| x != 0));
+    assert_eq!(gen_bytes(&mut seeded_rng(7), 32), gen_bytes(&mut seeded_rng(7), 32));
+    assert_eq!(gen_variant(&mut seeded_rng(9)).get_bufvu8(), gen_variant(&mut seeded_rng(9)).get_bufvu8());
+    assert!((0..100).all(|_| rng3.below(5) < 5));
+    // Reference values of SplitMix64 with seed 0
+    let mut rng0 = seeded_rng(0);
+    assert_eq!(rng0.next_u64(), 0xE220A8397B1DCDAF);
+    assert_eq!(rng0.next_u64(), 0x6E789E6AA1B965F4);
+}
+
+pub fn test_panicfree() {
+    assert_eq!(integer::try_intvalue::<isize>(" 0x10 "), Ok(16));
+    assert_eq!(integer::try_intvalue::<u8>("255"), Ok(255u8));
+    assert_eq!(integer::try_intvalue::<i8>("-128"), Ok(-128i8));
+    let err = integer::try_intvalue::<u8>("256").unwrap_err();
+    du_log!(info, "TEST:PanicFree:{}", err);
+    assert!(matches!(err, DataUtilsError::OutOfRange { .. }));
+    assert!(matches!(integer::try_intvalue::<isize>("12a"), Err(DataUtilsError::ParseInt { .. })));
+    assert!(matches!(integer::try_intvalue::<isize>("0x-10"), Err(DataUtilsError::ParseInt { .. })));
+    assert!(integer::try_intvalue::<isize>("0x+5").is_err());
+    assert!("0x+5".parse::<integer::U8X>().is_err());
+    assert!(Variant::from_literal("0x-1").is_err());
+    assert_eq!(integer::try_intvalue::<isize>("-16"), Ok(-16));
+    assert!(matches!(integer::U8X::try_from_isize(-1), Err(DataUtilsError::OutOfRange { .. })));
+    assert_eq!(integer::U8X::try_from_isize(200).unwrap().0, 200);
+    assert!(matches!(Variant::from_literal(" 123 "), Ok(Variant::IntValue(123))));
+    assert!(matches!(Variant::from_literal("\"hi\""), Ok(Variant::StrValue(_))));
+    assert!(matches!(Variant::from_literal("$0x0102"), Ok(Variant::BufValue(_))));
+    assert!(matches!(Variant::from_literal("$0x012"), Err(DataUtilsError::ParseHex { .. })));
+    assert!(matches!(Variant::from_literal("\""), Err(DataUtilsError::ParseInt { .. })));
+    assert!(Variant::from_literal("abc").is_err());
+    let v = Variant::BufValue(vec![1, 2]);
+    assert_eq!(v.try_get_byteelement(1), Ok(2));
+    assert_eq!(v.try_get_byteelement(2), Err(DataUtilsError::IndexOutOfBounds { index: 2, len: 2 }));
+    assert!(hex::vu8_from_hex("aé").is_err());
+    let serr: String = DataUtilsError::Invalid("oops".to_string()).into();
+    assert_eq!(serr, "ERRR:DU:Invalid:oops");
+}
+
+pub fn test_slicefirst() {
+    let abuf = [0xDEu8, 0xAD, 0xBE, 0xEF];
+    assert_eq!(hex::hex_from_vu8(abuf), "DEADBEEF");
+    assert_eq!(hex::hex_from_vu8(&abuf[1..3]), "ADBE");
+    assert_eq!(hex::hex_from_vu8(vec![0xDEu8, 0xAD, 0xBE, 0xEF]), "DEADBEEF");
+    let af = [1.0f32, 2.0, 3.0, 6.0];
+    du_log!(info, "TEST:SliceFirst:{}:{}", sigpro::vec_sum(&af), sigpro::vec_avg(&af[..3]));
+    assert_eq!(sigpro::vec_sum(&af), 12.0);
+    assert_eq!(sigpro::vec_avg(&af[..3]), 2.0);
+    let axf = [(0u8, 0.0f32), (1, 3.0), (2, 6.0)];
+    assert_vec_close(&sigpro::sw_average_f_of_xf(&axf, 3).iter().map(|x| x.1).collect::<Vec<f32>>(), &[0.0, 3.0, 6.0], 1e-5);
+    assert_eq!(sigpro::sw_crosscorr_f_of_xf(&axf, &[0.5, 0.0, 0.5])[1], (1, 3.0));
+    let mut v = Variant::IntValue(0);
+    v.set_bufvu8(&abuf);
+    assert_eq!(v.get_bufvu8(), abuf.to_vec());
+}
+
+pub fn test_prelude() {
+    use crate::prelude::*;
+    let vbuf = Vec::<u8>::from_hex("00ff10").unwrap();
+    du_log!(info, "TEST:Prelude:{}", vbuf.to_hex());
+    assert_eq!(vbuf.to_hex(), "00FF10");
+    assert_eq!([0xABu8, 0xCD].to_hex(), "ABCD");
+    assert_eq!(vbuf[1..].to_hex(), "FF10");
+    assert!(Vec::<u8>::from_hex("0").is_err());
+    let mut bc = ByteCursor::new();
+    bc.put_u16_be(0x1234);
+    assert_eq!(bc.as_slice().get_u16_be(0), Ok(0x1234));
+    let v = Variant::from_literal("$0x0102").unwrap();
+    assert!(matches!(v.get_type(), VDataType::Buffer));
+    let mut rng = SplitMix64::new(1);
+    assert!(rng.below(10) < 10);
+    let mut tk = Tokenizer::new("a \"b c\"");
+    assert_eq!(tk.next().map(|t| t.kind), Some(TokenKind::Word));
+}
+
+#[cfg(feature = "log")]
+struct CaptureLogger {
+    vmsgs: std::sync::Mutex<Vec<String>>,
+}
+
+#[cfg(feature = "log")]
+impl log::Log for CaptureLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        self.vmsgs.lock().unwrap().push(format!("{}:{}", record.level(), record.args()));
+    }
+
+    fn flush(&self) {}
+}
+
+#[cfg(feature = "log")]
+static CAPTURE_LOGGER: CaptureLogger = CaptureLogger { vmsgs: std::sync::Mutex::new(Vec::new()) };
+
+pub fn test_logfacade() {
+    #[cfg(feature = "log")]
+    {
+        if log::set_logger(&CAPTURE_LOGGER).is_ok() {
+            log::set_max_level(log::LevelFilter::Trace);
+        }
+    }
+    pack::calcsize("<u16 4s").unwrap();
+    du_log!(info, "TEST:LogFacade:Info");
+    #[cfg(feature = "log")]
+    {
+        let vmsgs = CAPTURE_LOGGER.vmsgs.lock().unwrap();
+        assert!(vmsgs.iter().any(|m| m == "INFO:TEST:LogFacade:Info"));
+        assert!(vmsgs.iter().any(|m| m.starts_with("TRACE:DBUG:DU:Pack:ParseFormat:[<u16 4s]")));
+    }
+}
+
+/// Run the fuzz entries over a seeded random corpus, along with some known tricky inputs
+#[cfg(feature = "fuzzing")]
+pub fn test_fuzzentries() {
+    use crate::fuzzing;
+    let vtricky: Vec<&[u8]> = vec![b"", b"\"", b"\\", b"$0x", b"$0x0", b"0x", b"-", b"\"abc", b"(a \"b)", b"a\\\nb", "é€".as_bytes(), b"\xFF\xFE", b"99999999999999999999999", b"+F", b"$0x+F"];
+    let mut rng = seeded_rng(0xF022);
+    let mut vcorpus: Vec<Vec<u8>> = vtricky.iter().map(|x| x.to_vec()).collect();
+    for _ in 0..500 {
+        vcorpus.push(gen_bytes(&mut rng, 24));
+        vcorpus.push(gen_string(&mut rng, 24).into_bytes());
+    }
+    for data in &vcorpus {
+        fuzzing::fuzz_entry_hex(data);
+        fuzzing::fuzz_entry_token(data);
+        fuzzing::fuzz_entry_variant(data);
+    }
+    du_log!(info, "TEST:FuzzEntries:{} inputs", vcorpus.len());
+}
+
+pub fn test_constgeneric() {
+    let amac: [u8; 6] = hex::decode_array("0011aaBBccFF").unwrap();
+    assert_eq!(amac, [0x00, 0x11, 0xAA, 0xBB, 0xCC, 0xFF]);
+    assert!(hex::decode_array::<2>("001122").is_err());
+    assert!(hex::decode_array::<2>("00zz").is_err());
+    assert!(hex::decode_array::<1>("é").is_err());
+    assert_eq!(hex::decode_array::<0>(""), Ok([]));
+    let vbuf = pack::pack("<u16 2x >i16 u8", &[Variant::IntValue(0x1234), Variant::IntValue(-2), Variant::IntValue(7)]).unwrap();
+    let afields: [i64; 3] = pack::unpack_into("<u16 2x >i16 u8", &vbuf).unwrap();
+    du_log!(info, "TEST:ConstGeneric:{}:{:?}", hex::hex_from_vu8(&vbuf), afields);
+    assert_eq!(afields, [0x1234, -2, 7]);
+    assert!(pack::unpack_into::<2>("<u16 2x >i16 u8", &vbuf).is_err());
+    assert!(pack::unpack_into::<4>("<u16 2x >i16 u8", &vbuf).is_err());
+    assert!(pack::unpack_into::<1>("u8 2s", &vbuf).is_err());
+    assert!(pack::unpack_into::<1>("u64", &vbuf).is_err());
+    let mut ma = sigpro::MovingAverageConst::<3>::new();
+    assert_eq!(ma.average(), None);
+    let vout: Vec<f32> = [3.0, 6.0, 9.0, 12.0, 0.0].iter().map(|&x| ma.push(x)).collect();
+    assert_vec_close(&vout, &[3.0, 4.5, 6.0, 9.0, 7.0], 1e-5);
+    assert_eq!(ma.average(), Some(7.0));
+    ma.reset();
+    assert_eq!(ma.push(1.0), 1.0);
+    let mut ma1 = sigpro::MovingAverageConst::<1>::default();
+    assert_eq!(ma1.push(5.0), 5.0);
+    assert_eq!(ma1.push(-5.0), -5.0);
+}
+
+pub fn test_slicenumext() {
+    use crate::prelude::SliceNumExt;
+    let vf = vec![3.0f32, 1.0, 4.0, 1.0, 5.0, 9.0];
+    du_log!(info, "TEST:SliceNumExt:{:?}:avg:{:?}:median:{:?}:minmax:{:?}:rms:{:?}", vf, vf.avg(), vf.median(), vf.minmax(), vf.rms());
+    assert_eq!(vf.avg(), Some(23.0/6.0));
+    assert_eq!(vf.median(), Some(3.5));
+    assert_eq!(vf.minmax(), Some((1.0, 9.0)));
+    assert!((vf.rms().unwrap() - (133.0f64/6.0).sqrt()).abs() < 1e-9);
+    let ai = [7i32, -2, 4];
+    assert_eq!(ai.avg(), Some(3.0));
+    assert_eq!(vec![1i32; 65536].avg(), Some(1.0));
+    assert_eq!(vec![1.5f32; 70000].avg(), Some(1.5));
+    assert_eq!(ai.median(), Some(4.0));
+    assert_eq!(ai[..2].minmax(), Some((-2, 7)));
+    assert_eq!([3.0f64, 4.0][..].rms(), Some((12.5f64).sqrt()));
+    let ve: Vec<u16> = Vec::new();
+    assert_eq!(ve.avg(), None);
+    assert_eq!(ve.median(), None);
+    assert_eq!(ve.minmax(), None);
+    assert_eq!(ve.rms(), None);
+    // the original slice is left untouched by median
+    assert_eq!(vf, vec![3.0f32, 1.0, 4.0, 1.0, 5.0, 9.0]);
+}
+
+pub fn test_variantformatint() {
+    let ivar = Variant::IntValue(255);
+    du_log!(info, "TEST:VariantFormatInt:{}:{}:{}", ivar.format_int(16, 6, '0').unwrap(), ivar.format_int(8, 0, '0').unwrap(), ivar.format_int(2, 12, ' ').unwrap());
+    assert_eq!(ivar.format_int(16, 6, '0').unwrap(), "0x00FF");
+    assert_eq!(ivar.format_int(16, 0, '0').unwrap(), "0xFF");
+    assert_eq!(ivar.format_int(8, 0, '0').unwrap(), "0o377");
+    assert_eq!(ivar.format_int(2, 12, ' ').unwrap(), "  0b11111111");
+    assert_eq!(ivar.format_int(10, 5, '0').unwrap(), "00255");
+    assert_eq!(ivar.format_int(36, 0, '0').unwrap(), "73");
+    assert_eq!(Variant::IntValue(0).format_int(16, 4, '0').unwrap(), "0x00");
+    assert_eq!(Variant::IntValue(-10).format_int(16, 6, '0').unwrap(), "-0x00A");
+    assert_eq!(Variant::IntValue(-10).format_int(10, 5, '*').unwrap(), "**-10");
+    assert_eq!(Variant::IntValue(isize::MIN).format_int(16, 0, '0').unwrap(), format!("-0x{:X}", isize::MIN.unsigned_abs()));
+    assert_eq!(Variant::from("\"0x1f\"").format_int(16, 0, '0').unwrap(), "0x1F");
+    assert!(ivar.format_int(1, 0, '0').is_err());
+    assert!(ivar.format_int(37, 0, '0').is_err());
+    assert!(Variant::from("\"abc\"").format_int(16, 0, '0').is_err());
+}
+
+pub fn test_variantbuilder() {
+    use crate::variant::{VariantBuilder, VDataType};
+    let v = VariantBuilder::map()
+        .str("name", "x")
+        .int("count", 3)
+        .list("vals", vec![Variant::IntValue(1), Variant::IntValue(2)])
+        .put("inner", VariantBuilder::list().str("a").buf(&[0xAB]).push(VariantBuilder::map().int("deep", -1)))
+        .int("count", 4)
+        .build();
+    du_log!(info, "TEST:VariantBuilder:{}", v);
+    assert!(matches!(v.get_type(), VDataType::Map));
+    assert_eq!(v.to_string(), "{count: 4, inner: [a, AB, {deep: -1}], name: x, vals: [1, 2]}");
+    assert_eq!(v.get_mapvalue("name").unwrap().get_string(), "x");
+    assert_eq!(v.get_mapvalue("count").unwrap().get_isize(), Ok(4));
+    assert!(v.get_mapvalue("missing").is_none());
+    let inner = v.get_mapvalue("inner").unwrap();
+    assert!(matches!(inner.get_type(), VDataType::List));
+    assert_eq!(inner.get_list().unwrap().len(), 3);
+    assert_eq!(inner.get_arrayelement(2).unwrap().get_mapvalue("deep").unwrap().get_isize(), Ok(-1));
+    assert!(inner.get_arrayelement(3).is_err());
+    assert_eq!(v.get_arrayelement(2).unwrap().get_string(), "x");
+    assert!(v.get_isize().is_err());
+    assert!(v.get_list().is_none());
+    let vl = Variant::from(vec![Variant::StrValue("ab".to_string()), Variant::BufValue(vec![1, 2])]);
+    assert_buf_eq_hex(&vl.get_bufvu8(), "61620102");
+    assert_eq!(VariantBuilder::list().build().to_string(), "[]");
+    assert_eq!(VariantBuilder::map().build().to_string(), "{}");
+}
+
+pub fn test_variantparsestream() {
+    let sdata = "123\n\"a string\"\n\n  $0x0a0B \n0x10\nbad\n__TIME__STAMP__";
+    let vres: Vec<Result<Variant, DataUtilsError>> = variant::parse_stream(std::io::Cursor::new(sdata), b'\n').collect();
+    du_log!(info, "TEST:VariantParseStream:{:?}", vres);
+    assert_eq!(vres.len(), 6);
+    assert!(matches!(vres[0], Ok(Variant::IntValue(123))));
+    assert_eq!(vres[1].as_ref().unwrap().get_string(), "a string");
+    assert_buf_eq_hex(&vres[2].as_ref().unwrap().get_bufvu8(), "0A0B");
+    assert!(matches!(vres[3], Ok(Variant::IntValue(16))));
+    assert!(matches!(vres[4], Err(DataUtilsError::ParseInt { .. })));
+    assert!(matches!(vres[5], Ok(Variant::XTimeStamp)));
+    let vints: Vec<isize> = variant::parse_stream(&b"1;2; 3;"[..], b';').map(|v| v.unwrap().get_isize().unwrap()).collect();
+    assert_eq!(vints, vec![1, 2, 3]);
+    let mut vs = variant::parse_stream(&b"\xff\xfe\n5"[..], b'\n');
+    assert!(matches!(vs.next(), Some(Err(DataUtilsError::Invalid(_)))));
+    assert!(matches!(vs.next(), Some(Ok(Variant::IntValue(5)))));
+    assert!(vs.next().is_none());
+}
+
+pub fn test_compileliteral() {
+    use crate::variant::{compile_literal, CompiledLiteral, VDataType};
+    let vtokens = [" 0x10 ", "\"hi there\"", "$0x0102", "__TIME__STAMP__", "count_2", "_tmp", "-5"];
+    for token in vtokens {
+        du_log!(info, "TEST:CompileLiteral:[{}]:{:?}", token, compile_literal(token, VDataType::Unknown));
+    }
+    assert!(matches!(compile_literal(" 0x10 ", VDataType::Unknown), Ok(CompiledLiteral::Value(Variant::IntValue(16)))));
+    assert!(matches!(compile_literal("-5", VDataType::Unknown), Ok(CompiledLiteral::Value(Variant::IntValue(-5)))));
+    assert!(matches!(compile_literal("\"hi there\"", VDataType::String), Ok(CompiledLiteral::Value(Variant::StrValue(s))) if s == "hi there"));
+    assert!(matches!(compile_literal("$0x0102", VDataType::Buffer), Ok(CompiledLiteral::Value(Variant::BufValue(b))) if b == [1, 2]));
+    assert!(matches!(compile_literal("__TIME__STAMP__", VDataType::Integer), Ok(CompiledLiteral::Value(Variant::XTimeStamp))));
+    assert!(matches!(compile_literal("count_2", VDataType::Integer), Ok(CompiledLiteral::Identifier(s)) if s == "count_2"));
+    assert!(matches!(compile_literal("_tmp", VDataType::Unknown), Ok(CompiledLiteral::Identifier(_))));
+    // conversion to the expected type
+    assert!(matches!(compile_literal("\"0x20\"", VDataType::Integer), Ok(CompiledLiteral::Value(Variant::IntValue(32)))));
+    assert!(matches!(compile_literal("42", VDataType::String), Ok(CompiledLiteral::Value(Variant::StrValue(s))) if s == "42"));
+    assert!(matches!(compile_literal("\"ab\"", VDataType::Buffer), Ok(CompiledLiteral::Value(Variant::BufValue(b))) if b == b"ab"));
+    // errors
+    assert!(compile_literal("\"abc\"", VDataType::Integer).is_err());
+    assert!(compile_literal("__UNKNOWN__", VDataType::Unknown).is_err());
+    assert!(compile_literal("$0x012", VDataType::Unknown).is_err());
+    assert!(compile_literal("9abc", VDataType::Unknown).is_err());
+    assert!(compile_literal("12", VDataType::List).is_err());
+}
+
+pub fn test_evalexpr() {
+    use crate::variant::eval_expr;
+    let resolver = |name: &str| match name {
+        "len" => Some(Variant::IntValue(10)),
+        "name" => Some(Variant::StrValue("abc".to_string())),
+        "snum" => Some(Variant::StrValue("0x20".to_string())),
+        _ => None,
+    };
+    let eval = |expr: &str| eval_expr(expr, resolver);
+    du_log!(info, "TEST:EvalExpr:{:?}", eval("len + 2 * 8"));
+    assert!(matches!(eval("len + 2 * 8"), Ok(Variant::IntValue(26))));
+    assert!(matches!(eval("(len + 2) * 8"), Ok(Variant::IntValue(96))));
+    assert!(matches!(eval("len-3-2"), Ok(Variant::IntValue(5))));
+    assert!(matches!(eval("-len % 3"), Ok(Variant::IntValue(-1))));
+    assert!(matches!(eval("--0x10 / 3"), Ok(Variant::IntValue(5))));
+    assert!(matches!(eval("snum * 2"), Ok(Variant::IntValue(64))));
+    assert!(matches!(eval("len * 2 >= 20"), Ok(Variant::IntValue(1))));
+    assert!(matches!(eval("len < 3 + 4"), Ok(Variant::IntValue(0))));
+    assert!(matches!(eval("name == \"abc\""), Ok(Variant::IntValue(1))));
+    assert!(matches!(eval("name != \"abc\""), Ok(Variant::IntValue(0))));
+    assert!(matches!(eval("$0x0102 == $0x0102"), Ok(Variant::IntValue(1))));
+    assert!(matches!(eval("name + \"-\" + len"), Ok(Variant::StrValue(s)) if s == "abc-10"));
+    assert!(matches!(eval("(1 < 2) + 1"), Ok(Variant::IntValue(2))));
+    // errors
+    assert!(eval("len / 0").is_err());
+    assert!(eval("unknown + 1").is_err());
+    assert!(eval("(len + 2").is_err());
+    assert!(eval("len + ").is_err());
+    assert!(eval("len 2").is_err());
+    assert!(eval("len # 2").is_err());
+    assert!(eval("\"abc").is_err());
+    assert!(eval("name * 2").is_err());
+    assert!(eval("").is_err());
+    assert!(eval(&format!("{} + 1", isize::MAX)).is_err());
+    // deep nesting errors out, instead of overflowing the stack
+    assert!(eval(&format!("{}1", "-".repeat(200000))).is_err());
+    assert!(eval(&format!("{}1{}", "(".repeat(200000), ")".repeat(200000))).is_err());
+    assert!(matches!(eval(&format!("{}1", "-".repeat(256))), Ok(Variant::IntValue(1))));
+    assert!(matches!(eval(&format!("{}1{}", "(".repeat(200), ")".repeat(200))), Ok(Variant::IntValue(1))));
+}
+
+pub fn test_grid2d() {
+    use sigpro::grid::{moving_avg_2d, moving_avg_2d_rows, EdgeMode};
+    let vgrid = vec![
+        1.0, 2.0, 3.0, 4.0,
+        5.0, 6.0, 7.0, 8.0,
+        9.0, 10.0, 11.0, 12.0,
+    ];
+    let vkeep = moving_avg_2d(&vgrid, 4, 3, 3, EdgeMode::Keep).unwrap();
+    du_log!(info, "TEST:Grid2D:Keep:{:?}", vkeep);
+    assert_vec_close(&vkeep, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0], 1e-5);
+    let vshrink = moving_avg_2d(&vgrid, 4, 3, 3, EdgeMode::Shrink).unwrap();
+    du_log!(info, "TEST:Grid2D:Shrink:{:?}", vshrink);
+    assert_vec_close(&vshrink[..4], &[3.5, 4.0, 5.0, 5.5], 1e-5);
+    assert_vec_close(&vshrink[4..8], &[5.5, 6.0, 7.0, 7.5], 1e-5);
+    let vclamp = moving_avg_2d(&vgrid, 4, 3, 1, EdgeMode::Clamp).unwrap();
+    assert_vec_close(&vclamp[..4], &[4.0/3.0, 2.0, 3.0, 11.0/3.0], 1e-5);
+    // 1x1 window leaves the grid as is
+    assert_vec_close(&moving_avg_2d(&vgrid, 4, 1, 1, EdgeMode::Shrink).unwrap(), &vgrid, 1e-6);
+    let vrows = vec![vec![0.0, 0.0, 0.0], vec![0.0, 9.0, 0.0], vec![0.0, 0.0, 0.0]];
+    let vsmooth = moving_avg_2d_rows(&vrows, 3, 3, EdgeMode::Keep).unwrap();
+    assert_eq!(vsmooth[1], vec![0.0, 1.0, 0.0]);
+    assert!(moving_avg_2d_rows(&[vec![1.0], vec![1.0, 2.0]], 3, 3, EdgeMode::Keep).is_err());
+    assert!(moving_avg_2d_rows(&[], 3, 3, EdgeMode::Keep).unwrap().is_empty());
+    assert!(moving_avg_2d(&vgrid, 5, 3, 3, EdgeMode::Keep).is_err());
+    assert!(moving_avg_2d(&vgrid, 4, 0, 3, EdgeMode::Keep).is_err());
+}
+
+pub fn test_complex() {
+    use sigpro::complex::{self, Complex32};
+    use core::f32::consts::PI;
+    let vc = vec![Complex32::new(3.0, 4.0), Complex32::from((0.0, -2.0)), Complex32::new(-1.0, 0.0), Complex32::default()];
+    let vmag = complex::magnitude(&vc);
+    let vph = complex::phase(&vc);
+    du_log!(info, "TEST:Complex:Mag:{:?}:Phase:{:?}", vmag, vph);
+    assert_vec_close(&vmag, &[5.0, 2.0, 1.0, 0.0], 1e-6);
+    assert_vec_close(&vph, &[(4.0f32).atan2(3.0), -PI/2.0, PI, 0.0], 1e-6);
+    // a phase ramp of 1 radian per sample, wrapped into -PI to PI
+    let vramp: Vec<f32> = (0..20).map(|i| i as f32).collect();
+    let vwrapped: Vec<f32> = vramp.iter().map(|p| Complex32::new(p.cos(), p.sin()).arg()).collect();
+    assert!(vwrapped.iter().all(|p| p.abs() <= PI));
+    assert_vec_close(&complex::unwrap_phase(&vwrapped), &vramp, 1e-4);
+    // decreasing ramp, with jumps of more than 2*PI
+    let vdown = [0.0, -3.0, -5.5 + 4.0*PI, -8.0 + 2.0*PI];
+    assert_vec_close(&complex::unwrap_phase(&vdown), &[0.0, -3.0, -5.5, -8.0], 1e-4);
+    assert!(complex::unwrap_phase(&[]).is_empty());
+}
+
+pub fn test_detectclipping() {
+    let vdata = [0.1, 1.0, 1.0, 0.5, -1.0, f32::NAN, 0.99, 1.2, 1.0, -1.0, 0.0, 1.0];
+    let report = sigpro::detect_clipping(&vdata, 1.0);
+    du_log!(info, "TEST:DetectClipping:{:?}", report);
+    assert_eq!(report.count, 7);
+    assert_eq!(report.longest_run, 3);
+    assert_eq!(report.ranges, vec![1..3, 4..5, 7..10, 11..12]);
+    let report = sigpro::detect_clipping(&[0.1, -0.2], 1.0);
+    assert_eq!(report, sigpro::ClipReport::default());
+    assert_eq!(sigpro::detect_clipping(&[], 1.0).longest_run, 0);
+}
+
+pub fn test_swcorrelation() {
+    // b follows a for the first half, and then mirrors it
+    let va: Vec<f32> = (0..40).map(|i| (i as f32 * 0.7).sin()).collect();
+    let vb: Vec<f32> = va.iter().enumerate().map(|(i, a)| if i < 20 { 2.0*a + 1.0 } else { -a }).collect();
+    let vcorr = sigpro::sw_correlation(&va, &vb, 5);
+    du_log!(info, "TEST:SwCorrelation:{:?}", vcorr);
+    assert_eq!(vcorr.len(), 40);
+    assert_vec_close(&vcorr[..18], &[1.0; 18], 1e-4);
+    assert_vec_close(&vcorr[22..], &[-1.0; 18], 1e-4);
+    // only the overlapping part is used
+    assert_eq!(sigpro::sw_correlation(&va, &vb[..10], 4).len(), 10);
+    // constant series has undefined correlation
+    assert!(sigpro::sw_correlation(&[1.0, 1.0, 1.0], &[1.0, 2.0, 3.0], 3)[1].is_nan());
+    assert!(sigpro::sw_correlation(&[], &[], 3).is_empty());
+}
+
+pub fn test_fitexpdecay() {
+    let vdata: Vec<(f64, f32)> = (0..50).map(|i| { let x = i as f64 * 0.1; (x, (3.0*(-x/1.5).exp() + 0.5) as f32) }).collect();
+    let fit = sigpro::fit_exp_decay(&vdata).unwrap();
+    du_log!(info, "TEST:FitExpDecay:{:?}", fit);
+    assert!((fit.amplitude - 3.0).abs() < 1e-2);
+    assert!((fit.tau - 1.5).abs() < 1e-2);
+    assert!((fit.offset - 0.5).abs() < 1e-2);
+    // a rising step response, ie negative amplitude
+    let vrise: Vec<(f64, f32)> = (0..60).map(|i| { let x = i as f64; (x, (10.0 - 8.0*(-x/12.0).exp()) as f32) }).collect();
+    let fit = sigpro::fit_exp_decay(&vrise).unwrap();
+    du_log!(info, "TEST:FitExpDecay:Rise:{:?}", fit);
+    assert!((fit.amplitude + 8.0).abs() < 5e-2);
+    assert!((fit.tau - 12.0).abs() < 0.2);
+    assert!((fit.offset - 10.0).abs() < 5e-2);
+    assert!(sigpro::fit_exp_decay(&vdata[..2]).is_err());
+    assert!(sigpro::fit_exp_decay(&[(0.0, 1.0), (1.0, 1.0), (2.0, 1.0)]).is_err());
+}
+
+pub fn test_onlinefilters() {
+    let vdata: Vec<f32> = (0..30).map(|i| (i as f32 * 0.3).sin() + (i % 3) as f32).collect();
+    // MovingAverage matches the fully computed part of sw_average
+    let mut ma = sigpro::MovingAverage::new(5);
+    let vma: Vec<Option<f32>> = vdata.iter().map(|d| ma.push(*d)).collect();
+    du_log!(info, "TEST:OnlineFilters:MovingAverage:{:?}", vma);
+    assert!(vma[..4].iter().all(|v| v.is_none()));
+    let vmaf: Vec<f32> = vma[4..].iter().map(|v| v.unwrap()).collect();
+    assert_vec_close(&vmaf, &sigpro::sw_average(&vdata, 5)[2..28], 1e-5);
+    ma.reset();
+    assert_eq!(ma.push(1.0), None);
+    assert_eq!(sigpro::MovingAverage::new(0).push(3.0), Some(3.0));
+    // FirFilter matches the fully computed part of crosscorr_weighted
+    let vweights = [0.1, 0.2, 0.4, 0.2, 0.1];
+    let mut fir = sigpro::FirFilter::new(&vweights).unwrap();
+    let vfir: Vec<f32> = vdata.iter().filter_map(|d| fir.push(*d)).collect();
+    du_log!(info, "TEST:OnlineFilters:FirFilter:{:?}", vfir);
+    assert_eq!(vfir.len(), 26);
+    assert_vec_close(&vfir, &sigpro::crosscorr_weighted(&vdata, &vweights)[2..28], 1e-5);
+    fir.reset();
+    assert_eq!(fir.push(1.0), None);
+    let mut fir = sigpro::FirFilter::new(&[1.0, -1.0]).unwrap();
+    assert_eq!(fir.push(5.0), None);
+    assert_eq!(fir.push(2.0), Some(3.0));
+    assert_eq!(fir.push(7.0), Some(-5.0));
+    assert!(sigpro::FirFilter::new(&[]).is_err());
+}
+
+pub fn test_processchunked() {
+    let vdata: Vec<f32> = (0..1000).map(|i| (i as f32 * 0.05).sin()).collect();
+    let mut ma = sigpro::MovingAverage::new(7);
+    let mut vout = Vec::new();
+    let mut vprogress = Vec::new();
+    let done = sigpro::process_chunked(vdata.iter().copied(), 128, |chunk| {
+        vout.extend(chunk.iter().filter_map(|d| ma.push(*d)));
+        Ok(())
+    }, |done, total| { vprogress.push((done, total)); true }).unwrap();
+    du_log!(info, "TEST:ProcessChunked:{}:{:?}", done, vprogress);
+    assert_eq!(done, 1000);
+    assert_eq!(vprogress.len(), 8);
+    assert_eq!(vprogress[0], (128, 1000));
+    assert_eq!(vprogress[7], (1000, 1000));
+    assert_vec_close(&vout, &sigpro::sw_average(&vdata, 7)[3..997], 1e-5);
+    // cancellation and unknown total
+    let mut calls = 0;
+    let done = sigpro::process_chunked(vdata.iter().copied().filter(|d| *d > -2.0), 100, |_| Ok(()), |done, total| {
+        assert_eq!(total, 0);
+        calls += 1;
+        done < 300
+    }).unwrap();
+    assert_eq!((done, calls), (300, 3));
+    // errors from op are returned
+    let res = sigpro::process_chunked(vdata.iter().copied(), 100, |chunk| if chunk[0] > 0.5 { Err("too big".to_string()) } else { Ok(()) }, |_, _| true);
+    assert_eq!(res, Err("too big".to_string()));
+    assert!(sigpro::process_chunked(vdata.iter().copied(), 0, |_| Ok(()), |_, _| true).is_err());
+    assert_eq!(sigpro::process_chunked(Vec::new(), 10, |_| Ok(()), |_, _| true), Ok(0));
+}
+
+pub fn test_renderascii() {
+    let vdata: Vec<f32> = (0..80).map(|i| (i as f32 * 0.1).sin()).collect();
+    let sspark = sigpro::render_sparkline(&vdata, 40);
+    du_log!(info, "TEST:RenderAscii:Sparkline:{}", sspark);
+    assert_eq!(sspark.chars().count(), 40);
+    assert_eq!(sigpro::render_sparkline(&[0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0], 20), "▁▂▃▄▅▆▇█");
+    assert_eq!(sigpro::render_sparkline(&[1.0, f32::NAN, 1.0], 10), "▄ ▄");
+    assert_eq!(sigpro::render_sparkline(&[], 10), "");
+    let vxf: Vec<(f64, f32)> = vdata.iter().enumerate().map(|(i, f)| (i as f64 * 0.1, *f)).collect();
+    let splot = sigpro::render_plot(&vxf, 40, 8);
+    du_log!(info, "TEST:RenderAscii:Plot:\n{}", splot);
+    let vlines: Vec<&str> = splot.lines().collect();
+    assert_eq!(vlines.len(), 10);
+    assert!(vlines[0].starts_with("     0.999 |"));
+    assert!(vlines[..8].iter().all(|l| l.chars().count() == 52));
+    assert_eq!(splot.matches('*').count(), 40);
+    assert!(vlines[9].trim_start().starts_with("0.000"));
+    assert!(vlines[9].ends_with("7.900"));
+    let splot = sigpro::render_plot(&[(1.0, 2.0)], 3, 2);
+    assert_eq!(splot.matches('*').count(), 1);
+    assert_eq!(sigpro::render_plot(&[], 5, 3).matches('*').count(), 0);
+}
+
+pub fn test_exportxf() {
+    let vdata = [(0.0, 1.5f32), (0.125, -2.0), (0.25, f32::NAN)];
+    let mut vcsv = Vec::new();
+    sigpro::export_csv(&mut vcsv, &vdata, &sigpro::ExportOptions::default()).unwrap();
+    let scsv = String::from_utf8(vcsv).unwrap();
+    du_log!(info, "TEST:ExportXf:Csv:{}", scsv);
+    assert_eq!(scsv, "x,f\n0,1.5\n0.125,-2\n0.25,NaN\n");
+    let opts = sigpro::ExportOptions { x_name: "time".to_string(), f_name: "volt\"s".to_string(), precision: Some(2) };
+    let mut vcsv = Vec::new();
+    sigpro::export_csv(&mut vcsv, &vdata[..2], &opts).unwrap();
+    assert_eq!(String::from_utf8(vcsv).unwrap(), "time,volt\"s\n0.00,1.50\n0.12,-2.00\n");
+    let mut vjson = Vec::new();
+    sigpro::export_json(&mut vjson, &vdata, &opts).unwrap();
+    let sjson = String::from_utf8(vjson).unwrap();
+    du_log!(info, "TEST:ExportXf:Json:{}", sjson);
+    assert_eq!(sjson, "[\n  {\"time\": 0.00, \"volt\\\"s\": 1.50},\n  {\"time\": 0.12, \"volt\\\"s\": -2.00},\n  {\"time\": 0.25, \"volt\\\"s\": null}\n]\n");
+    let mut vjson = Vec::new();
+    sigpro::export_json(&mut vjson, &[], &sigpro::ExportOptions::default()).unwrap();
+    assert_eq!(vjson, b"[\n]\n");
+}
+
+pub fn test_hexvalidate() {
+    use crate::hex::HexIssue;
+    assert!(hex::validate("0a1B").is_empty());
+    assert!(hex::validate("").is_empty());
+    let vissues = hex::validate("0a 1Gé\tz23");
+    du_log!(info, "TEST:HexValidate:{:?}", vissues);
+    assert_eq!(vissues, vec![
+        HexIssue::Whitespace { pos: 2, ch: ' ' },
+        HexIssue::InvalidChar { pos: 4, ch: 'G' },
+        HexIssue::InvalidChar { pos: 5, ch: 'é' },
+        HexIssue::Whitespace { pos: 7, ch: '\t' },
+        HexIssue::InvalidChar { pos: 8, ch: 'z' },
+        HexIssue::OddLength { digits: 5 },
+    ]);
+    // validate agrees with vu8_from_hex
+    for shex in ["0a1B", "0a1", "0a 1", "zz", "+F", "00-1"] {
+        assert_eq!(hex::validate(shex).is_empty(), hex::vu8_from_hex(shex).is_ok());
+    }
+}
+
+#[cfg(feature = "wav")]
+pub fn test_wavio() {
+    use sigpro::io;
+    let vdata: Vec<f32> = (0..101).map(|i| (i as f32 * 0.2).sin()*0.9).collect();
+    let tmpfile = std::env::temp_dir().join(format!("du_test_wavio_{}.wav", std::process::id()));
+    for (bits, eps) in [(8, 0.5/128.0), (16, 0.5/32768.0), (24, 1e-6)] {
+        io::write_wav(&tmpfile, 8000, &vdata, bits).unwrap();
+        let (rate, vback) = io::read_wav(&tmpfile).unwrap();
+        du_log!(info, "TEST:WavIO:{} bits:{}:{:?}", bits, rate, &vback[..4]);
+        assert_eq!(rate, 8000);
+        assert_vec_close(&vback, &vdata, eps);
+    }
+    std::fs::remove_file(&tmpfile).unwrap();
+    // clipping and the 16 bit layout
+    let vbuf = io::wav_bytes(44100, &[2.0, -2.0, 0.0], 16).unwrap();
+    assert_eq!(vbuf.len(), 44 + 6);
+    assert_buf_eq_hex(&vbuf[44..], "FF7F00800000");
+    // stereo file, with a extra chunk before data, is mixed down to mono
+    let mut vstereo = io::wav_bytes(1000, &[], 16).unwrap();
+    vstereo[22] = 2;
+    vstereo[32] = 4;
+    vstereo.truncate(36);
+    vstereo.extend_from_slice(b"LIST\x03\x00\x00\x00abc\x00");
+    vstereo.extend_from_slice(b"data\x08\x00\x00\x00\x00\x40\x00\x00\x00\xC0\x00\x00");
+    let (rate, vmono) = io::parse_wav(&vstereo).unwrap();
+    assert_eq!(rate, 1000);
+    assert_vec_close(&vmono, &[0.25, -0.25], 1e-6);
+    assert!(io::parse_wav(b"RIFF\x00\x00\x00\x00WAVE").is_err());
+    assert!(io::parse_wav(b"not a wav file").is_err());
+    assert!(io::wav_bytes(8000, &vdata, 12).is_err());
+    assert!(io::wav_bytes(u32::MAX, &vdata, 16).is_err());
+    assert!(io::wav_bytes(u32::MAX, &vdata, 8).is_ok());
+    assert!(io::read_wav(std::env::temp_dir().join("du_test_nonexistent.wav")).is_err());
+}
+
+#[cfg(feature = "plot")]
+pub fn test_plotchart() {
+    use sigpro::plot::{self, ChartOptions};
+    let vsine: Vec<(f64, f32)> = (0..50).map(|i| (i as f64*0.1, (i as f32*0.1).sin())).collect();
+    let vcos: Vec<(f64, f32)> = (0..50).map(|i| (i as f64*0.1, (i as f32*0.1).cos())).collect();
+    let opts = ChartOptions { title: "sine & cosine".to_string(), ..Default::default() };
+    let tmpfile = std::env::temp_dir().join(format!("du_test_plotchart_{}.svg", std::process::id()));
+    plot::line_chart(&tmpfile, &[("sine", &vsine), ("cos<x>", &vcos)], &opts).unwrap();
+    let svg = std::fs::read_to_string(&tmpfile).unwrap();
+    du_log!(info, "TEST:PlotChart:svg:{} bytes", svg.len());
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.contains("sine &amp; cosine"));
+    assert!(svg.contains("cos&lt;x&gt;"));
+    assert!(svg.to_uppercase().contains("#FF7F0E"));
+    std::fs::remove_file(&tmpfile).unwrap();
+    let tmpfile = tmpfile.with_extension("png");
+    plot::line_chart(&tmpfile, &[("sine", &vsine), ("flat", &[(1.0, 0.5)])], &ChartOptions::default()).unwrap();
+    let vpng = std::fs::read(&tmpfile).unwrap();
+    du_log!(info, "TEST:PlotChart:png:{} bytes", vpng.len());
+    assert!(vpng.starts_with(b"\x89PNG\r\n\x1a\n"));
+    std::fs::remove_file(&tmpfile).unwrap();
+    assert!(plot::line_chart(tmpfile.with_extension("gif"), &[("sine", &vsine)], &opts).is_err());
+    assert!(plot::line_chart(tmpfile.with_extension("svg"), &[("empty", &[])], &opts).is_err());
+}
+
+pub fn test_utf8helpers() {
+    use crate::text::Utf8Mode;
+    assert_eq!(text::utf8_bytes_of('A'), vec![0x41]);
+    assert_eq!(text::utf8_bytes_of('é'), vec![0xC3, 0xA9]);
+    assert_eq!(text::utf8_bytes_of('€'), vec![0xE2, 0x82, 0xAC]);
+    assert_eq!(text::utf8_bytes_of('😀'), vec![0xF0, 0x9F, 0x98, 0x80]);
+    assert_eq!(text::codepoints_from_utf8("aé€".as_bytes(), Utf8Mode::Strict), Ok(vec!['a', 'é', '€']));
+    assert!(text::codepoints_from_utf8(b"ab\xFFc", Utf8Mode::Strict).unwrap_err().contains("byte 2"));
+    assert_eq!(text::codepoints_from_utf8(b"ab\xFFc", Utf8Mode::Lossy), Ok(vec!['a', 'b', '\u{FFFD}', 'c']));
+    for sin in ["naïve €", "abc", "é", ""] {
+        let vvariants = text::malformed_utf8_variants(sin);
+        du_log!(info, "TEST:Utf8Helpers:[{}]:{} variants:{:?}", sin, vvariants.len(), vvariants.iter().map(hex::hex_from_vu8).collect::<Vec<_>>());
+        assert!(vvariants.len() >= 4);
+        for v in &vvariants {
+            assert!(core::str::from_utf8(v).is_err(), "{:?} is valid utf8", v);
+            assert!(text::codepoints_from_utf8(v, Utf8Mode::Strict).is_err());
+            assert!(text::codepoints_from_utf8(v, Utf8Mode::Lossy).unwrap().contains(&'\u{FFFD}'));
+        }
+    }
+    assert_eq!(text::malformed_utf8_variants("abc").len(), 6);
+    assert_eq!(text::malformed_utf8_variants("a€").len(), 7);
+}
+
+pub fn test_floatvalue() {
+    use crate::float::{floatvalue, parse_unit_f64};
+    assert_eq!(floatvalue(" 1.5e3 "), Ok(1500.0));
+    assert_eq!(floatvalue("-0.25"), Ok(-0.25));
+    assert!(floatvalue("inf").unwrap().is_infinite());
+    assert!(matches!(floatvalue("1.5.3"), Err(DataUtilsError::ParseFloat { .. })));
+    assert!(floatvalue("").is_err());
+    let units = ["s", "ms", "%", "ppm"];
+    for sval in ["2.5ms", "10 %", "-3e2ppm", "7", "1.5s"] {
+        du_log!(info, "TEST:FloatValue:[{}]:{:?}", sval, parse_unit_f64(sval, &units));
+    }
+    assert_eq!(parse_unit_f64("2.5ms", &units), Ok((2.5, "ms".to_string())));
+    assert_eq!(parse_unit_f64(" 10 % ", &units), Ok((10.0, "%".to_string())));
+    assert_eq!(parse_unit_f64("-3e2ppm", &units), Ok((-300.0, "ppm".to_string())));
+    assert_eq!(parse_unit_f64("7", &units), Ok((7.0, String::new())));
+    assert!(parse_unit_f64("2.5kg", &units).is_err());
+    assert!(parse_unit_f64("ms", &units).is_err());
+    // any unit
+    assert_eq!(parse_unit_f64("1.5e3Hz", &[]), Ok((1500.0, "Hz".to_string())));
+    assert_eq!(parse_unit_f64("4 µV", &[]), Ok((4.0, "µV".to_string())));
+    assert_eq!(parse_unit_f64("2e", &[]), Ok((2.0, "e".to_string())));
+    assert!(parse_unit_f64("abc", &[]).is_err());
+    assert!(parse_unit_f64("", &[]).is_err());
+    // inf and nan are only values by themselves, not prefixes of a unit word
+    assert!(parse_unit_f64("nanoseconds", &[]).is_err());
+    assert!(parse_unit_f64("information", &[]).is_err());
+    assert!(parse_unit_f64("infs", &["s"]).is_err());
+    assert!(parse_unit_f64("nan", &[]).unwrap().0.is_nan());
+    assert_eq!(parse_unit_f64("-inf", &units), Ok((f64::NEG_INFINITY, String::new())));
+    assert_eq!(parse_unit_f64("5.ms", &units), Ok((5.0, "ms".to_string())));
+}
+
+pub fn test_classifyliteral() {
+    use crate::integer::{classify_literal, LiteralInfo};
+    for sval in ["123", "-0x1F", "0b1010_1010u8", "0o777", "+42i16", "0xFF_u8"] {
+        du_log!(info, "TEST:ClassifyLiteral:[{}]:{:?}", sval, classify_literal(sval));
+    }
+    assert_eq!(classify_literal(" 123 "), Ok(LiteralInfo { radix: 10, signed: false, width_hint: None, value: 123 }));
+    assert_eq!(classify_literal("-0x1F"), Ok(LiteralInfo { radix: 16, signed: true, width_hint: None, value: -31 }));
+    assert_eq!(classify_literal("0b1010_1010u8"), Ok(LiteralInfo { radix: 2, signed: false, width_hint: Some(8), value: 170 }));
+    assert_eq!(classify_literal("0O777"), Ok(LiteralInfo { radix: 8, signed: false, width_hint: None, value: 511 }));
+    assert_eq!(classify_literal("+42i16"), Ok(LiteralInfo { radix: 10, signed: true, width_hint: Some(16), value: 42 }));
+    assert_eq!(classify_literal("0xFF_u8").unwrap().value, 255);
+    // without a _, a hex literal ending in what looks like a suffix is treated has digits
+    assert!(classify_literal("0x1u8").is_err());
+    assert!(classify_literal("0xABu16").is_err());
+    assert_eq!(classify_literal("-128i8").unwrap().value, -128);
+    assert_eq!(classify_literal("-170141183460469231731687303715884105728").unwrap().value, i128::MIN);
+    assert!(matches!(classify_literal("340282366920938463463374607431768211455u128"), Err(DataUtilsError::ParseInt { .. })));
+    // errors
+    assert!(matches!(classify_literal("256u8"), Err(DataUtilsError::OutOfRange { .. })));
+    assert!(matches!(classify_literal("-1u32"), Err(DataUtilsError::OutOfRange { .. })));
+    assert!(matches!(classify_literal("128i8"), Err(DataUtilsError::OutOfRange { .. })));
+    assert!(classify_literal("").is_err());
+    assert!(classify_literal("0x").is_err());
+    assert!(classify_literal("u8").is_err());
+    assert!(classify_literal("12a").is_err());
+    assert!(classify_literal("_12").is_err());
+    // i128 and u128 suffixes, at their extremes (within i128)
+    assert_eq!(classify_literal("5i128").unwrap(), LiteralInfo { radix: 10, signed: true, width_hint: Some(128), value: 5 });
+    assert_eq!(classify_literal("-170141183460469231731687303715884105728i128").unwrap().value, i128::MIN);
+    assert_eq!(classify_literal("170141183460469231731687303715884105727i128").unwrap().value, i128::MAX);
+    assert_eq!(classify_literal("0x7fff_ffff_ffff_ffff_ffff_ffff_ffff_ffff_u128").unwrap().value, i128::MAX);
+    assert_eq!(classify_literal("0u128").unwrap().value, 0);
+    assert!(matches!(classify_literal("-1u128"), Err(DataUtilsError::OutOfRange { .. })));
+    // only one sign, and no sign after the radix prefix
+    assert!(matches!(classify_literal("-+5"), Err(DataUtilsError::ParseInt { .. })));
+    assert!(classify_literal("+-5").is_err());
+    assert!(classify_literal("0x+F").is_err());
+    assert!(classify_literal("0b-1").is_err());
+}
+
+pub fn test_randdata() {
+    use crate::randdata::{self, Zipf};
+    let mut rng = seeded_rng(1978);
+    let vints: Vec<i64> = (0..1000).map(|_| randdata::int_in(&mut rng, -3..=3)).collect();
+    du_log!(info, "TEST:RandData:IntIn:{:?}", &vints[..20]);
+    assert!(vints.iter().all(|v| (-3..=3).contains(v)));
+    for v in -3..=3 {
+        assert!(vints.contains(&v));
+    }
+    assert_eq!(randdata::int_in(&mut rng, 5..=5), 5);
+    assert_eq!(randdata::int_in(&mut rng, core::ops::RangeInclusive::new(5, 1)), 5);
+    let _ = randdata::int_in(&mut rng, i64::MIN..=i64::MAX);
+    // reproducible with the same seed
+    let mut rng2 = seeded_rng(1978);
+    assert_eq!(randdata::int_in(&mut rng2, -3..=3), vints[0]);
+    // choose_weighted
+    let items = [("a", 1.0), ("b", 0.0), ("c", 3.0), ("d", -2.0)];
+    let mut counts = [0usize; 4];
+    for _ in 0..4000 {
+        let item = randdata::choose_weighted(&mut rng, &items).unwrap();
+        counts[items.iter().position(|(i, _)| i == item).unwrap()] += 1;
+    }
+    du_log!(info, "TEST:RandData:ChooseWeighted:{:?}", counts);
+    assert_eq!((counts[1], counts[3]), (0, 0));
+    assert!((counts[2] as f64/counts[0] as f64 - 3.0).abs() < 0.5);
+    assert!(randdata::choose_weighted::<u8>(&mut rng, &[]).is_none());
+    assert!(randdata::choose_weighted(&mut rng, &[(1, 0.0)]).is_none());
+    // uniform and geometric
+    let vu: Vec<f64> = (0..1000).map(|_| randdata::uniform(&mut rng, 2.0, 4.0)).collect();
+    assert!(vu.iter().all(|v| (2.0..4.0).contains(v)));
+    assert!((vu.iter().sum::<f64>()/1000.0 - 3.0).abs() < 0.1);
+    let gmean = (0..4000).map(|_| randdata::geometric(&mut rng, 0.25) as f64).sum::<f64>()/4000.0;
+    du_log!(info, "TEST:RandData:Geometric:Mean:{}", gmean);
+    assert!((gmean - 3.0).abs() < 0.3);
+    assert_eq!(randdata::geometric(&mut rng, 1.0), 0);
+    assert_eq!(randdata::geometric(&mut rng, 0.0), u64::MAX);
+    // zipf
+    let zipf = Zipf::new(10, 1.0).unwrap();
+    let mut zcounts = [0usize; 11];
+    for _ in 0..5000 {
+        zcounts[zipf.sample(&mut rng) as usize] += 1;
+    }
+    du_log!(info, "TEST:RandData:Zipf:{:?}", zcounts);
+    assert_eq!(zcounts[0], 0);
+    assert!(zcounts[1] > zcounts[2] && zcounts[2] > zcounts[5] && zcounts[5] > zcounts[10]);
+    assert!((zcounts[1] as f64/zcounts[2] as f64 - 2.0).abs() < 0.4);
+    assert_eq!(Zipf::new(1, 2.0).unwrap().sample(&mut rng), 1);
+    assert!(Zipf::new(0, 1.0).is_err());
+    assert!(Zipf::new(5, -1.0).is_err());
+}
+
+pub fn test_boundeduint() {
+    use crate::integer::{U8X, U16X, U32X};
+    let a: U8X = "200".parse().unwrap();
+    let b: U16X = "0x1234".parse().unwrap();
+    let c: U32X = " 70000 ".parse().unwrap();
+    du_log!(info, "TEST:BoundedUInt:{} {} {}", a, b, c);
+    assert_eq!((a.to_string(), b.to_string(), c.to_string()), ("200".to_string(), "4660".to_string(), "70000".to_string()));
+    assert_eq!(format!("{:>5}", a), "  200");
+    assert!(matches!("256".parse::<U8X>(), Err(DataUtilsError::OutOfRange { .. })));
+    assert!(matches!("-1".parse::<U16X>(), Err(DataUtilsError::OutOfRange { .. })));
+    assert!(matches!("12z".parse::<U32X>(), Err(DataUtilsError::ParseInt { .. })));
+    // comparisons
+    assert!(a == 200u8 && a > 100u8 && a < U8X(201));
+    assert!(b > U16X(0x1000) && b <= 0x1234u16);
+    // arithmetic
+    assert_eq!(a + 50u8, U8X(250));
+    assert_eq!(a - U8X(100), 100u8);
+    assert_eq!(b * 2u16, U16X(0x2468));
+    assert_eq!(c / 7u32, 10000u32);
+    assert_eq!(c % U32X(300), 100u32);
+    // conversions
+    assert_eq!(U16X::try_from_isize(65535).unwrap(), 65535u16);
+    assert!(U16X::try_from_isize(65536).is_err());
+    assert_eq!(U32X::from(123isize), 123u32);
+    let raw: u32 = c.into();
+    assert_eq!(raw, 70000);
+}
+
+pub fn test_variantarena() {
+    use crate::variant::{VDataType, VariantArena, VariantRef};
+    let mut arena = VariantArena::with_capacity(8, 64);
+    let text = "10\n\"hello\"\n\n$0xA0B1\n  0x20  \n\"\"\n__TIME__STAMP__\n";
+    let r = arena.parse_lines(text).unwrap();
+    du_log!(info, "TEST:VariantArena:{:?}:{:?}", r, arena.iter().collect::<Vec<_>>());
+    assert_eq!((r, arena.len(), arena.bytes_used()), (0..6, 6, 7));
+    assert_eq!(arena.get(0), Some(VariantRef::IntValue(10)));
+    assert_eq!(arena.get(1), Some(VariantRef::StrValue("hello")));
+    assert_eq!(arena.get(2), Some(VariantRef::BufValue(&[0xA0, 0xB1])));
+    assert_eq!(arena.get(3), Some(VariantRef::IntValue(32)));
+    assert_eq!(arena.get(4), Some(VariantRef::StrValue("")));
+    assert_eq!(arena.get(5).unwrap().get_type(), VDataType::Special);
+    assert!(arena.get(6).is_none());
+    assert_eq!(arena.get(1).unwrap().to_variant().get_string(), "hello");
+    // same results has the owned parsing
+    for (line, vref) in ["10", "\"hello\"", "$0xA0B1", "0x20"].iter().zip(arena.iter()) {
+        assert_eq!(vref.to_variant().get_bufvu8(), Variant::from_literal(line).unwrap().get_bufvu8());
+    }
+    // a bad line leaves the arena as is
+    let err = arena.parse_lines("1\n\"x\"\n$0xABC\n").unwrap_err();
+    du_log!(info, "TEST:VariantArena:Err:{}", err);
+    assert!(err.to_string().contains("Line 3"));
+    assert_eq!((arena.len(), arena.bytes_used()), (6, 7));
+    assert!(arena.push_literal("$0xZZ").is_err());
+    assert!(matches!(arena.push_literal("$0x+F"), Err(DataUtilsError::ParseHex { .. })));
+    assert!(matches!(arena.push_literal("12a"), Err(DataUtilsError::ParseInt { .. })));
+    assert_eq!(arena.len(), 6);
+    let r = arena.parse_lines("7\n8").unwrap();
+    assert_eq!(r, 6..8);
+    assert_eq!(arena.push_buf(&[1, 2]), 8);
+    assert_eq!(arena.push_str("abc"), 9);
+    assert_eq!(arena.get(9), Some(VariantRef::StrValue("abc")));
+    arena.clear();
+    assert!(arena.is_empty() && arena.bytes_used() == 0);
+}
+
+pub fn test_clock() {
+    use crate::clock::{Clock, ManualClock, SystemClock};
+    use crate::variant::VariantBuilder;
+    let clock = ManualClock::new(1_000);
+    let ts = Variant::XTimeStamp;
+    assert_eq!(ts.get_isize_with_clock(&clock).unwrap(), 1000);
+    clock.advance(234);
+    assert_eq!(ts.get_string_with_clock(&clock), "1234");
+    assert_eq!(ts.get_bufvu8_with_clock(&clock), 1234u128.to_ne_bytes().to_vec());
+    clock.set(7);
+    let vlist = VariantBuilder::list().int(1).push(Variant::XTimeStamp).build();
+    du_log!(info, "TEST:Clock:{}", vlist.get_string_with_clock(&clock));
+    assert_eq!(vlist.get_string_with_clock(&clock), "[1, 7]");
+    let vmap = VariantBuilder::map().put("ts", Variant::XTimeStamp).build();
+    assert_eq!(vmap.get_bufvu8_with_clock(&clock), 7u128.to_ne_bytes().to_vec());
+    // non time variants are not affected by the clock
+    assert_eq!(Variant::IntValue(5).get_isize_with_clock(&clock).unwrap(), 5);
+    // the default system clock
+    assert!(SystemClock.now_ms() > 1_600_000_000_000);
+    assert!(ts.get_isize().unwrap() as u128 >= SystemClock.now_ms() - 1000);
+}
+
+pub fn test_sharedbuf() {
+    use crate::variant::{SharedBytes, VDataType};
+    let capture = SharedBytes::new((0..=255u8).cycle().take(1000).collect());
+    let mut vparts: Vec<Variant> = (0..10).map(|i| Variant::from(capture.slice(i*100..(i+1)*100).unwrap())).collect();
+    du_log!(info, "TEST:SharedBuf:ShareCount:{}", capture.share_count());
+    assert_eq!(capture.share_count(), 11);
+    assert_eq!(vparts[1].get_type(), VDataType::Buffer);
+    assert_eq!(vparts[1].get_bufvu8(), (100..200u8).collect::<Vec<u8>>());
+    let small = Variant::SharedBuf(capture.slice(0x10..0x13).unwrap());
+    assert_eq!(small.get_string(), "101112");
+    assert_eq!(small.to_string(), "101112");
+    let bint = Variant::SharedBuf(capture.slice(0..8).unwrap());
+    assert_eq!(bint.get_isize().unwrap(), isize::from_ne_bytes([0, 1, 2, 3, 4, 5, 6, 7]));
+    assert!(small.get_isize().is_err());
+    // array elements share the data too
+    let ele = vparts[2].get_arrayelement(5).unwrap();
+    assert!(matches!(&ele, Variant::SharedBuf(sb) if sb.as_slice() == [205]));
+    assert_eq!(capture.share_count(), 14);
+    assert!(vparts[2].get_arrayelement(100).is_err());
+    assert!(vparts[0].get_bufvu8_mut().is_none());
+    // slices are relative to the view
+    let sub = capture.slice(500..600).unwrap().slice(10..12).unwrap();
+    assert_eq!(&*sub, &[254, 255]);
+    assert!(capture.slice(990..1001).is_none());
+    assert!(sub.slice(1..3).is_none());
+    vparts.clear();
+    assert_eq!(capture.share_count(), 5);
+}
+
+pub fn test_variantformat() {
+    let vint = Variant::IntValue(255);
+    let vneg = Variant::IntValue(-42);
+    let vstr = Variant::StrValue("hello".to_string());
+    let tests = [
+        (&vint, "{}", "255"), (&vint, "{:>8}", "     255"), (&vint, "{:08x}", "000000ff"),
+        (&vint, "{:#06x}", "0x00ff"), (&vint, "{:X}", "FF"), (&vint, "{:#b}", "0b11111111"), (&vint, "{:o}", "377"),
+        (&vint, "{:+}", "+255"), (&vint, "{:*^9}", "***255***"), (&vint, "{:.3}", "255"), (&vint, "{:s}", "255"),
+        (&vneg, "{:06}", "-00042"), (&vneg, "{:#x}", "-0x2a"), (&vneg, "{:>5}", "  -42"),
+        (&vstr, "{:8}", "hello   "), (&vstr, "{:>8}", "   hello"), (&vstr, "{:.3}", "hel"), (&vstr, "{:*^9.3}", "***hel***"),
+        (&vstr, "{:-^4}", "hello"),
+    ];
+    for (v, spec, exp) in tests {
+        let got = v.format(spec).unwrap();
+        du_log!(info, "TEST:VariantFormat:{}:[{}]", spec, got);
+        assert_eq!(got, exp, "spec {}", spec);
+    }
+    assert_eq!(Variant::BufValue(vec![0xAB, 0x01]).format("{:>6}").unwrap(), "  AB01");
+    assert_eq!(Variant::StrValue("0x10".to_string()).format("{:d}").unwrap(), "16");
+    for bad in ["", ":>8", "{:>8", "{:q}", "{:.}", "{:8x8}", "{x}"] {
+        assert!(vint.format(bad).is_err(), "spec {}", bad);
+    }
+    assert!(vstr.format("{:x}").is_err());
+    // format_int continues to work
+    assert_eq!(vint.format_int(16, 6, '0').unwrap(), "0x00FF");
+}
+
+pub fn test_basen() {
+    use crate::encode::BaseN;
+    let b64 = BaseN::new("ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/").unwrap();
+    let b32 = BaseN::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ234567").unwrap();
+    let b58 = BaseN::new("123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz").unwrap();
+    let b16 = BaseN::new("0123456789ABCDEF").unwrap();
+    let tests: [(&BaseN, &[u8], &str); 9] = [
+        (&b64, b"foobar", "Zm9vYmFy"), (&b64, b"fo", "Zm8"), (&b64, b"f", "Zg"), (&b32, b"foobar", "MZXW6YTBOI"),
+        (&b32, b"f", "MY"), (&b58, b"Hello World!", "2NEpo7TZRRrLZSi2U"), (&b58, &[0, 0, 1], "112"),
+        (&b58, b"", ""), (&b16, &[0x12, 0xAB], "12AB"),
+    ];
+    for (basen, data, exp) in tests {
+        let enc = basen.encode(data);
+        du_log!(info, "TEST:BaseN:{:?}:{}", data, enc);
+        assert_eq!(enc, exp);
+        assert_eq!(basen.decode(&enc).unwrap(), data);
+    }
+    assert_eq!(b16.encode(&[0, 1, 0xFE]), hex::hex_from_vu8([0, 1, 0xFE]));
+    // crockford base32
+    let c32 = BaseN::crockford32();
+    let data = [0x00, 0x44, 0x32, 0x14, 0xC7, 0x42, 0x54, 0xB6, 0x35, 0xCF];
+    let enc = c32.encode(&data);
+    assert_eq!(enc, "0123456789ABCDEF");
+    assert_eq!(c32.decode("oi23456789abcdef").unwrap(), data);
+    assert_eq!(c32.decode("OL23456789ABCDEF").unwrap(), data);
+    // errors
+    assert!(b64.decode("Zm9v!").is_err());
+    assert!(b64.decode("Z").is_err());
+    assert!(b64.decode("Zh").is_err());
+    assert!(b32.decode("my").is_err());
+    assert!(b32.clone().ignore_case().decode("my").is_ok());
+    assert!(BaseN::new("A").is_err());
+    assert!(BaseN::new("ABCA").is_err());
+    assert!(BaseN::new("AB\u{e9}").is_err());
+    // round trips with random data, including leading zeros
+    let mut rng = seeded_rng(1984);
+    let b36 = BaseN::new("0123456789abcdefghijklmnopqrstuvwxyz").unwrap();
+    for basen in [&b64, &b32, &b58, &b36, &c32] {
+        for len in 0..20 {
+            let mut data = vec![0u8; len];
+            rng.fill_bytes(&mut data);
+            if len > 3 {
+                data[0] = 0;
+            }
+            assert_eq!(basen.decode(&basen.encode(&data)).unwrap(), data);
+        }
+    }
+}
+
+pub fn test_bitvec() {
+    use crate::bits::BitVec;
+    let mut bv = BitVec::new(130);
+    for i in [0, 3, 63, 64, 65, 129] {
+        bv.set(i, true).unwrap();
+    }
+    du_log!(info, "TEST:BitVec:Ones:{:?}", bv.iter_ones().collect::<Vec<_>>());
+    assert_eq!(bv.iter_ones().collect::<Vec<_>>(), vec![0, 3, 63, 64, 65, 129]);
+    assert_eq!((bv.count_ones(), bv.count_zeros()), (6, 124));
+    assert_eq!((bv.rank(0), bv.rank(4), bv.rank(64), bv.rank(65), bv.rank(1000)), (0, 2, 3, 4, 6));
+    assert_eq!((bv.get(3), bv.get(4), bv.get(130)), (Some(true), Some(false), None));
+    assert!(!bv.flip(3).unwrap());
+    assert!(bv.flip(4).unwrap());
+    bv.set(4, false).unwrap();
+    assert!(matches!(bv.set(130, true), Err(DataUtilsError::IndexOutOfBounds { index: 130, len: 130 })));
+    assert!(bv.flip(200).is_err());
+    // not keeps the unused bits clear
+    let nbv = !&bv;
+    assert_eq!((nbv.len(), nbv.count_ones()), (130, 125));
+    assert_eq!(!&nbv, bv);
+    // binary ops
+    let a = BitVec::from_bit_str("1100_1010").unwrap();
+    let b = BitVec::from_bit_str("1010").unwrap();
+    assert_eq!((&a & &b).to_bit_string(), "10000000");
+    assert_eq!((&a | &b).to_bit_string(), "11101010");
+    assert_eq!((&a ^ &b).to_string(), "01101010");
+    assert!(BitVec::from_bit_str("10x1").is_err());
+    // bytes, lsb first
+    let c = BitVec::from_bytes(&[0x01, 0x80]);
+    assert_eq!(c.to_bit_string(), "1000000000000001");
+    assert_eq!(c.to_bytes(), vec![0x01, 0x80]);
+    assert_eq!(BitVec::from_bit_str("1111_1111_11").unwrap().to_bytes(), vec![0xFF, 0x03]);
+    let vbytes: Vec<u8> = (0..20u8).map(|i| i.wrapping_mul(37)).collect();
+    assert_eq!(BitVec::from_bytes(&vbytes).to_bytes(), vbytes);
+    // resize, push, fill
+    let mut d = BitVec::new(0);
+    assert!(d.is_empty());
+    for i in 0..70 {
+        d.push(i % 3 == 0);
+    }
+    assert_eq!((d.len(), d.count_ones()), (70, 24));
+    d.fill(true);
+    assert_eq!(d.count_ones(), 70);
+    d.resize(10);
+    d.resize(100);
+    assert_eq!(d.count_ones(), 10);
+    d.fill(false);
+    assert_eq!(d, BitVec::new(100));
+}
+
+pub fn test_ringseries() {
+    use crate::sigpro::RingSeries;
+    let mut ring = RingSeries::new(4);
+    assert!(ring.is_empty() && ring.mean().is_none() && ring.min().is_none() && ring.age(1.0).is_none());
+    let vals = [5.0f32, 1.0, 3.0, 8.0, 2.0, 7.0, 6.0, 4.0];
+    let mut rng = seeded_rng(1986);
+    for (i, v) in vals.iter().enumerate() {
+        ring.push(i as f64*0.5, *v);
+        let start = (i+1).saturating_sub(4);
+        let win = &vals[start..=i];
+        let emin = win.iter().cloned().fold(f32::MAX, f32::min);
+        let emax = win.iter().cloned().fold(f32::MIN, f32::max);
+        let emean = win.iter().sum::<f32>()/win.len() as f32;
+        du_log!(info, "TEST:RingSeries:{}:{:?}:{:?}:{:?}", i, ring.min(), ring.max(), ring.mean());
+        assert_eq!((ring.min(), ring.max()), (Some(emin), Some(emax)));
+        assert!((ring.mean().unwrap() - emean).abs() < 1e-5);
+    }
+    assert_eq!((ring.len(), ring.capacity()), (4, 4));
+    assert_eq!(ring.oldest(), Some((2.0, 2.0)));
+    assert_eq!(ring.latest(), Some((3.5, 4.0)));
+    assert_eq!(ring.span(), Some(1.5));
+    assert_eq!(ring.age(5.0), Some(1.5));
+    assert_eq!(ring.iter().map(|(_, v)| *v).collect::<Vec<_>>(), vec![2.0, 7.0, 6.0, 4.0]);
+    // NaN is ignored by min/max
+    ring.push(4.0, f32::NAN);
+    assert_eq!((ring.min(), ring.max()), (Some(4.0), Some(7.0)));
+    assert!(ring.mean().unwrap().is_nan());
+    // and NaN/inf only affect the mean while they are within the window
+    let mut ring2 = RingSeries::new(2);
+    ring2.push(0.0, f32::NAN);
+    ring2.push(0.0, 1.0);
+    assert!(ring2.mean().unwrap().is_nan());
+    ring2.push(0.0, 2.0);
+    assert_eq!(ring2.mean(), Some(1.5));
+    ring2.push(0.0, 3.0);
+    assert_eq!(ring2.mean(), Some(2.5));
+    ring2.push(0.0, f32::INFINITY);
+    assert_eq!(ring2.mean(), Some(f32::INFINITY));
+    ring2.push(0.0, f32::NEG_INFINITY);
+    assert!(ring2.mean().unwrap().is_nan());
+    ring2.push(0.0, 1.0);
+    assert_eq!(ring2.mean(), Some(f32::NEG_INFINITY));
+    ring2.push(0.0, 2.0);
+    assert_eq!(ring2.mean(), Some(1.5));
+    // compare against brute force over a longer random stream
+    let mut ring = RingSeries::new(16);
+    let mut vall = Vec::new();
+    for i in 0..500 {
+        let v = (rng.next_u64() % 1000) as f32;
+        vall.push(v);
+        ring.push(i as f64, v);
+        let win = &vall[vall.len().saturating_sub(16)..];
+        assert_eq!(ring.min(), win.iter().cloned().reduce(f32::min));
+        assert_eq!(ring.max(), win.iter().cloned().reduce(f32::max));
+    }
+    ring.clear();
+    assert!(ring.is_empty() && ring.max().is_none());
+    ring.push(1.0, 3.0);
+    assert_eq!((ring.min(), ring.max(), ring.mean()), (Some(3.0), Some(3.0), Some(3.0)));
+    assert_eq!(RingSeries::new(0).capacity(), 1);
+}
+
+pub fn test_splitdelimited() {
+    let data = b"ab|c\\|d|\\\\|e\\";
+    let vparts = bytes::split_delimited(data, b'|', Some(b'\\'));
+    du_log!(info, "TEST:SplitDelimited:{:?}", vparts);
+    assert_eq!(vparts, vec![&b"ab"[..], b"c\\|d", b"\\\\", b"e\\"]);
+    assert_eq!(bytes::split_delimited_spans(data, b'|', Some(b'\\')), vec![0..2, 3..7, 8..10, 11..13]);
+    assert_eq!(bytes::unescape_delimited(vparts[1], b'\\'), b"c|d");
+    assert_eq!(bytes::unescape_delimited(vparts[2], b'\\'), b"\\");
+    assert_eq!(bytes::unescape_delimited(vparts[3], b'\\'), b"e\\");
+    // without escaping, same has slice split
+    let data = [1u8, 0, 2, 3, 0, 0];
+    let exp: Vec<&[u8]> = data.split(|b| *b == 0).collect();
+    assert_eq!(bytes::split_delimited(&data, 0, None), exp);
+    assert_eq!(bytes::split_delimited(&data, 0, Some(0)), exp);
+    assert_eq!(bytes::split_delimited(b"", b',', None), vec![&b""[..]]);
+    assert_eq!(bytes::split_delimited(b"\x7d\x7e\x7e", 0x7e, Some(0x7d)), vec![&b"\x7d\x7e"[..], b""]);
+}
+
+pub fn test_bytesfind() {
+    let naive = |h: &[u8], n: &[u8]| -> Vec<usize> {
+        (0..=h.len().saturating_sub(n.len())).filter(|i| h.len() >= n.len() && &h[*i..*i+n.len()] == n).collect()
+    };
+    let hay = b"the quick brown fox jumps over the lazy dog, the end";
+    assert_eq!(bytes::find(hay, b"the"), Some(0));
+    assert_eq!(bytes::find_all(hay, b"the"), vec![0, 31, 45]);
+    assert_eq!(bytes::find(hay, b"dog,"), Some(40));
+    assert_eq!(bytes::find(hay, b"cat"), None);
+    assert_eq!(bytes::find(hay, b"d"), Some(40));
+    assert_eq!(bytes::find_all(b"aaaa", b"aa"), vec![0, 1, 2]);
+    assert_eq!(bytes::find(b"ab", b"abc"), None);
+    assert_eq!(bytes::find(b"", b"a"), None);
+    assert_eq!(bytes::find(b"abc", b""), Some(0));
+    // compare against naive search, using small alphabets, so that periodic needles are common
+    let mut rng = seeded_rng(1988);
+    for round in 0..300 {
+        let alpha = 2 + (round % 3) as u64;
+        let hlen = rng.below(200);
+        let hay: Vec<u8> = (0..hlen).map(|_| b'a' + (rng.next_u64() % alpha) as u8).collect();
+        let nlen = 1 + rng.below(8);
+        let needle: Vec<u8> = if hlen > nlen && rng.below(2) == 0 {
+            let s = rng.below(hlen - nlen);
+            hay[s..s+nlen].to_vec()
+        } else {
+            (0..nlen).map(|_| b'a' + (rng.next_u64() % alpha) as u8).collect()
+        };
+        let exp = naive(&hay, &needle);
+        let finder = bytes::Finder::new(&needle);
+        assert_eq!(finder.find_all_in(&hay), exp, "needle {:?}", needle);
+        assert_eq!(finder.find_in(&hay), exp.first().copied());
+    }
+    // single byte search across the 8 byte chunks
+    let mut big = vec![0u8; 1000];
+    big[997] = 7;
+    assert_eq!(bytes::find(&big, &[7]), Some(997));
+    big[8] = 7;
+    assert_eq!(bytes::find_all(&big, &[7]), vec![8, 997]);
+    du_log!(info, "TEST:BytesFind:Done");
+}
+
+pub fn test_matchmasked() {
+    let (pattern, mask) = bytes::parse_masked_pattern("DE AD ?? EF").unwrap();
+    du_log!(info, "TEST:MatchMasked:{:02X?}:{:02X?}", pattern, mask);
+    assert_eq!((pattern.clone(), mask.clone()), (vec![0xDE, 0xAD, 0x00, 0xEF], vec![0xFF, 0xFF, 0x00, 0xFF]));
+    assert!(bytes::match_masked(&[0xDE, 0xAD, 0x12, 0xEF], &pattern, &mask));
+    assert!(bytes::match_masked(&[0xDE, 0xAD, 0xFF, 0xEF], &pattern, &mask));
+    assert!(!bytes::match_masked(&[0xDE, 0xAE, 0x12, 0xEF], &pattern, &mask));
+    assert!(!bytes::match_masked(&[0xDE, 0xAD, 0x12], &pattern, &mask));
+    assert!(!bytes::match_masked(&[0xDE, 0xAD, 0x12, 0xEF, 0x00], &pattern, &mask));
+    // nibble wildcards and no whitespace
+    let (pattern, mask) = bytes::parse_masked_pattern("a?0b?7").unwrap();
+    assert_eq!((pattern.clone(), mask.clone()), (vec![0xA0, 0x0B, 0x07], vec![0xF0, 0xFF, 0x0F]));
+    assert!(bytes::match_masked(&[0xAF, 0x0B, 0x37], &pattern, &mask));
+    assert!(!bytes::match_masked(&[0xBF, 0x0B, 0x37], &pattern, &mask));
+    assert!(bytes::match_masked(&[], &[], &[]));
+    assert!(bytes::parse_masked_pattern("DE A").is_err());
+    assert!(bytes::parse_masked_pattern("DE AG").is_err());
+    assert_eq!(bytes::parse_masked_pattern("").unwrap(), (vec![], vec![]));
+}
+
+pub fn test_movingavgconstfn() {
+    use crate::sigpro::MovingAverageConst;
+    const MAVG: MovingAverageConst<3> = MovingAverageConst::new();
+    let mut mavg = MAVG;
+    let vavg: Vec<f32> = [3.0, 6.0, 9.0, 12.0].iter().map(|s| mavg.push(*s)).collect();
+    du_log!(info, "TEST:MovingAvgConstFn:{:?}", vavg);
+    assert_eq!(vavg, vec![3.0, 4.5, 6.0, 9.0]);
+    assert_eq!(MAVG.average(), None);
+    assert_eq!(core::mem::size_of::<MovingAverageConst<3>>(), core::mem::size_of::<[f32; 4]>() + 2*core::mem::size_of::<usize>());
+}
+
+pub fn test_interleave() {
+    let vdata = [1.0f32, 10.0, 2.0, 20.0, 3.0, 30.0, 4.0];
+    let vchannels = sigpro::deinterleave(&vdata, 2);
+    du_log!(info, "TEST:Interleave:{:?}", vchannels);
+    assert_eq!(vchannels, vec![vec![1.0, 2.0, 3.0], vec![10.0, 20.0, 30.0]]);
+    assert_eq!(sigpro::interleave(&vchannels).unwrap(), vdata[..6].to_vec());
+    assert_eq!(sigpro::deinterleave(&vdata, 1), vec![vdata.to_vec()]);
+    assert!(sigpro::deinterleave(&vdata, 0).is_empty());
+    assert_eq!(sigpro::deinterleave(&vdata, 8), vec![Vec::<f32>::new(); 8]);
+    assert!(sigpro::interleave(&[vec![1.0f32], vec![1.0, 2.0]]).is_err());
+    assert!(sigpro::interleave::<&[f32]>(&[]).unwrap().is_empty());
+    let slices: [&[f32]; 3] = [&[1.0], &[2.0], &[3.0]];
+    assert_eq!(sigpro::interleave(&slices).unwrap(), vec![1.0, 2.0, 3.0]);
+    // per channel filtering
+    let vout = sigpro::map_channels(&vdata, 2, |_, c| sigpro::sw_average(c, 2)).unwrap();
+    assert_eq!(vout, sigpro::interleave(&[sigpro::sw_average(&vchannels[0], 2), sigpro::sw_average(&vchannels[1], 2)]).unwrap());
+    let vout = sigpro::map_channels(&vdata, 2, |ch, c| {
+        let mut fir = sigpro::FirFilter::new(&[0.5, 0.5]).unwrap();
+        c.iter().filter_map(|s| fir.push(*s)).map(|v| v + ch as f32).collect()
+    }).unwrap();
+    assert_eq!(vout, vec![1.5, 16.0, 2.5, 26.0]);
+    assert!(sigpro::map_channels(&vdata, 2, |ch, c| c[ch..].to_vec()).is_err());
+}
+
+pub fn test_samples() {
+    use crate::sigpro::samples;
+    assert_eq!((samples::u8_to_f32(0), samples::u8_to_f32(128), samples::u8_to_f32(255)), (-1.0, 0.0, 127.0/128.0));
+    assert_eq!((samples::i16_to_f32(i16::MIN), samples::i16_to_f32(0), samples::i16_to_f32(16384)), (-1.0, 0.0, 0.5));
+    assert_eq!(samples::i24_bytes_to_f32([0x00, 0x00, 0x80]), -1.0);
+    assert_eq!(samples::i24_bytes_to_f32([0x00, 0x00, 0x40]), 0.5);
+    assert_eq!(samples::i24_bytes_to_f32([0xFF, 0xFF, 0xFF]), -1.0/8388608.0);
+    // clipping, rounding and NaN
+    assert_eq!((samples::f32_to_i16(1.0), samples::f32_to_i16(2.0), samples::f32_to_i16(-3.0)), (i16::MAX, i16::MAX, i16::MIN));
+    assert_eq!((samples::f32_to_i16(f32::NAN), samples::f32_to_i16(0.5), samples::f32_to_i16(-0.5)), (0, 16384, -16384));
+    assert_eq!((samples::f32_to_u8(-1.0), samples::f32_to_u8(0.0), samples::f32_to_u8(1.0)), (0, 128, 255));
+    assert_eq!(samples::f32_to_i24_bytes(1.5), [0xFF, 0xFF, 0x7F]);
+    assert_eq!(samples::f32_to_i24_bytes(-1.0), [0x00, 0x00, 0x80]);
+    assert_eq!(samples::f32_to_i24_bytes(f32::NAN), [0, 0, 0]);
+    // round trips are exact
+    for s in [i16::MIN, -1234, -1, 0, 1, 5678, i16::MAX] {
+        assert_eq!(samples::f32_to_i16(samples::i16_to_f32(s)), s);
+    }
+    for s in 0..=255u8 {
+        assert_eq!(samples::f32_to_u8(samples::u8_to_f32(s)), s);
+    }
+    for b in [[0x12, 0x34, 0x56], [0xFF, 0xFF, 0xFF], [0x00, 0x00, 0x80], [0xFF, 0xFF, 0x7F]] {
+        assert_eq!(samples::f32_to_i24_bytes(samples::i24_bytes_to_f32(b)), b);
+    }
+    du_log!(info, "TEST:Samples:Done");
+}
+
+pub fn test_runningpercentile() {
+    use crate::sigpro::RunningPercentile;
+    let mut rp = RunningPercentile::new(0.5).unwrap();
+    assert_eq!(rp.estimate(), None);
+    for v in [3.0, 1.0, f32::NAN, 2.0] {
+        rp.push(v);
+    }
+    assert_eq!((rp.count(), rp.estimate()), (3, Some(2.0)));
+    let mut rng = seeded_rng(1993);
+    for p in [0.5, 0.9, 0.95, 0.99] {
+        let mut rp = RunningPercentile::new(p).unwrap();
+        let mut vall = Vec::new();
+        for _ in 0..20000 {
+            // a skewed distribution, with values in 0..100
+            let u = rng.next_f64();
+            let v = (u*u*100.0) as f32;
+            rp.push(v);
+            vall.push(v);
+        }
+        vall.sort_by(|a, b| a.total_cmp(b));
+        let exact = vall[(p*vall.len() as f64) as usize - 1];
+        let est = rp.estimate().unwrap();
+        du_log!(info, "TEST:RunningPercentile:P{}:Est:{}:Exact:{}", p*100.0, est, exact);
+        assert!((est - exact).abs() < 1.0, "p {} est {} exact {}", p, est, exact);
+    }
+    // monotonic input
+    let mut rp = RunningPercentile::new(0.95).unwrap();
+    for i in 0..1000 {
+        rp.push(i as f32);
+    }
+    assert!((rp.estimate().unwrap() - 950.0).abs() < 5.0);
+    rp.reset();
+    assert_eq!((rp.count(), rp.estimate()), (0, None));
+    assert!(RunningPercentile::new(0.0).is_err());
+    assert!(RunningPercentile::new(1.0).is_err());
+    assert!(RunningPercentile::new(f64::NAN).is_err());
+}
+
+pub fn test_vecmad() {
+    use crate::sigpro::SliceNumExt;
+    let vdata = [1.0f32, 1.0, 2.0, 2.0, 4.0, 6.0, 9.0];
+    // median 2, deviations 1 1 0 0 2 4 7, their median 1
+    assert_eq!(sigpro::vec_mad(&vdata), Some(1.0));
+    assert_eq!(vdata.mad(), Some(1.0));
+    assert_eq!(sigpro::vec_mad(&[5i32, 1, 9, 3]), Some(2.0));
+    assert_eq!(sigpro::vec_mad::<f32>(&[]), None);
+    // a outlier barely moves the mad, while it blows up the std deviation
+    let vclean: Vec<f32> = (0..20).map(|i| 10.0 + (i % 5) as f32).collect();
+    let mut vdirty = vclean.clone();
+    vdirty[7] = 1000.0;
+    let (mclean, mdirty) = (sigpro::vec_mad(&vclean).unwrap(), sigpro::vec_mad(&vdirty).unwrap());
+    du_log!(info, "TEST:VecMad:{}:{}", mclean, mdirty);
+    assert!((mclean - mdirty).abs() <= 1.0);
+    let vz = sigpro::robust_zscores(&vdirty).unwrap();
+    let voutliers: Vec<usize> = vz.iter().enumerate().filter(|(_, z)| z.abs() > 3.5).map(|(i, _)| i).collect();
+    assert_eq!(voutliers, vec![7]);
+    assert_eq!(sigpro::robust_zscores(&[1.0f32, 2.0, 3.0]).unwrap(), vec![-0.6745, 0.0, 0.6745]);
+    assert!(sigpro::robust_zscores(&[3.0f32, 3.0, 3.0, 9.0]).is_none());
+    assert!(sigpro::robust_zscores::<f32>(&[]).is_none());
+}
+
+pub fn test_globmatch() {
+    let tests = [
+        ("*.rs", "lib.rs", true), ("*.rs", "lib.rsx", false), ("test_??", "test_01", true), ("test_??", "test_1", false),
+        ("case[0-9]*", "case7_long", true), ("case[0-9]*", "casex", false), ("[!a-c]x", "dx", true), ("[^a-c]x", "bx", false),
+        ("[]a]", "]", true), ("[!]a]", "b", true), ("a*b*c", "aXbYbZc", true), ("a*b*c", "aXbYbZ", false),
+        ("*", "", true), ("", "", true), ("", "a", false), ("**a**", "bab", true), ("a\\*b", "a*b", true),
+        ("a\\*b", "aXb", false), ("[ab", "[ab", true), ("ü?", "üñ", true), ("*[xyz]", "helloz", true),
+        ("id_[0-9][0-9]_*", "id_42_final", true), ("id_[0-9][0-9]_*", "id_4x_final", false),
+    ];
+    for (pattern, text, exp) in tests {
+        du_log!(info, "TEST:GlobMatch:{}:{}:{}", pattern, text, exp);
+        assert_eq!(text::glob_match(pattern, text), exp, "pattern {} text {}", pattern, text);
+    }
+    assert!(text::glob_match_slice(b"\x01?\x03*", &[1, 0xFF, 3, 4, 5]));
+    assert!(!text::glob_match_slice(b"[\x00-\x0f]", &[0x10]));
+    // pathological patterns dont blow up
+    let text = "a".repeat(200);
+    assert!(!text::glob_match("*a*a*a*a*a*a*a*b", &text));
+}
+
+pub fn test_naturalcmp() {
+    use core::cmp::Ordering;
+    let mut vnames = vec!["case10", "case2", "case1", "case02_b", "case2_a", "Case3", "case", "case001", "v1.10.2", "v1.9.10", "v1.9.9"];
+    vnames.sort_by(|a, b| text::natural_cmp(a, b));
+    du_log!(info, "TEST:NaturalCmp:{:?}", vnames);
+    assert_eq!(vnames, vec!["Case3", "case", "case001", "case1", "case2", "case2_a", "case02_b", "case10", "v1.9.9", "v1.9.10", "v1.10.2"]);
+    assert_eq!(text::natural_cmp("a2", "a10"), Ordering::Less);
+    assert_eq!(text::natural_cmp("a10", "a2"), Ordering::Greater);
+    assert_eq!(text::natural_cmp("a10b", "a10b"), Ordering::Equal);
+    assert_eq!(text::natural_cmp("a01", "a1"), "a01".cmp("a1"));
+    assert_eq!(text::natural_cmp("x00", "x0"), "x00".cmp("x0"));
+    assert_eq!(text::natural_cmp("", "a"), Ordering::Less);
+    assert_eq!(text::natural_cmp("99999999999999999999999", "100000000000000000000000"), Ordering::Less);
+    assert_eq!(text::natural_cmp("file-2", "file-10"), Ordering::Less);
+}
+
+pub fn test_jarowinkler() {
+    let tests = [
+        ("MARTHA", "MARHTA", 0.9611), ("DWAYNE", "DUANE", 0.84), ("DIXON", "DICKSONX", 0.8133),
+        ("JELLYFISH", "SMELLYFISH", 0.8963), ("abc", "abc", 1.0), ("abc", "xyz", 0.0), ("", "", 1.0), ("a", "", 0.0),
+    ];
+    for (a, b, exp) in tests {
+        let sim = text::jaro_winkler(a, b);
+        du_log!(info, "TEST:JaroWinkler:{}:{}:{}", a, b, sim);
+        assert!((sim - exp).abs() < 1e-3, "{} {} got {} expected {}", a, b, sim, exp);
+        assert!((text::jaro_winkler(b, a) - sim).abs() < 1e-9);
+    }
+    assert!((text::jaro_winkler_slice(b"\x01\x02\x03\x04", b"\x01\x02\x04\x03") - text::jaro_winkler("abcd", "abdc")).abs() < 1e-9);
+    // near duplicates score higher than unrelated inputs
+    assert!(text::jaro_winkler("fuzz_input_0001", "fuzz_input_0002") > text::jaro_winkler("fuzz_input_0001", "seed_corpus_x"));
+    let sim = text::jaro_winkler("üñîçødé", "üñîcode");
+    assert!(sim > 0.7 && sim < 1.0);
 }
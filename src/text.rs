@@ -0,0 +1,1186 @@
+//!
+//! Text related utility functions, like tokenising a line of text
+//!
+//! HanishKVC, 2022
+//!
+
+use core::cmp::Ordering;
+use core::fmt::Display;
+use core::ops::Range;
+use core::str::Chars;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+#[allow(unused_imports)]
+use crate::alloc_prelude::*;
+
+use crate::integer;
+
+
+///
+/// Decode the escape sequence, whose escape char has already been consumed.
+///
+/// * \n, \t, \r, \0, \\, \", \'
+/// * \xNN - the char with the given 2 digit hex code point
+/// * \u{N..} - the unicode char with the given hex code point
+///
+/// Returns None if there is nothing after the escape char. Any other char
+/// following the escape char (or a malformed \x or \u sequence) is returned
+/// as a Err, with the char to use as is.
+///
+fn decode_escape(chars: &mut Chars) -> Option<Result<char, char>> {
+    let c = chars.next()?;
+    let dc = match c {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        '0' => '\0',
+        '\\' | '"' | '\'' => c,
+        'x' => {
+            let mut shex = String::new();
+            for _ in 0..2 {
+                match chars.clone().next() {
+                    Some(h) if h.is_ascii_hexdigit() => {
+                        shex.push(h);
+                        chars.next();
+                    }
+                    _ => break,
+                }
+            }
+            match u8::from_str_radix(&shex, 16) {
+                Ok(u) => u as char,
+                Err(_) => return Some(Err('x')),
+            }
+        }
+        'u' => {
+            if !chars.as_str().starts_with('{') {
+                return Some(Err('u'));
+            }
+            chars.next();
+            let mut shex = String::new();
+            for h in chars.by_ref() {
+                if h == '}' {
+                    break;
+                }
+                shex.push(h);
+            }
+            // from_str_radix allows a leading + sign, so check for hex digits explicitly
+            if !shex.chars().all(|h| h.is_ascii_hexdigit()) {
+                return Some(Err(char::REPLACEMENT_CHARACTER));
+            }
+            match u32::from_str_radix(&shex, 16).ok().and_then(char::from_u32) {
+                Some(uc) => uc,
+                None => return Some(Err(char::REPLACEMENT_CHARACTER)),
+            }
+        }
+        _ => return Some(Err(c)),
+    };
+    Some(Ok(dc))
+}
+
+/// The problems that can be found in a line, when tokenising it strictly.
+///
+/// The columns are byte offsets (0 based) within the line, same as Token spans.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenError {
+    /// a quote was not closed before the end of the line
+    UnterminatedQuote { start_col: usize },
+    /// a bracketed group was not closed before the end of the line
+    UnterminatedGroup { start_col: usize },
+    /// a escape sequence is unknown or malformed
+    BadEscape { col: usize },
+}
+
+impl Display for TokenError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TokenError::UnterminatedQuote { start_col } => write!(f, "ERRR:DU:Text:Token:Unterminated quote starting at col {}", start_col),
+            TokenError::UnterminatedGroup { start_col } => write!(f, "ERRR:DU:Text:Token:Unterminated group starting at col {}", start_col),
+            TokenError::BadEscape { col } => write!(f, "ERRR:DU:Text:Token:Bad escape sequence at col {}", col),
+        }
+    }
+}
+
+///
+/// Get the next token from the given string, along with the remaining string.
+///
+/// Tokens are seperated by whitespace. A token starting with a double quote
+/// extends till the matching closing double quote, and includes the quotes.
+/// Escape sequences (\n, \t, \\, \", \xNN, \u{...}) within a quoted token
+/// are decoded. Balanced bracketed groups like (...), [...], {...} are kept
+/// together (as is) within a token, even if they contain whitespace.
+///
+pub fn next_token(ins: &str) -> (String, String) {
+    let mut toker = Tokenizer::new(ins);
+    match toker.next() {
+        Some(tok) => {
+            let stok = match tok.kind {
+                TokenKind::Quoted => format!("\"{}\"", tok.text),
+                _ => tok.text,
+            };
+            (stok, ins[tok.span.end..].to_string())
+        }
+        None => (String::new(), String::new()),
+    }
+}
+
+///
+/// Get the next token from the given string, along with the remaining string,
+/// in the same way as next_token. However unlike next_token, which silently
+/// returns whatever it could make out, this returns a error if the token has a
+/// unterminated quote or a bad escape sequence in it.
+///
+pub fn next_token_checked(ins: &str) -> Result<(String, String), TokenError> {
+    let mut toker = Tokenizer::new(ins);
+    match toker.next_checked()? {
+        Some(tok) => {
+            let stok = match tok.kind {
+                TokenKind::Quoted => format!("\"{}\"", tok.text),
+                _ => tok.text,
+            };
+            Ok((stok, ins[tok.span.end..].to_string()))
+        }
+        None => Ok((String::new(), String::new())),
+    }
+}
+
+///
+/// Get the next token from the given string, along with the remaining string,
+/// without building new Strings, ie both are slices of the given string.
+///
+/// The token is returned as is, ie escape sequences are not decoded and quoted
+/// tokens include their quotes. Like next_token_checked, it returns a error if
+/// the token has a unterminated quote or a bad escape sequence in it.
+///
+pub fn next_token_ref(ins: &str) -> Result<(&str, &str), TokenError> {
+    let mut toker = Tokenizer::new(ins);
+    match toker.next_ref()? {
+        Some(tok) => Ok((tok, toker.remaining())),
+        None => Ok(("", "")),
+    }
+}
+
+/// The kind of a token
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    /// a whitespace delimited word
+    Word,
+    /// a double quoted string, with escape sequences decoded
+    Quoted,
+    /// a word which is a integer (decimal or 0x hex) or float literal
+    Number,
+    /// a balanced bracketed group like (...), [...] or {...}, kept as is
+    Group,
+}
+
+/// A token along with its kind and its byte span in the original line.
+///
+/// For Quoted tokens, the text doesnt include the quotes, while the span does.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub text: String,
+    pub kind: TokenKind,
+    pub span: Range<usize>,
+}
+
+/// Control how a Tokenizer splits a line into tokens.
+///
+/// The default config matches the rules of next_token.
+#[derive(Debug, Clone)]
+pub struct TokenizerConfig {
+    /// the chars which can start (and end) a quoted token
+    pub quote_chars: Vec<char>,
+    /// the char which starts a escape sequence, None disables escape handling
+    pub escape_char: Option<char>,
+    /// whether escape sequences are decoded in non quoted tokens also
+    pub escape_outside_quotes: bool,
+    /// the chars which seperate tokens, a empty set means any whitespace
+    pub delimiters: Vec<char>,
+    /// the open and close chars of bracketed groups, delimiters within a
+    /// (possibly nested) group dont split the token
+    pub brackets: Vec<(char, char)>,
+    /// whether a escape char followed by a newline is treated as a line
+    /// continuation, ie both are dropped, as if they were not there
+    pub line_continuation: bool,
+}
+
+impl Default for TokenizerConfig {
+    fn default() -> Self {
+        TokenizerConfig {
+            quote_chars: vec!['"'],
+            escape_char: Some('\\'),
+            escape_outside_quotes: false,
+            delimiters: Vec::new(),
+            brackets: vec![('(', ')'), ('[', ']'), ('{', '}')],
+            line_continuation: true,
+        }
+    }
+}
+
+impl TokenizerConfig {
+
+    fn is_delimiter(&self, c: char) -> bool {
+        if self.delimiters.is_empty() {
+            return c.is_whitespace();
+        }
+        self.delimiters.contains(&c)
+    }
+
+    /// Skip any line continuations at the current position, returns true if any
+    fn skip_continuation(&self, chars: &mut Chars) -> bool {
+        let esc = match self.escape_char {
+            Some(esc) if self.line_continuation => esc,
+            _ => return false,
+        };
+        let mut skipped = false;
+        loop {
+            let rem = chars.as_str();
+            let after = match rem.strip_prefix(esc) {
+                Some(after) => after,
+                None => return skipped,
+            };
+            let nl = if after.starts_with('\n') {
+                1
+            } else if after.starts_with("\r\n") {
+                2
+            } else {
+                return skipped;
+            };
+            *chars = rem[esc.len_utf8()+nl..].chars();
+            skipped = true;
+        }
+    }
+
+}
+
+/// Iterate over the tokens in a line of text, as per the rules of next_token
+/// (or the given TokenizerConfig), while keeping track of where each token is
+/// in the line. A run of delimiters is treated as a single seperator.
+#[derive(Debug, Clone)]
+pub struct Tokenizer<'a> {
+    line: &'a str,
+    pos: usize,
+    config: TokenizerConfig,
+}
+
+impl<'a> Tokenizer<'a> {
+
+    pub fn new(line: &'a str) -> Tokenizer<'a> {
+        Self::with_config(line, TokenizerConfig::default())
+    }
+
+    pub fn with_config(line: &'a str, config: TokenizerConfig) -> Tokenizer<'a> {
+        Tokenizer { line, pos: 0, config }
+    }
+
+    /// The remaining part of the line, which is yet to be tokenised
+    pub fn remaining(&self) -> &'a str {
+        &self.line[self.pos..]
+    }
+
+    fn is_number(word: &str) -> bool {
+        let digits = word.strip_prefix(['-', '+']).unwrap_or(word);
+        if !digits.starts_with(|c: char| c.is_ascii_digit()) {
+            return false;
+        }
+        integer::try_intvalue::<isize>(word).is_ok() || word.parse::<f64>().is_ok()
+    }
+
+}
+
+/// The text of a token being scanned, which is built only if required
+struct TokText {
+    text: String,
+    keep: bool,
+}
+
+impl TokText {
+
+    fn push(&mut self, c: char) {
+        if self.keep {
+            self.text.push(c);
+        }
+    }
+
+}
+
+impl<'a> Tokenizer<'a> {
+
+    /// Get the next token, but fail if it has a unterminated quote or group,
+    /// or a bad escape sequence in it.
+    ///
+    /// Even on error, the tokenizer moves past the problematic token.
+    pub fn next_checked(&mut self) -> Result<Option<Token>, TokenError> {
+        match self.scan(true) {
+            Some((_, Some(err))) => Err(err),
+            Some((tok, None)) => Ok(Some(tok)),
+            None => Ok(None),
+        }
+    }
+
+    /// Decode a escape sequence, whose escape char is at col, into text.
+    /// Returns a error, if the escape sequence is unknown or malformed.
+    fn push_escape(&self, chars: &mut Chars, text: &mut TokText, col: usize) -> Option<TokenError> {
+        match decode_escape(chars) {
+            Some(Ok(dc)) => {
+                text.push(dc);
+                None
+            }
+            Some(Err(dc)) => {
+                text.push(dc);
+                if self.config.quote_chars.contains(&dc) || (Some(dc) == self.config.escape_char) {
+                    None
+                } else {
+                    Some(TokenError::BadEscape { col })
+                }
+            }
+            None => Some(TokenError::BadEscape { col }),
+        }
+    }
+
+    /// Get the next token as a slice of the line, as is, along with the remaining part
+    /// of the line. Escape sequences in the token are not decoded, so that no new
+    /// String needs to be built. Quoted tokens include their quotes.
+    ///
+    /// Fails if the token has a unterminated quote or group, or a bad escape sequence in it.
+    pub fn next_ref(&mut self) -> Result<Option<&'a str>, TokenError> {
+        match self.scan(false) {
+            Some((_, Some(err))) => Err(err),
+            Some((tok, None)) => Ok(Some(&self.line[tok.span])),
+            None => Ok(None),
+        }
+    }
+
+    /// Scan the next token, along with the first problem (if any) found in it.
+    /// The token's text is built only if keep_text is true.
+    fn scan(&mut self, keep_text: bool) -> Option<(Token, Option<TokenError>)> {
+        let cfg = &self.config;
+        let mut chars = self.remaining().chars();
+        loop {
+            if cfg.skip_continuation(&mut chars) {
+                continue;
+            }
+            match chars.clone().next() {
+                Some(c) if cfg.is_delimiter(c) => chars.next(),
+                _ => break,
+            };
+        }
+        let start = self.line.len() - chars.as_str().len();
+        let mut text = TokText { text: String::new(), keep: keep_text };
+        let mut oerr = None;
+        let kind;
+        let col = |chars: &Chars| self.line.len() - chars.as_str().len();
+        let oquote = chars.clone().next().filter(|c| cfg.quote_chars.contains(c));
+        if let Some(quote) = oquote {
+            chars.next();
+            kind = TokenKind::Quoted;
+            let mut closed = false;
+            loop {
+                if cfg.skip_continuation(&mut chars) {
+                    continue;
+                }
+                let c = match chars.next() {
+                    Some(c) => c,
+                    None => break,
+                };
+                if c == quote {
+                    closed = true;
+                    break;
+                }
+                if Some(c) == cfg.escape_char {
+                    let ecol = col(&chars) - c.len_utf8();
+                    let err = self.push_escape(&mut chars, &mut text, ecol);
+                    oerr = oerr.or(err);
+                    continue;
+                }
+                text.push(c);
+            }
+            if !closed {
+                oerr = oerr.or(Some(TokenError::UnterminatedQuote { start_col: start }));
+            }
+        } else {
+            // the close chars (and their cols) of the currently open bracketed groups,
+            // and any open quote within them
+            let mut vclose: Vec<(char, usize)> = Vec::new();
+            let mut oinquote: Option<(char, usize)> = None;
+            loop {
+                if cfg.skip_continuation(&mut chars) {
+                    continue;
+                }
+                let c = match chars.clone().next() {
+                    Some(c) => c,
+                    None => break,
+                };
+                if vclose.is_empty() && cfg.is_delimiter(c) {
+                    break;
+                }
+                let ccol = col(&chars);
+                chars.next();
+                if !vclose.is_empty() {
+                    text.push(c);
+                    if let Some((quote, _)) = oinquote {
+                        if Some(c) == cfg.escape_char {
+                            if let Some(ec) = chars.next() {
+                                text.push(ec);
+                            }
+                        } else if c == quote {
+                            oinquote = None;
+                        }
+                    } else if cfg.quote_chars.contains(&c) {
+                        oinquote = Some((c, ccol));
+                    } else if Some(c) == vclose.last().map(|x| x.0) {
+                        vclose.pop();
+                    } else if let Some(b) = cfg.brackets.iter().find(|b| b.0 == c) {
+                        vclose.push((b.1, ccol));
+                    }
+                    continue;
+                }
+                if let Some(b) = cfg.brackets.iter().find(|b| b.0 == c) {
+                    vclose.push((b.1, ccol));
+                    text.push(c);
+                    continue;
+                }
+                if cfg.escape_outside_quotes && (Some(c) == cfg.escape_char) {
+                    let err = self.push_escape(&mut chars, &mut text, ccol);
+                    oerr = oerr.or(err);
+                    continue;
+                }
+                text.push(c);
+            }
+            if let Some((_, qcol)) = oinquote {
+                oerr = oerr.or(Some(TokenError::UnterminatedQuote { start_col: qcol }));
+            }
+            if let Some((_, gcol)) = vclose.first() {
+                oerr = oerr.or(Some(TokenError::UnterminatedGroup { start_col: *gcol }));
+            }
+            let raw = &self.line[start..col(&chars)];
+            if raw.is_empty() {
+                self.pos = self.line.len();
+                return None;
+            }
+            kind = if cfg.brackets.iter().any(|b| raw.starts_with(b.0)) {
+                TokenKind::Group
+            } else if Self::is_number(if keep_text { &text.text } else { raw }) {
+                TokenKind::Number
+            } else {
+                TokenKind::Word
+            };
+        }
+        let end = col(&chars);
+        self.pos = end;
+        Some((Token { text: text.text, kind, span: start..end }, oerr))
+    }
+
+}
+
+impl Iterator for Tokenizer<'_> {
+    type Item = Token;
+
+    /// Get the next token, ignoring any problems in it, like a unterminated
+    /// quote (the token extends till the end of the line) or a unknown escape
+    /// sequence (the char following the escape char is used as is).
+    fn next(&mut self) -> Option<Token> {
+        self.scan(true).map(|x| x.0)
+    }
+
+}
+
+///
+/// Remove extra whitespaces in the given string, ie leading and trailing
+/// whitespaces are removed and runs of whitespace are replaced by a single
+/// space. Whitespaces within double quoted parts are retained as is.
+///
+pub fn remove_extra_whitespaces(ins: &str) -> String {
+    let mut outs = String::new();
+    let mut in_quote = false;
+    let mut escaped = false;
+    let mut pending_space = false;
+    for c in ins.trim().chars() {
+        if in_quote {
+            outs.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_quote = false;
+            }
+            continue;
+        }
+        if c.is_whitespace() {
+            pending_space = true;
+            continue;
+        }
+        if pending_space {
+            outs.push(' ');
+            pending_space = false;
+        }
+        if c == '"' {
+            in_quote = true;
+        }
+        outs.push(c);
+    }
+    outs
+}
+
+///
+/// Split a line of comma (or the given delimiter) seperated values, honoring
+/// RFC-4180 style quoting, ie
+///
+/// * a field starting with a double quote extends till the closing double quote,
+///   and can contain the delimiter within it
+/// * a doubled double quote within a quoted field stands for a single double quote
+/// * empty fields are retained
+///
+/// Any chars between a closing quote and the next delimiter are kept as is.
+///
+pub fn split_csv(line: &str, delimiter: char) -> Vec<String> {
+    let mut vfields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut at_start = true;
+    let mut in_quote = false;
+    while let Some(c) = chars.next() {
+        if in_quote {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    in_quote = false;
+                }
+            } else {
+                field.push(c);
+            }
+            continue;
+        }
+        if c == delimiter {
+            vfields.push(core::mem::take(&mut field));
+            at_start = true;
+            continue;
+        }
+        if at_start && (c == '"') {
+            in_quote = true;
+        } else {
+            field.push(c);
+        }
+        at_start = false;
+    }
+    vfields.push(field);
+    vfields
+}
+
+///
+/// Expand the ${name} placeholders in the given template, with the values
+/// returned by the resolver for the corresponding names.
+///
+/// * $$ stands for a literal $
+/// * a $ not followed by { or $ is kept as is
+///
+/// Its a error, if the resolver doesnt know a name, or if a placeholder is not closed.
+///
+pub fn substitute(template: &str, resolver: impl Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut outs = String::new();
+    let mut rem = template;
+    while let Some(i) = rem.find('$') {
+        outs.push_str(&rem[..i]);
+        let after = &rem[i+1..];
+        if let Some(after) = after.strip_prefix('$') {
+            outs.push('$');
+            rem = after;
+        } else if let Some(after) = after.strip_prefix('{') {
+            let ei = match after.find('}') {
+                Some(ei) => ei,
+                None => return Err(format!("ERRR:DU:Text:Substitute:Unterminated placeholder at {}", template.len()-rem.len()+i)),
+            };
+            let name = &after[..ei];
+            match resolver(name) {
+                Some(val) => outs.push_str(&val),
+                None => return Err(format!("ERRR:DU:Text:Substitute:Unknown name [{}]", name)),
+            }
+            rem = &after[ei+1..];
+        } else {
+            outs.push('$');
+            rem = after;
+        }
+    }
+    outs.push_str(rem);
+    Ok(outs)
+}
+
+///
+/// Join lines ending with a backslash (line continuation) with the line following
+/// them. The trailing backslash is dropped, while the following line is appended
+/// as is. A line ending with a escaped backslash (ie \\) is not continued.
+///
+/// This allows long statements to be split across multiple lines.
+///
+pub fn join_continued_lines<S: AsRef<str>>(lines: &[S]) -> Vec<String> {
+    join_continued_lines_at(lines).into_iter().map(|(_, line)| line).collect()
+}
+
+/// Same has join_continued_lines, but also returns the index of the line (in the
+/// passed lines) at which each joined line starts.
+fn join_continued_lines_at<S: AsRef<str>>(lines: &[S]) -> Vec<(usize, String)> {
+    let mut vlines = Vec::new();
+    let mut cur: Option<(usize, String)> = None;
+    for (i, line) in lines.iter().enumerate() {
+        let line = line.as_ref();
+        let trailing = line.len() - line.trim_end_matches('\\').len();
+        let (start, mut joined) = cur.take().unwrap_or((i, String::new()));
+        if trailing % 2 == 1 {
+            joined.push_str(&line[..line.len()-1]);
+            cur = Some((start, joined));
+        } else {
+            joined.push_str(line);
+            vlines.push((start, joined));
+        }
+    }
+    if let Some(last) = cur {
+        vlines.push(last);
+    }
+    vlines
+}
+
+///
+/// Extract the part enclosed by the first open char in the given string and its
+/// matching close char, returning the enclosed part and the part after the close char.
+///
+/// Nested open/close pairs are handled. Open/close chars within double quoted
+/// parts (with \ as the escape char) are ignored.
+///
+/// ex: extract_balanced("foo(a, \"b,c)\", (1,2)) rest", '(', ')')
+///     gives ("a, \"b,c)\", (1,2)", " rest")
+///
+pub fn extract_balanced(s: &str, open: char, close: char) -> Result<(&str, &str), String> {
+    let si = match s.find(open) {
+        Some(si) => si + open.len_utf8(),
+        None => return Err(format!("ERRR:DU:Text:ExtractBalanced:No [{}] in [{}]", open, s)),
+    };
+    let mut depth = 1;
+    let mut in_quote = false;
+    let mut escaped = false;
+    for (i, c) in s[si..].char_indices() {
+        if in_quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_quote = false;
+            }
+            continue;
+        }
+        if c == '"' {
+            in_quote = true;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return Ok((&s[si..si+i], &s[si+i+close.len_utf8()..]));
+            }
+        } else if c == open {
+            depth += 1;
+        }
+    }
+    Err(format!("ERRR:DU:Text:ExtractBalanced:Unbalanced [{}] at {} in [{}]", open, si - open.len_utf8(), s))
+}
+
+///
+/// Convert the given raw string into the double quoted and escaped form, which
+/// the tokenizer (and unescape_str) understands.
+///
+/// * \\, \", \n, \t, \r are escaped as such
+/// * other non printable chars upto 0xFF are escaped as \xNN
+/// * other non printable unicode chars are escaped as \u{N..}
+///
+pub fn escape_str(ins: &str) -> String {
+    let mut outs = String::with_capacity(ins.len()+2);
+    outs.push('"');
+    for c in ins.chars() {
+        match c {
+            '\\' => outs.push_str("\\\\"),
+            '"' => outs.push_str("\\\""),
+            '\n' => outs.push_str("\\n"),
+            '\t' => outs.push_str("\\t"),
+            '\r' => outs.push_str("\\r"),
+            c if c.is_control() && ((c as u32) <= 0xFF) => outs.push_str(&format!("\\x{:02X}", c as u32)),
+            c if c.is_control() => outs.push_str(&format!("\\u{{{:X}}}", c as u32)),
+            c => outs.push(c),
+        }
+    }
+    outs.push('"');
+    outs
+}
+
+///
+/// Convert the given double quoted and escaped string back into the raw string,
+/// ie the reverse of escape_str. If the given string is not enclosed in double
+/// quotes, its decoded as is.
+///
+/// The cols in the errors returned are byte offsets within the given string.
+///
+pub fn unescape_str(ins: &str) -> Result<String, TokenError> {
+    let (inner, offset) = match ins.strip_prefix('"') {
+        Some(rest) => match rest.strip_suffix('"') {
+            Some(inner) if (inner.len() - inner.trim_end_matches('\\').len()).is_multiple_of(2) => (inner, 1),
+            _ => return Err(TokenError::UnterminatedQuote { start_col: 0 }),
+        },
+        None => (ins, 0),
+    };
+    let mut outs = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            outs.push(c);
+            continue;
+        }
+        let col = offset + inner.len() - chars.as_str().len() - 1;
+        match decode_escape(&mut chars) {
+            Some(Ok(dc)) => outs.push(dc),
+            _ => return Err(TokenError::BadEscape { col }),
+        }
+    }
+    Ok(outs)
+}
+
+///
+/// Levenshtein (edit) distance btw the given sequences, ie the minimum number of
+/// single element insertions, deletions or substitutions to convert a into b.
+///
+/// The calculation stops early once the distance is known to exceed max_dist,
+/// in which case max_dist+1 is returned. Use usize::MAX for no limit.
+/// This works for byte buffers (&[u8]) has well has any other slices.
+///
+pub fn levenshtein_slice<T: PartialEq>(a: &[T], b: &[T], max_dist: usize) -> usize {
+    let over = max_dist.saturating_add(1);
+    if a.len().abs_diff(b.len()) > max_dist {
+        return over;
+    }
+    let mut vprev: Vec<usize> = (0..=b.len()).collect();
+    let mut vcur = vec![0; b.len()+1];
+    for (i, ea) in a.iter().enumerate() {
+        vcur[0] = i + 1;
+        let mut rowmin = vcur[0];
+        for (j, eb) in b.iter().enumerate() {
+            let cost = if ea == eb { 0 } else { 1 };
+            vcur[j+1] = (vprev[j] + cost).min(vprev[j+1] + 1).min(vcur[j] + 1);
+            rowmin = rowmin.min(vcur[j+1]);
+        }
+        if rowmin > max_dist {
+            return over;
+        }
+        core::mem::swap(&mut vprev, &mut vcur);
+    }
+    if vprev[b.len()] > max_dist {
+        return over;
+    }
+    vprev[b.len()]
+}
+
+/// Levenshtein distance btw the given strings, in terms of chars, refer to levenshtein_slice.
+pub fn levenshtein(a: &str, b: &str, max_dist: usize) -> usize {
+    let va: Vec<char> = a.chars().collect();
+    let vb: Vec<char> = b.chars().collect();
+    levenshtein_slice(&va, &vb, max_dist)
+}
+
+/// Match the pattern element at pi against the given text element, returning
+/// whether it matched and the index of the next pattern element, refer to glob_match_slice.
+fn glob_element<T: Copy + PartialOrd + From<u8>>(p: &[T], pi: usize, tc: T) -> (bool, usize) {
+    let lit = |c: u8| T::from(c);
+    let c = p[pi];
+    if c == lit(b'?') {
+        return (true, pi + 1);
+    }
+    if c == lit(b'\\') && pi + 1 < p.len() {
+        return (p[pi+1] == tc, pi + 2);
+    }
+    if c == lit(b'[') {
+        let mut i = pi + 1;
+        let negate = i < p.len() && (p[i] == lit(b'!') || p[i] == lit(b'^'));
+        if negate {
+            i += 1;
+        }
+        let mut matched = false;
+        let mut first = true;
+        while i < p.len() && (first || p[i] != lit(b']')) {
+            first = false;
+            if i + 2 < p.len() && p[i+1] == lit(b'-') && p[i+2] != lit(b']') {
+                matched |= p[i] <= tc && tc <= p[i+2];
+                i += 3;
+            } else {
+                matched |= p[i] == tc;
+                i += 1;
+            }
+        }
+        if i < p.len() {
+            return (matched != negate, i + 1);
+        }
+        // a unclosed [ is treated has a literal
+    }
+    (c == tc, pi + 1)
+}
+
+///
+/// Check if the given text matches the given glob pattern, where
+/// * `*` matches any sequence of elements (including none)
+/// * `?` matches any single element
+/// * `[abc]` matches any of the listed elements, `[a-z]` any element in the range, while
+///   `[!abc]` or `[^abc]` matches any element not listed. A `]` right after the `[` (or the
+///   negation) is treated has a listed element.
+/// * `\` makes the following element a literal
+///
+/// This works for byte buffers (&[u8]) has well has slices of chars.
+///
+pub fn glob_match_slice<T: Copy + PartialOrd + From<u8>>(pattern: &[T], text: &[T]) -> bool {
+    let star = T::from(b'*');
+    let (mut pi, mut ti) = (0, 0);
+    // where to resume, if the match after the last * fails, ie the pattern index after
+    // the * and the text index to be consumed by it
+    let mut resume: Option<(usize, usize)> = None;
+    while ti < text.len() {
+        if pi < pattern.len() {
+            if pattern[pi] == star {
+                resume = Some((pi + 1, ti));
+                pi += 1;
+                continue;
+            }
+            let (matched, next) = glob_element(pattern, pi, text[ti]);
+            if matched {
+                pi = next;
+                ti += 1;
+                continue;
+            }
+        }
+        match resume {
+            Some((rpi, rti)) => {
+                pi = rpi;
+                ti = rti + 1;
+                resume = Some((rpi, rti + 1));
+            },
+            None => return false,
+        }
+    }
+    pattern[pi..].iter().all(|c| *c == star)
+}
+
+/// Check if the given text matches the given glob pattern, in terms of chars, refer to glob_match_slice.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let vpattern: Vec<char> = pattern.chars().collect();
+    let vtext: Vec<char> = text.chars().collect();
+    glob_match_slice(&vpattern, &vtext)
+}
+
+///
+/// Compare the given strings, treating the runs of ascii digits within them has numbers,
+/// so that "case2" sorts before "case10". Other chars are compared has is.
+///
+/// Numbers are compared by value, irrespective of any leading zeros. If the strings are
+/// otherwise equal (like "a01" and "a1"), they are ordered using the normal string order,
+/// so that the result is a consistent total order, usable with sort_by.
+///
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (va, vb): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let (mut i, mut j) = (0, 0);
+    while i < va.len() && j < vb.len() {
+        if va[i].is_ascii_digit() && vb[j].is_ascii_digit() {
+            let digits = |v: &[char], mut k: usize| {
+                while k < v.len() && v[k] == '0' {
+                    k += 1;
+                }
+                let start = k;
+                while k < v.len() && v[k].is_ascii_digit() {
+                    k += 1;
+                }
+                (start, k)
+            };
+            let ((sa, ea), (sb, eb)) = (digits(&va, i), digits(&vb, j));
+            // without the leading zeros, a longer number is a bigger number
+            let ord = (ea - sa).cmp(&(eb - sb)).then_with(|| va[sa..ea].cmp(&vb[sb..eb]));
+            if ord != Ordering::Equal {
+                return ord;
+            }
+            (i, j) = (ea, eb);
+            continue;
+        }
+        let ord = va[i].cmp(&vb[j]);
+        if ord != Ordering::Equal {
+            return ord;
+        }
+        i += 1;
+        j += 1;
+    }
+    (va.len() - i).cmp(&(vb.len() - j)).then_with(|| a.cmp(b))
+}
+
+///
+/// Jaro-Winkler similarity btw the given sequences, in the range 0.0 (nothing in common)
+/// to 1.0 (same), which complements the levenshtein distance with a normalised measure.
+///
+/// It is the Jaro similarity (based on the elements common to both within a window and
+/// the transpositions among them), boosted for a common prefix (upto 4 elements, with
+/// a scale of 0.1). Two empty sequences are treated has same.
+/// This works for byte buffers (&[u8]) has well has any other slices.
+///
+pub fn jaro_winkler_slice<T: PartialEq>(a: &[T], b: &[T]) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let window = (a.len().max(b.len())/2).saturating_sub(1);
+    let mut vamatched = vec![false; a.len()];
+    let mut vbmatched = vec![false; b.len()];
+    let mut matches = 0;
+    for (i, ea) in a.iter().enumerate() {
+        let end = (i + window + 1).min(b.len());
+        for j in i.saturating_sub(window)..end {
+            if !vbmatched[j] && b[j] == *ea {
+                vamatched[i] = true;
+                vbmatched[j] = true;
+                matches += 1;
+                break;
+            }
+        }
+    }
+    if matches == 0 {
+        return 0.0;
+    }
+    // the matched elements, which are out of order btw a and b
+    let vbm = b.iter().zip(&vbmatched).filter(|(_, m)| **m).map(|(e, _)| e);
+    let vam = a.iter().zip(&vamatched).filter(|(_, m)| **m).map(|(e, _)| e);
+    let half_transpositions = vam.zip(vbm).filter(|(ea, eb)| ea != eb).count();
+    let m = matches as f64;
+    let jaro = (m/a.len() as f64 + m/b.len() as f64 + (m - (half_transpositions/2) as f64)/m)/3.0;
+    let prefix = a.iter().zip(b).take(4).take_while(|(ea, eb)| ea == eb).count();
+    jaro + prefix as f64*0.1*(1.0 - jaro)
+}
+
+/// Jaro-Winkler similarity btw the given strings, in terms of chars, refer to jaro_winkler_slice.
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let va: Vec<char> = a.chars().collect();
+    let vb: Vec<char> = b.chars().collect();
+    jaro_winkler_slice(&va, &vb)
+}
+
+/// The utf8 encoded bytes of the given char
+pub fn utf8_bytes_of(ch: char) -> Vec<u8> {
+    let mut abuf = [0u8; 4];
+    ch.encode_utf8(&mut abuf).as_bytes().to_vec()
+}
+
+/// How invalid utf8 sequences are handled by codepoints_from_utf8
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Utf8Mode {
+    /// Any invalid sequence is a error
+    Strict,
+    /// Invalid sequences are replaced with the unicode replacement char U+FFFD
+    Lossy,
+}
+
+/// Decode the given utf8 bytes into the unicode chars (codepoints) in it.
+///
+/// In Strict mode, the error mentions the byte offset of the invalid sequence.
+pub fn codepoints_from_utf8(buf: &[u8], mode: Utf8Mode) -> Result<Vec<char>, String> {
+    match mode {
+        Utf8Mode::Strict => match core::str::from_utf8(buf) {
+            Ok(s) => Ok(s.chars().collect()),
+            Err(e) => Err(format!("ERRR:DU:Text:CodepointsFromUtf8:Invalid utf8 at byte {}", e.valid_up_to())),
+        },
+        Utf8Mode::Lossy => Ok(String::from_utf8_lossy(buf).chars().collect()),
+    }
+}
+
+///
+/// Generate malformed utf8 variants of the given string, to help test how logics
+/// handle invalid utf8 at the char boundaries. Each variant is invalid utf8.
+///
+/// * a lone continuation byte inserted at the start and at the end
+/// * the last multibyte char (if any) truncated by a byte
+/// * a lead byte without its continuation bytes at the end
+/// * the first char (if ascii) replaced with its overlong 2 byte encoding
+/// * a utf16 surrogate (U+D800) encoded has utf8, at the end
+/// * the never valid 0xFF byte inserted after the first char
+///
+pub fn malformed_utf8_variants(ins: &str) -> Vec<Vec<u8>> {
+    let bins = ins.as_bytes();
+    let mut vvariants = Vec::new();
+    vvariants.push([&[0x80], bins].concat());
+    vvariants.push([bins, &[0xBF]].concat());
+    if let Some((i, ch)) = ins.char_indices().rev().find(|(_, ch)| ch.len_utf8() > 1) {
+        let mut v = bins[..i + ch.len_utf8() - 1].to_vec();
+        v.extend_from_slice(&bins[i + ch.len_utf8()..]);
+        vvariants.push(v);
+    }
+    vvariants.push([bins, &[0xE2, 0x82]].concat());
+    if let Some(ch) = ins.chars().next().filter(|ch| ch.is_ascii()) {
+        let cp = ch as u8;
+        vvariants.push([&[0xC0 | (cp >> 6), 0x80 | (cp & 0x3F)], &bins[1..]].concat());
+    }
+    vvariants.push([bins, &[0xED, 0xA0, 0x80]].concat());
+    let first = ins.chars().next().map(|ch| ch.len_utf8()).unwrap_or(0);
+    vvariants.push([&bins[..first], &[0xFF], &bins[first..]].concat());
+    vvariants
+}
+
+/// Maximum nesting of !include, to catch include loops
+#[cfg(feature = "std")]
+const PREPROCESSOR_MAX_INCLUDE_DEPTH: usize = 16;
+
+///
+/// A simple line oriented preprocessor, which flattens a set of lines, handling
+///
+/// * !include path - the lines of the given file (relative to the including file)
+/// * !define NAME value - NAME (as a whole word, outside double quotes) in the
+///   following lines is replaced with value
+/// * !undef NAME
+/// * !ifdef NAME / !ifndef NAME, !else, !endif - conditional blocks, which can be nested
+///
+/// Continued lines (trailing backslash) are joined before processing.
+///
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+pub struct Preprocessor {
+    defines: HashMap<String, String>,
+}
+
+#[cfg(feature = "std")]
+impl Preprocessor {
+
+    pub fn new() -> Preprocessor {
+        Preprocessor::default()
+    }
+
+    /// Define a macro, as if a !define was seen
+    pub fn define(&mut self, name: &str, value: &str) {
+        self.defines.insert(name.to_string(), value.to_string());
+    }
+
+    /// Process the given file, including the files it includes.
+    pub fn process_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<String>, String> {
+        self.process_file_at(path.as_ref(), 0)
+    }
+
+    /// Process the given lines, with any included file paths being relative to base_dir.
+    pub fn process_lines<S: AsRef<str>>(&mut self, lines: &[S], base_dir: &Path) -> Result<Vec<String>, String> {
+        self.process_lines_at(lines, base_dir, "lines", 0)
+    }
+
+    fn process_file_at(&mut self, path: &Path, depth: usize) -> Result<Vec<String>, String> {
+        if depth > PREPROCESSOR_MAX_INCLUDE_DEPTH {
+            return Err(format!("ERRR:DU:Text:PreProc:Include depth beyond {}, loop?:{}", PREPROCESSOR_MAX_INCLUDE_DEPTH, path.display()));
+        }
+        let sdata = match fs::read_to_string(path) {
+            Ok(sdata) => sdata,
+            Err(err) => return Err(format!("ERRR:DU:Text:PreProc:Reading {}:{}", path.display(), err)),
+        };
+        du_log!(debug, "DBUG:DU:Text:PreProc:Processing {}, depth {}", path.display(), depth);
+        let lines: Vec<&str> = sdata.lines().collect();
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        self.process_lines_at(&lines, base_dir, &path.display().to_string(), depth)
+    }
+
+    fn process_lines_at<S: AsRef<str>>(&mut self, lines: &[S], base_dir: &Path, src: &str, depth: usize) -> Result<Vec<String>, String> {
+        let mut vout = Vec::new();
+        // For each open conditional block: (is this block active, has else been seen)
+        let mut vconds: Vec<(bool, bool)> = Vec::new();
+        // li is the index of the source line, at which the (joined) line starts
+        for (li, line) in join_continued_lines_at(lines).iter() {
+            let active = vconds.iter().all(|c| c.0);
+            let err = |msg: &str| format!("ERRR:DU:Text:PreProc:{}:{}:{}", src, li+1, msg);
+            let tline = line.trim();
+            if !tline.starts_with('!') {
+                if active {
+                    vout.push(self.expand(line));
+                }
+                continue;
+            }
+            let (directive, rest) = match tline.split_once(char::is_whitespace) {
+                Some((d, r)) => (d, r.trim()),
+                None => (tline, ""),
+            };
+            match directive {
+                "!ifdef" | "!ifndef" => {
+                    let defined = self.defines.contains_key(rest);
+                    vconds.push((defined == (directive == "!ifdef"), false));
+                }
+                "!else" => {
+                    match vconds.last_mut() {
+                        Some(c) if !c.1 => *c = (!c.0, true),
+                        _ => return Err(err("!else without matching !ifdef/!ifndef")),
+                    }
+                }
+                "!endif" => {
+                    if vconds.pop().is_none() {
+                        return Err(err("!endif without matching !ifdef/!ifndef"));
+                    }
+                }
+                _ if !active => (),
+                "!include" => {
+                    let path = rest.trim_matches('"');
+                    let mut vinc = self.process_file_at(&base_dir.join(path), depth+1)?;
+                    vout.append(&mut vinc);
+                }
+                "!define" => {
+                    let (name, value) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                    if name.is_empty() {
+                        return Err(err("!define without a name"));
+                    }
+                    self.define(name, value.trim());
+                }
+                "!undef" => {
+                    self.defines.remove(rest);
+                }
+                _ => return Err(err(&format!("Unknown directive {}", directive))),
+            }
+        }
+        if !vconds.is_empty() {
+            return Err(format!("ERRR:DU:Text:PreProc:{}:Missing !endif", src));
+        }
+        Ok(vout)
+    }
+
+    /// Replace the defined names (as whole words, outside double quotes) in the line
+    fn expand(&self, line: &str) -> String {
+        if self.defines.is_empty() {
+            return line.to_string();
+        }
+        let is_word = |c: char| c.is_alphanumeric() || (c == '_');
+        let mut outs = String::with_capacity(line.len());
+        let mut word = String::new();
+        let mut in_quote = false;
+        let mut escaped = false;
+        for c in line.chars() {
+            if !in_quote && is_word(c) {
+                word.push(c);
+                continue;
+            }
+            if !word.is_empty() {
+                outs.push_str(self.defines.get(&word).unwrap_or(&word));
+                word.clear();
+            }
+            outs.push(c);
+            if in_quote {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == '"' {
+                    in_quote = false;
+                }
+            } else if c == '"' {
+                in_quote = true;
+            }
+        }
+        if !word.is_empty() {
+            outs.push_str(self.defines.get(&word).unwrap_or(&word));
+        }
+        outs
+    }
+
+}
@@ -3,20 +3,88 @@
 //! HanishKVC, 2022
 //!
 
-use std::fmt::Display;
-use std::time;
+use core::fmt::Display;
+use core::ops::{Deref, Range};
+use alloc::sync::Arc;
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use std::io::BufRead;
 
 use crate::hex;
 use crate::integer;
+use crate::clock::{Clock, SystemClock};
+use crate::error::DataUtilsError;
+#[allow(unused_imports)]
+use crate::alloc_prelude::*;
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum VDataType {
     Unknown,
     Integer,
     String,
     Buffer,
     Special,
+    List,
+    Map,
+}
+
+
+///
+/// A view (start..end) into a byte buffer which is shared (reference counted), so that
+/// cloning or slicing it doesnt copy the underlying data.
+///
+#[derive(Debug, Clone)]
+pub struct SharedBytes {
+    buf: Arc<Vec<u8>>,
+    start: usize,
+    end: usize,
+}
+
+impl SharedBytes {
+
+    pub fn new(buf: Vec<u8>) -> SharedBytes {
+        SharedBytes::from_arc(Arc::new(buf))
+    }
+
+    /// A view of the whole of the given shared buffer
+    pub fn from_arc(buf: Arc<Vec<u8>>) -> SharedBytes {
+        let end = buf.len();
+        SharedBytes { buf, start: 0, end }
+    }
+
+    /// A view of the given range (relative to this view) of the data, which shares
+    /// the underlying buffer. None if the range is not within this view.
+    pub fn slice(&self, range: Range<usize>) -> Option<SharedBytes> {
+        if range.start > range.end || range.end > self.len() {
+            return None;
+        }
+        Some(SharedBytes { buf: self.buf.clone(), start: self.start + range.start, end: self.start + range.end })
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buf[self.start..self.end]
+    }
+
+    /// The number of views (including this one) sharing the underlying buffer
+    pub fn share_count(&self) -> usize {
+        Arc::strong_count(&self.buf)
+    }
+
+}
+
+impl Deref for SharedBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl From<Vec<u8>> for SharedBytes {
+    fn from(buf: Vec<u8>) -> Self {
+        SharedBytes::new(buf)
+    }
 }
 
 
@@ -30,11 +98,20 @@ pub enum VDataType {
 /// The logic tries to convert the stored data into the
 /// requested type in a predefined and potentially sane
 /// way, which should be fine in many cases.
+///
+/// ListValue and MapValue allow structured (nested) data to be maintained,
+/// refer to VariantBuilder for a easy way to create them.
+///
+/// SharedBuf is a buffer whose data is shared (without copying) with other variants,
+/// which helps when a large captured payload is sliced into many variants.
 pub enum Variant {
     IntValue(isize),
     StrValue(String),
     BufValue(Vec<u8>),
+    SharedBuf(SharedBytes),
     XTimeStamp,
+    ListValue(Vec<Variant>),
+    MapValue(BTreeMap<String, Variant>),
 }
 
 impl Variant {
@@ -43,8 +120,10 @@ impl Variant {
         match self {
             Variant::IntValue(_) => VDataType::Integer,
             Variant::StrValue(_) => VDataType::String,
-            Variant::BufValue(_) => VDataType::Buffer,
+            Variant::BufValue(_) | Variant::SharedBuf(_) => VDataType::Buffer,
             Variant::XTimeStamp => VDataType::Special,
+            Variant::ListValue(_) => VDataType::List,
+            Variant::MapValue(_) => VDataType::Map,
         }
     }
 
@@ -53,14 +132,20 @@ impl Variant {
     /// * String -> Try interpret the string as a textual literal value of a integer
     /// * Buf -> Try interpret the buf as the underlying raw byte values of a integer
     /// * XTimeStamp -> milliseconds from UnixEpoch truncated
+    /// * List and Map -> error
     ///
     pub fn get_isize(&self) -> Result<isize, String> {
+        self.get_isize_with_clock(&SystemClock)
+    }
+
+    /// Same has get_isize, but XTimeStamp uses the given clock, rather than the system clock
+    pub fn get_isize_with_clock(&self, clock: &dyn Clock) -> Result<isize, String> {
         match self {
             Self::IntValue(ival) => {
                 return Ok(*ival);
             },
             Self::StrValue(sval) => {
-                let ival = integer::intvalue(sval);
+                let ival = integer::try_intvalue::<isize>(sval);
                 if ival.is_ok() {
                     return Ok(ival.unwrap());
                 }
@@ -74,17 +159,25 @@ impl Variant {
                 }
                 return Err(format!("Variant:GetISize:BufValue:[{:?}]:Adapting buf for int failed? Wrong number of bytes or?:{}", bval, bsval.unwrap_err()));
             },
+            Self::SharedBuf(sbuf) => {
+                match sbuf.as_slice().try_into() {
+                    Ok(bval) => Ok(isize::from_ne_bytes(bval)),
+                    Err(e) => Err(format!("Variant:GetISize:SharedBuf:[{:?}]:Adapting buf for int failed? Wrong number of bytes or?:{}", sbuf.as_slice(), e)),
+                }
+            },
             Self::XTimeStamp => {
-                let ts = time::SystemTime::now().duration_since(time::UNIX_EPOCH).unwrap();
-                let uts = ts.as_millis();
+                let uts = clock.now_ms();
                 return Ok(uts as isize);
             },
+            Self::ListValue(_) | Self::MapValue(_) => {
+                Err(format!("Variant:GetISize:{:?}:Not convertable to int", self.get_type()))
+            },
         }
     }
 
     ///
     /// Return a unsigned (ie positive interger value), this is built upon
-    /// get_isize. If the underlying value is negative, then it will panic
+    /// get_isize. If the underlying value is negative, then it returns a error
     ///
     #[allow(dead_code)]
     fn get_usize(&self) -> Result<usize, String> {
@@ -92,7 +185,7 @@ impl Variant {
         match ival {
             Ok(ival) => {
                 if ival < 0 {
-                    panic!("Variant:GetUSize: Negative int value not supported here")
+                    return Err(format!("Variant:GetUSize:Negative int value {} not supported here", ival));
                 }
                 return Ok(ival as usize);
             }
@@ -105,8 +198,15 @@ impl Variant {
     /// * Returns String as is
     /// * Returns Buf8 data as a hex string
     /// * XTimeStamp returns current System time converted to milliseconds since UNIX Epoch, as a string
+    /// * List returns [elem1, elem2, ...] and Map returns {key1: value1, ...}, with each element/value
+    ///   in turn as returned by its get_string
     ///
     pub fn get_string(&self) -> String {
+        self.get_string_with_clock(&SystemClock)
+    }
+
+    /// Same has get_string, but XTimeStamp uses the given clock, rather than the system clock
+    pub fn get_string_with_clock(&self, clock: &dyn Clock) -> String {
         match self {
             Self::IntValue(ival) => {
                 return ival.to_string();
@@ -117,23 +217,143 @@ impl Variant {
             Self::BufValue(bval) => {
                 return hex::hex_from_vu8(bval);
             },
+            Self::SharedBuf(sbuf) => {
+                hex::hex_from_vu8(sbuf.as_slice())
+            },
             Self::XTimeStamp => {
-                let ts = time::SystemTime::now().duration_since(time::UNIX_EPOCH).unwrap();
-                let uts = ts.as_millis();
+                let uts = clock.now_ms();
                 return uts.to_string();
             },
+            Self::ListValue(lval) => {
+                let vs: Vec<String> = lval.iter().map(|v| v.get_string_with_clock(clock)).collect();
+                format!("[{}]", vs.join(", "))
+            },
+            Self::MapValue(mval) => {
+                let vs: Vec<String> = mval.iter().map(|(k, v)| format!("{}: {}", k, v.get_string_with_clock(clock))).collect();
+                format!("{{{}}}", vs.join(", "))
+            },
          }
     }
 
+    ///
+    /// Render the integer value (refer get_isize) of the variant in the given radix (2 to 36),
+    /// padded to atleast width chars using the given pad char.
+    ///
+    /// * radix 2, 8 and 16 get a 0b, 0o and 0x prefix respectively, digits beyond 9 are upper case.
+    /// * negative values are rendered has a - followed by the magnitude, ie not has two's complement.
+    /// * the width includes the sign and prefix, like the std formatter. A '0' pad char goes
+    ///   after the sign and prefix, any other pad char goes before them.
+    ///
+    /// So IntValue(255).format_int(16, 6, '0') gives 0x00FF.
+    ///
+    pub fn format_int(&self, radix: u32, width: usize, pad: char) -> Result<String, String> {
+        if !(2..=36).contains(&radix) {
+            return Err(format!("Variant:FormatInt:Unsupported radix {}", radix));
+        }
+        let ival = self.get_isize().map_err(|e| format!("Variant:FormatInt:{}", e))?;
+        let mut head = String::new();
+        if ival < 0 {
+            head.push('-');
+        }
+        head.push_str(match radix {
+            2 => "0b",
+            8 => "0o",
+            16 => "0x",
+            _ => "",
+        });
+        let sdigits = int_digits(ival.unsigned_abs(), radix, true);
+        let npad = width.saturating_sub(head.len() + sdigits.len());
+        let spad: String = core::iter::repeat_n(pad, npad).collect();
+        if pad == '0' {
+            Ok(format!("{}{}{}", head, spad, sdigits))
+        } else {
+            Ok(format!("{}{}{}", spad, head, sdigits))
+        }
+    }
+
+    ///
+    /// Render the variant has specified by the given format spec, which follows a subset
+    /// of the std format spec, ie `{:[[fill]align][+][#][0][width][.precision][type]}`
+    ///
+    /// * align is < (left), ^ (center) or > (right), fill is any char (default space).
+    ///   Numbers are right aligned and others left aligned by default.
+    /// * type d, x, X, b, o render the integer value (refer get_isize) in decimal, hex
+    ///   (lower or upper case), binary or octal, while s renders the string value (refer
+    ///   get_string). Without a type, IntValue is rendered has d and others has s.
+    /// * the + flag shows the sign for non negative numbers also, # adds the 0x/0b/0o prefix
+    ///   and 0 pads the number with zeros after the sign and prefix, to the width.
+    /// * precision truncates the string value to that many chars, it is ignored for numbers.
+    ///
+    /// So IntValue(255).format("{:#06x}") gives 0x00ff and StrValue("hello").format("{:*^9.3}") gives ***hel***
+    ///
+    pub fn format(&self, spec: &str) -> Result<String, String> {
+        let fs = FormatSpec::parse(spec)?;
+        let numeric = match fs.ty {
+            Some(ty) => ty != 's',
+            None => matches!(self, Variant::IntValue(_)),
+        };
+        let sval = if numeric {
+            let radix = match fs.ty {
+                Some('x') | Some('X') => 16,
+                Some('b') => 2,
+                Some('o') => 8,
+                _ => 10,
+            };
+            let ival = self.get_isize().map_err(|e| format!("Variant:Format:{}", e))?;
+            let mut head = String::new();
+            if ival < 0 {
+                head.push('-');
+            } else if fs.plus {
+                head.push('+');
+            }
+            if fs.alternate {
+                head.push_str(match radix {
+                    2 => "0b",
+                    8 => "0o",
+                    16 => "0x",
+                    _ => "",
+                });
+            }
+            let sdigits = int_digits(ival.unsigned_abs(), radix, fs.ty == Some('X'));
+            if fs.zero {
+                let npad = fs.width.saturating_sub(head.len() + sdigits.len());
+                return Ok(format!("{}{}{}", head, "0".repeat(npad), sdigits));
+            }
+            head + &sdigits
+        } else {
+            let sval = self.get_string();
+            match fs.precision {
+                Some(p) => sval.chars().take(p).collect(),
+                None => sval,
+            }
+        };
+        let npad = fs.width.saturating_sub(sval.chars().count());
+        let align = fs.align.unwrap_or(if numeric { '>' } else { '<' });
+        let (nleft, nright) = match align {
+            '<' => (0, npad),
+            '^' => (npad/2, npad - npad/2),
+            _ => (npad, 0),
+        };
+        let lpad: String = core::iter::repeat_n(fs.fill, nleft).collect();
+        let rpad: String = core::iter::repeat_n(fs.fill, nright).collect();
+        Ok(format!("{}{}{}", lpad, sval, rpad))
+    }
+
     ///
     /// * returns int values as underlying byte values based vector in the native endianess format
     /// * Returns String as the underlying byte values based vector
     /// * Returns Buf8 data as is (rather a cloned buf)
     /// * XTimeStamp -> milliseconds from UnixEpoch, as the underlying byte values of the int
+    /// * List -> the bytes of its elements concatenated, Map -> the bytes of its values (in key order) concatenated
     ///
     /// TODO:ThinkAgain: Should I return a fixed endian format like network byte order (BigEndian) or little endian
     /// rather than native byte order (If testing between systems having different endianess, it could help)
     pub fn get_bufvu8(&self) -> Vec<u8> {
+        self.get_bufvu8_with_clock(&SystemClock)
+    }
+
+    /// Same has get_bufvu8, but XTimeStamp uses the given clock, rather than the system clock
+    pub fn get_bufvu8_with_clock(&self, clock: &dyn Clock) -> Vec<u8> {
         match self {
             Self::IntValue(ival) => {
                 return ival.to_ne_bytes().to_vec();
@@ -144,14 +364,40 @@ impl Variant {
             Self::BufValue(bval) => {
                 return bval.clone();
             },
+            Self::SharedBuf(sbuf) => {
+                sbuf.to_vec()
+            },
             Self::XTimeStamp => {
-                let ts = time::SystemTime::now().duration_since(time::UNIX_EPOCH).unwrap();
-                let uts = ts.as_millis();
+                let uts = clock.now_ms();
                 return uts.to_ne_bytes().to_vec();
             },
+            Self::ListValue(lval) => {
+                lval.iter().flat_map(|v| v.get_bufvu8_with_clock(clock)).collect()
+            },
+            Self::MapValue(mval) => {
+                mval.values().flat_map(|v| v.get_bufvu8_with_clock(clock)).collect()
+            },
          }
     }
 
+    /// Get the elements of a list variant
+    pub fn get_list(&self) -> Option<&[Variant]> {
+        if let Self::ListValue(lval) = self {
+            return Some(lval);
+        }
+        None
+    }
+
+    /// Get the value of the given key in a map variant
+    pub fn get_mapvalue(&self, key: &str) -> Option<&Variant> {
+        if let Self::MapValue(mval) = self {
+            return mval.get(key);
+        }
+        None
+    }
+
+    /// Get mutable access to the buffer of a BufValue variant.
+    /// NOTE: SharedBuf gives None, has its data is shared with other variants.
     pub fn get_bufvu8_mut(&mut self) -> Option<&mut Vec<u8>> {
         if let Self::BufValue(thebuf) = self {
             return Some(thebuf.as_mut());
@@ -159,9 +405,20 @@ impl Variant {
         return None;
     }
 
+    /// Get the byte value at the given byte offset within the underlying/raw bytes
+    /// of the data stored in the variant, or a error if the index is beyond the data.
+    pub fn try_get_byteelement(&self, index: usize) -> Result<u8, DataUtilsError> {
+        let bval = self.get_bufvu8();
+        match bval.get(index) {
+            Some(aval) => Ok(*aval),
+            None => Err(DataUtilsError::IndexOutOfBounds { index, len: bval.len() }),
+        }
+    }
+
     #[allow(dead_code)]
     /// Get the byte value at the given byte offset within the underlying/raw bytes
     /// of the data stored in the variant.
+    #[deprecated(since = "0.1.2", note = "use try_get_byteelement, which doesnt panic if the index is beyond the data")]
     pub fn get_byteelement(&self, index: usize) -> u8 {
         let bval = self.get_bufvu8();
         let aval = bval[index];
@@ -174,6 +431,9 @@ impl Variant {
     /// String: the offset maps to char offset (and not byte offset)
     ///     should help with multibyte unicode chars which are stored internally.
     /// Buf: the offset maps to byte offset.
+    /// SharedBuf: the offset maps to byte offset, the returned element shares the data.
+    /// List: the offset maps to the element.
+    /// Map: the offset maps to the value, wrt the keys in sorted order.
     pub fn get_arrayelement(&self, index: usize) -> Result<Variant, String> {
         match self {
             Self::IntValue(_ival) => {
@@ -200,6 +460,24 @@ impl Variant {
                 let rval = &bval[index..index+1];
                 return Ok(Variant::BufValue(rval.to_vec()));
             }
+            Self::SharedBuf(sbuf) => {
+                match sbuf.slice(index..index+1) {
+                    Some(rval) => Ok(Variant::SharedBuf(rval)),
+                    None => Err(format!("Variant:GetArrayEle:SharedBuf:Invalid index {}, available length {}", index, sbuf.len())),
+                }
+            }
+            Self::ListValue(lval) => {
+                match lval.get(index) {
+                    Some(v) => Ok(v.clone()),
+                    None => Err(format!("Variant:GetArrayEle:ListValue:Invalid index {}, available length {}", index, lval.len())),
+                }
+            }
+            Self::MapValue(mval) => {
+                match mval.values().nth(index) {
+                    Some(v) => Ok(v.clone()),
+                    None => Err(format!("Variant:GetArrayEle:MapValue:Invalid index {}, available length {}", index, mval.len())),
+                }
+            }
             _ => {
                 let bval = self.get_bufvu8();
                 if index >= bval.len() {
@@ -213,25 +491,617 @@ impl Variant {
 
 }
 
-impl From<&str> for Variant {
+impl Variant {
 
     /// Create a appropriate Variant from the given string value.
     /// The passed string is trimmed, before processing.
     /// * if it starts with $0x, create binary buffer variant
     /// * if the passed value is enclosed in double quotes, create a string variant
     /// * if neither of above, create a integer
-    fn from(sin: &str) -> Self {
+    ///
+    /// If the value is not a valid literal of the corresponding type, a error is returned.
+    pub fn from_literal(sin: &str) -> Result<Variant, DataUtilsError> {
         let sin = sin.trim();
-        if sin.starts_with("$0x") {
-            return Variant::BufValue(hex::vu8_from_hex(&sin[3..]).unwrap())
+        if let Some(shex) = sin.strip_prefix("$0x") {
+            return hex::vu8_from_hex(shex).map(Variant::BufValue).map_err(|e| DataUtilsError::ParseHex { input: sin.to_string(), reason: e });
         }
-        if sin.starts_with('"') && sin.ends_with('"'){
-            return Variant::StrValue(sin[1..sin.len()-1].to_string());
+        if (sin.len() >= 2) && sin.starts_with('"') && sin.ends_with('"') {
+            return Ok(Variant::StrValue(sin[1..sin.len()-1].to_string()));
         }
         if sin == "__TIME__STAMP__" {
-            return Variant::XTimeStamp;
+            return Ok(Variant::XTimeStamp);
+        }
+        integer::try_intvalue::<isize>(sin).map(Variant::IntValue)
+    }
+
+}
+
+/// The result of compiling a literal token, refer to compile_literal
+#[derive(Debug, Clone)]
+pub enum CompiledLiteral {
+    /// The token was a literal value
+    Value(Variant),
+    /// The token was a variable name, which the caller needs to resolve
+    Identifier(String),
+}
+
+/// Check if the given token is a valid variable name, ie a letter or _ followed by
+/// letters, digits or _, which is not a __ special.
+fn is_identifier(token: &str) -> bool {
+    let mut chars = token.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => (),
+        _ => return false,
+    }
+    if token.len() > 4 && token.starts_with("__") && token.ends_with("__") {
+        return false;
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Compile the given token into a Variant or a Identifier, using the literal grammar
+///
+/// * a int literal (decimal or 0x prefixed hex), a "quoted string" or a $0x hex buffer literal,
+///   refer to Variant::from_literal
+/// * __TIME__STAMP__ special, any other __NAME__ is a error
+/// * a variable name (letter or _ followed by letters, digits or _) is returned has a Identifier
+///
+/// The literal value is converted to the expected type, if it is Integer, String or Buffer,
+/// using get_isize, get_string or get_bufvu8 respectively. Unknown keeps the value as is.
+/// Special literals and Identifiers are returned as is, as they can only be resolved at runtime.
+pub fn compile_literal(token: &str, expected: VDataType) -> Result<CompiledLiteral, DataUtilsError> {
+    let token = token.trim();
+    if is_identifier(token) {
+        return Ok(CompiledLiteral::Identifier(token.to_string()));
+    }
+    if token.len() > 4 && token.starts_with("__") && token.ends_with("__") && token != "__TIME__STAMP__" {
+        return Err(DataUtilsError::Invalid(format!("Variant:CompileLiteral:Unknown special {}", token)));
+    }
+    let v = Variant::from_literal(token)?;
+    if matches!(v.get_type(), VDataType::Special) {
+        return Ok(CompiledLiteral::Value(v));
+    }
+    let v = match expected {
+        VDataType::Unknown => v,
+        VDataType::Integer => Variant::IntValue(v.get_isize().map_err(|e| DataUtilsError::Invalid(format!("Variant:CompileLiteral:{}:{}", token, e)))?),
+        VDataType::String => Variant::StrValue(v.get_string()),
+        VDataType::Buffer => Variant::BufValue(v.get_bufvu8()),
+        _ => return Err(DataUtilsError::Invalid(format!("Variant:CompileLiteral:{}:Cant compile to {:?}", token, expected))),
+    };
+    Ok(CompiledLiteral::Value(v))
+}
+
+/// The digits of the given value in the given radix (2 to 36)
+fn int_digits(mut uval: usize, radix: u32, upper: bool) -> String {
+    let mut vdigits = Vec::new();
+    loop {
+        let digit = char::from_digit((uval % radix as usize) as u32, radix).unwrap_or('?');
+        vdigits.push(if upper { digit.to_ascii_uppercase() } else { digit });
+        uval /= radix as usize;
+        if uval == 0 {
+            break;
+        }
+    }
+    vdigits.iter().rev().collect()
+}
+
+/// The parsed format spec, refer to Variant::format
+#[derive(Debug, Clone, PartialEq)]
+struct FormatSpec {
+    fill: char,
+    align: Option<char>,
+    plus: bool,
+    alternate: bool,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+    ty: Option<char>,
+}
+
+impl FormatSpec {
+
+    fn parse(spec: &str) -> Result<FormatSpec, String> {
+        let err = || format!("Variant:Format:Invalid spec {}", spec);
+        let inner = spec.strip_prefix('{').and_then(|s| s.strip_suffix('}')).ok_or_else(err)?;
+        let mut fs = FormatSpec { fill: ' ', align: None, plus: false, alternate: false, zero: false, width: 0, precision: None, ty: None };
+        let rem = match inner.strip_prefix(':') {
+            Some(rem) => rem,
+            None if inner.is_empty() => return Ok(fs),
+            None => return Err(err()),
+        };
+        let vchars: Vec<char> = rem.chars().collect();
+        let mut i = 0;
+        if vchars.len() >= 2 && "<^>".contains(vchars[1]) {
+            fs.fill = vchars[0];
+            fs.align = Some(vchars[1]);
+            i = 2;
+        } else if !vchars.is_empty() && "<^>".contains(vchars[0]) {
+            fs.align = Some(vchars[0]);
+            i = 1;
+        }
+        let flag = |i: &mut usize, c: char| {
+            let found = vchars.get(*i) == Some(&c);
+            if found {
+                *i += 1;
+            }
+            found
+        };
+        fs.plus = flag(&mut i, '+');
+        fs.alternate = flag(&mut i, '#');
+        fs.zero = flag(&mut i, '0');
+        let number = |i: &mut usize| {
+            let start = *i;
+            while *i < vchars.len() && vchars[*i].is_ascii_digit() {
+                *i += 1;
+            }
+            vchars[start..*i].iter().collect::<String>().parse::<usize>().ok()
+        };
+        fs.width = number(&mut i).unwrap_or(0);
+        if vchars.get(i) == Some(&'.') {
+            i += 1;
+            fs.precision = Some(number(&mut i).ok_or_else(err)?);
+        }
+        if let Some(c) = vchars.get(i) {
+            if !"dxXbos".contains(*c) {
+                return Err(err());
+            }
+            fs.ty = Some(*c);
+            i += 1;
+        }
+        if i != vchars.len() {
+            return Err(err());
+        }
+        Ok(fs)
+    }
+
+}
+
+/// The tokens of a expression, refer to eval_expr
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ExprTok<'a> {
+    /// A literal or a variable name
+    Operand(&'a str),
+    Op(&'static str),
+    LParen,
+    RParen,
+}
+
+/// Split the given expression into its tokens
+fn expr_tokens(expr: &str) -> Result<Vec<ExprTok<'_>>, DataUtilsError> {
+    const OPS: [&str; 11] = ["==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "%"];
+    let bexpr = expr.as_bytes();
+    let mut vtoks = Vec::new();
+    let mut i = 0;
+    while i < bexpr.len() {
+        let c = bexpr[i];
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c == b'(' || c == b')' {
+            vtoks.push(if c == b'(' { ExprTok::LParen } else { ExprTok::RParen });
+            i += 1;
+            continue;
+        }
+        if let Some(op) = OPS.iter().find(|op| expr[i..].starts_with(**op)) {
+            vtoks.push(ExprTok::Op(op));
+            i += op.len();
+            continue;
+        }
+        let si = i;
+        if c == b'"' {
+            match expr[i+1..].find('"') {
+                Some(ei) => i += ei + 2,
+                None => return Err(DataUtilsError::Invalid(format!("Variant:EvalExpr:Unterminated string at {}", si))),
+            }
+        } else if c == b'$' || c.is_ascii_alphanumeric() || c == b'_' {
+            i += 1;
+            while i < bexpr.len() && (bexpr[i].is_ascii_alphanumeric() || bexpr[i] == b'_') {
+                i += 1;
+            }
+        } else {
+            return Err(DataUtilsError::Invalid(format!("Variant:EvalExpr:Unexpected char at {}", si)));
+        }
+        vtoks.push(ExprTok::Operand(&expr[si..i]));
+    }
+    Ok(vtoks)
+}
+
+/// The max nesting of unary - and ( ) allowed in a expression, so that the recursion
+/// doesnt overflow the stack.
+const EXPR_MAXDEPTH: usize = 256;
+
+/// A recursive descent parser + evaluator over the tokens of a expression
+struct ExprEval<'a, F: Fn(&str) -> Option<Variant>> {
+    vtoks: Vec<ExprTok<'a>>,
+    pos: usize,
+    depth: usize,
+    resolver: F,
+}
+
+impl<'a, F: Fn(&str) -> Option<Variant>> ExprEval<'a, F> {
+
+    fn peek(&self) -> Option<ExprTok<'a>> {
+        self.vtoks.get(self.pos).copied()
+    }
+
+    /// If the next token is one of the given operators, consume and return it
+    fn next_op(&mut self, ops: &[&str]) -> Option<&'static str> {
+        if let Some(ExprTok::Op(op)) = self.peek() {
+            if ops.contains(&op) {
+                self.pos += 1;
+                return Some(op);
+            }
+        }
+        None
+    }
+
+    /// Track going one level deeper, erroring out if too deep
+    fn enter(&mut self) -> Result<(), DataUtilsError> {
+        self.depth += 1;
+        if self.depth > EXPR_MAXDEPTH {
+            return Err(DataUtilsError::Invalid(format!("Variant:EvalExpr:Nesting deeper than {}", EXPR_MAXDEPTH)));
+        }
+        Ok(())
+    }
+
+    fn comparison(&mut self) -> Result<Variant, DataUtilsError> {
+        let lhs = self.additive()?;
+        match self.next_op(&["==", "!=", "<=", ">=", "<", ">"]) {
+            Some(op) => {
+                let rhs = self.additive()?;
+                expr_compare(op, &lhs, &rhs)
+            }
+            None => Ok(lhs),
+        }
+    }
+
+    fn additive(&mut self) -> Result<Variant, DataUtilsError> {
+        let mut lhs = self.multiplicative()?;
+        while let Some(op) = self.next_op(&["+", "-"]) {
+            let rhs = self.multiplicative()?;
+            lhs = expr_arith(op, &lhs, &rhs)?;
+        }
+        Ok(lhs)
+    }
+
+    fn multiplicative(&mut self) -> Result<Variant, DataUtilsError> {
+        let mut lhs = self.unary()?;
+        while let Some(op) = self.next_op(&["*", "/", "%"]) {
+            let rhs = self.unary()?;
+            lhs = expr_arith(op, &lhs, &rhs)?;
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Variant, DataUtilsError> {
+        if self.next_op(&["-"]).is_some() {
+            self.enter()?;
+            let v = self.unary()?;
+            self.depth -= 1;
+            return expr_arith("-", &Variant::IntValue(0), &v);
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Variant, DataUtilsError> {
+        let tok = self.peek();
+        self.pos += 1;
+        match tok {
+            Some(ExprTok::LParen) => {
+                self.enter()?;
+                let v = self.comparison()?;
+                if self.peek() != Some(ExprTok::RParen) {
+                    return Err(DataUtilsError::Invalid("Variant:EvalExpr:Missing )".to_string()));
+                }
+                self.pos += 1;
+                self.depth -= 1;
+                Ok(v)
+            }
+            Some(ExprTok::Operand(token)) => {
+                match compile_literal(token, VDataType::Unknown)? {
+                    CompiledLiteral::Value(v) => Ok(v),
+                    CompiledLiteral::Identifier(name) => match (self.resolver)(&name) {
+                        Some(v) => Ok(v),
+                        None => Err(DataUtilsError::Invalid(format!("Variant:EvalExpr:Unknown name [{}]", name))),
+                    },
+                }
+            }
+            Some(tok) => Err(DataUtilsError::Invalid(format!("Variant:EvalExpr:Unexpected {:?}", tok))),
+            None => Err(DataUtilsError::Invalid("Variant:EvalExpr:Unexpected end of expression".to_string())),
+        }
+    }
+
+}
+
+/// Check if the variant is a integer (or a timestamp, which maps to a integer)
+fn expr_isint(v: &Variant) -> bool {
+    matches!(v, Variant::IntValue(_) | Variant::XTimeStamp)
+}
+
+fn expr_int(v: &Variant) -> Result<isize, DataUtilsError> {
+    v.get_isize().map_err(|e| DataUtilsError::Invalid(format!("Variant:EvalExpr:{}", e)))
+}
+
+fn expr_arith(op: &str, lhs: &Variant, rhs: &Variant) -> Result<Variant, DataUtilsError> {
+    if op == "+" && (matches!(lhs, Variant::StrValue(_)) || matches!(rhs, Variant::StrValue(_))) {
+        return Ok(Variant::StrValue(format!("{}{}", lhs.get_string(), rhs.get_string())));
+    }
+    let a = expr_int(lhs)?;
+    let b = expr_int(rhs)?;
+    let r = match op {
+        "+" => a.checked_add(b),
+        "-" => a.checked_sub(b),
+        "*" => a.checked_mul(b),
+        "/" => a.checked_div(b),
+        _ => a.checked_rem(b),
+    };
+    match r {
+        Some(r) => Ok(Variant::IntValue(r)),
+        None => Err(DataUtilsError::Invalid(format!("Variant:EvalExpr:{} {} {}:Overflow or division by zero", a, op, b))),
+    }
+}
+
+fn expr_compare(op: &str, lhs: &Variant, rhs: &Variant) -> Result<Variant, DataUtilsError> {
+    let ord = if expr_isint(lhs) && expr_isint(rhs) {
+        expr_int(lhs)?.cmp(&expr_int(rhs)?)
+    } else if matches!(lhs, Variant::StrValue(_)) || matches!(rhs, Variant::StrValue(_)) {
+        lhs.get_string().cmp(&rhs.get_string())
+    } else {
+        lhs.get_bufvu8().cmp(&rhs.get_bufvu8())
+    };
+    let r = match op {
+        "==" => ord.is_eq(),
+        "!=" => ord.is_ne(),
+        "<=" => ord.is_le(),
+        ">=" => ord.is_ge(),
+        "<" => ord.is_lt(),
+        _ => ord.is_gt(),
+    };
+    Ok(Variant::IntValue(r as isize))
+}
+
+/// Evaluate a simple expression like "len + 2 * 8" over Variant values.
+///
+/// * operands are literals (refer to compile_literal) or variable names, which are
+///   resolved using the passed resolver.
+/// * supports + - * / % (with the usual precedence), unary -, ( ) and a single
+///   comparison (== != < <= > >=) at the top, which gives 1 (true) or 0 (false).
+/// * arithmetic is done on isize, with overflow and division by zero being errors.
+///   Non int operands are converted using get_isize, except for + where if either
+///   side is a string, the get_string of both sides are concatenated.
+/// * comparisons are numeric if both sides are ints, else wrt the strings if either
+///   side is a string, else wrt the bytes.
+/// * unary - and ( ) can be nested upto EXPR_MAXDEPTH (256) levels deep.
+///
+pub fn eval_expr(expr: &str, resolver: impl Fn(&str) -> Option<Variant>) -> Result<Variant, DataUtilsError> {
+    let vtoks = expr_tokens(expr)?;
+    let mut ee = ExprEval { vtoks, pos: 0, depth: 0, resolver };
+    let v = ee.comparison()?;
+    if let Some(tok) = ee.peek() {
+        return Err(DataUtilsError::Invalid(format!("Variant:EvalExpr:Unexpected {:?} after expression", tok)));
+    }
+    Ok(v)
+}
+
+/// Iterate over the records in a stream of delimited text, parsing each record into a Variant.
+/// Refer to parse_stream.
+#[cfg(feature = "std")]
+pub struct VariantStream<R: BufRead> {
+    records: std::io::Split<R>,
+    recnum: usize,
+}
+
+#[cfg(feature = "std")]
+impl<R: BufRead> Iterator for VariantStream<R> {
+    type Item = Result<Variant, DataUtilsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let rec = match self.records.next()? {
+                Ok(rec) => rec,
+                Err(e) => return Some(Err(DataUtilsError::Invalid(format!("Variant:ParseStream:Record {}:Read failed:{}", self.recnum, e)))),
+            };
+            self.recnum += 1;
+            let srec = match core::str::from_utf8(&rec) {
+                Ok(srec) => srec,
+                Err(e) => return Some(Err(DataUtilsError::Invalid(format!("Variant:ParseStream:Record {}:Not utf8:{}", self.recnum, e)))),
+            };
+            if srec.trim().is_empty() {
+                continue;
+            }
+            return Some(Variant::from_literal(srec));
+        }
+    }
+}
+
+/// Parse the records in the given reader, seperated by the given delimiter (like b'\n'),
+/// one at a time, into Variants using the literal grammar of Variant::from_literal.
+///
+/// Records which are empty or only whitespace are skipped. A record which is not a valid
+/// literal gives a error, but the iteration can continue with the following records.
+/// The reader is consumed has the iteration progresses, so whole files need not be buffered.
+#[cfg(feature = "std")]
+pub fn parse_stream<R: BufRead>(reader: R, delimiter: u8) -> VariantStream<R> {
+    VariantStream { records: reader.split(delimiter), recnum: 0 }
+}
+
+/// A variant whose string/buffer data is borrowed from a VariantArena
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VariantRef<'a> {
+    IntValue(isize),
+    StrValue(&'a str),
+    BufValue(&'a [u8]),
+    XTimeStamp,
+}
+
+impl VariantRef<'_> {
+
+    pub fn get_type(&self) -> VDataType {
+        match self {
+            VariantRef::IntValue(_) => VDataType::Integer,
+            VariantRef::StrValue(_) => VDataType::String,
+            VariantRef::BufValue(_) => VDataType::Buffer,
+            VariantRef::XTimeStamp => VDataType::Special,
+        }
+    }
+
+    /// Get a owned Variant, which copies the string/buffer data out of the arena
+    pub fn to_variant(&self) -> Variant {
+        match self {
+            VariantRef::IntValue(ival) => Variant::IntValue(*ival),
+            VariantRef::StrValue(sval) => Variant::StrValue(sval.to_string()),
+            VariantRef::BufValue(bval) => Variant::BufValue(bval.to_vec()),
+            VariantRef::XTimeStamp => Variant::XTimeStamp,
+        }
+    }
+
+}
+
+/// Where the data of a variant in the arena is, the string/buffer data is a start..end
+/// within the corresponding shared storage.
+#[derive(Debug, Clone, Copy)]
+enum ArenaSlot {
+    Int(isize),
+    Str(usize, usize),
+    Buf(usize, usize),
+    TimeStamp,
+}
+
+///
+/// Maintain a large number of (non nested) variants, with the string and buffer data of all
+/// of them stored in a shared string and a shared byte buffer respectively, rather than in
+/// a seperate allocation per variant. The variants are identified by their index, in the
+/// order they were added, and are handed out has VariantRef which borrow from the arena.
+///
+/// clear allows the arena (and its already allocated storage) to be reused for the next batch.
+///
+#[derive(Debug, Clone, Default)]
+pub struct VariantArena {
+    slots: Vec<ArenaSlot>,
+    strs: String,
+    bufs: Vec<u8>,
+}
+
+impl VariantArena {
+
+    pub fn new() -> VariantArena {
+        VariantArena::default()
+    }
+
+    /// Preallocate space for the given number of variants and bytes of string/buffer data (each)
+    pub fn with_capacity(nvariants: usize, nbytes: usize) -> VariantArena {
+        VariantArena { slots: Vec::with_capacity(nvariants), strs: String::with_capacity(nbytes), bufs: Vec::with_capacity(nbytes) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// The number of bytes of string and buffer data in the arena
+    pub fn bytes_used(&self) -> usize {
+        self.strs.len() + self.bufs.len()
+    }
+
+    /// Drop all the variants, while keeping the allocated storage for reuse
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.strs.clear();
+        self.bufs.clear();
+    }
+
+    fn add(&mut self, slot: ArenaSlot) -> usize {
+        self.slots.push(slot);
+        self.slots.len() - 1
+    }
+
+    pub fn push_int(&mut self, ival: isize) -> usize {
+        self.add(ArenaSlot::Int(ival))
+    }
+
+    pub fn push_str(&mut self, sval: &str) -> usize {
+        let start = self.strs.len();
+        self.strs.push_str(sval);
+        self.add(ArenaSlot::Str(start, self.strs.len()))
+    }
+
+    pub fn push_buf(&mut self, bval: &[u8]) -> usize {
+        let start = self.bufs.len();
+        self.bufs.extend_from_slice(bval);
+        self.add(ArenaSlot::Buf(start, self.bufs.len()))
+    }
+
+    /// Add the variant corresponding to the given literal, as parsed by Variant::from_literal,
+    /// returning its index.
+    pub fn push_literal(&mut self, sin: &str) -> Result<usize, DataUtilsError> {
+        match Variant::from_literal(sin)? {
+            Variant::IntValue(ival) => Ok(self.push_int(ival)),
+            Variant::StrValue(sval) => Ok(self.push_str(&sval)),
+            Variant::BufValue(bval) => Ok(self.push_buf(&bval)),
+            Variant::XTimeStamp => Ok(self.add(ArenaSlot::TimeStamp)),
+            v => Err(DataUtilsError::Invalid(format!("Variant:Arena:PushLiteral:Unsupported {:?}", v.get_type()))),
+        }
+    }
+
+    ///
+    /// Parse each line in the given text has a literal (refer push_literal), skipping lines
+    /// which are empty or only whitespace, returning the range of indexes of the added variants.
+    ///
+    /// If any line is not a valid literal, a error mentioning the line number (starting from 1)
+    /// is returned, and the arena is left has it was before the call.
+    ///
+    pub fn parse_lines(&mut self, text: &str) -> Result<core::ops::Range<usize>, DataUtilsError> {
+        let (nslots, nstrs, nbufs) = (self.slots.len(), self.strs.len(), self.bufs.len());
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Err(e) = self.push_literal(line) {
+                self.slots.truncate(nslots);
+                self.strs.truncate(nstrs);
+                self.bufs.truncate(nbufs);
+                return Err(DataUtilsError::Invalid(format!("Variant:Arena:ParseLines:Line {}:{}", i+1, e)));
+            }
+        }
+        Ok(nslots..self.slots.len())
+    }
+
+    /// Get the variant at the given index
+    pub fn get(&self, index: usize) -> Option<VariantRef<'_>> {
+        let slot = self.slots.get(index)?;
+        Some(self.slot_ref(slot))
+    }
+
+    fn slot_ref(&self, slot: &ArenaSlot) -> VariantRef<'_> {
+        match *slot {
+            ArenaSlot::Int(ival) => VariantRef::IntValue(ival),
+            ArenaSlot::Str(start, end) => VariantRef::StrValue(&self.strs[start..end]),
+            ArenaSlot::Buf(start, end) => VariantRef::BufValue(&self.bufs[start..end]),
+            ArenaSlot::TimeStamp => VariantRef::XTimeStamp,
+        }
+    }
+
+    /// Iterate over the variants in the order they were added
+    pub fn iter(&self) -> impl Iterator<Item = VariantRef<'_>> + '_ {
+        self.slots.iter().map(|slot| self.slot_ref(slot))
+    }
+
+}
+
+impl From<&str> for Variant {
+
+    /// Create a appropriate Variant from the given string value, refer to from_literal.
+    ///
+    /// NOTE: Panics if the value is not a valid literal, prefer Variant::from_literal
+    fn from(sin: &str) -> Self {
+        match Variant::from_literal(sin) {
+            Ok(v) => v,
+            Err(e) => panic!("ERRR:DU:Variant:From:{}", e),
         }
-        return Variant::IntValue(integer::intvalue(sin).unwrap());
     }
 
 }
@@ -239,12 +1109,13 @@ impl From<&str> for Variant {
 impl Display for Variant {
 
     /// Allow a variant to be shown/output to the user
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Variant::IntValue(ival) => f.write_str(&ival.to_string()),
             Variant::StrValue(sval) => f.write_str(sval),
             Variant::BufValue(bval) => f.write_str(&hex::hex_from_vu8(bval)),
-            Variant::XTimeStamp => f.write_str(&self.get_string()),
+            Variant::SharedBuf(sbuf) => f.write_str(&hex::hex_from_vu8(sbuf.as_slice())),
+            Variant::XTimeStamp | Variant::ListValue(_) | Variant::MapValue(_) => f.write_str(&self.get_string()),
         }
     }
 
@@ -265,8 +1136,133 @@ impl Variant {
     }
 
     /// Set a variant to a binary/byte buffer value
-    pub fn set_bufvu8(&mut self, bval: &Vec<u8>) {
-        *self = Variant::BufValue(bval.clone())
+    pub fn set_bufvu8(&mut self, bval: &[u8]) {
+        *self = Variant::BufValue(bval.to_vec())
+    }
+
+}
+
+impl From<SharedBytes> for Variant {
+    fn from(sbuf: SharedBytes) -> Self {
+        Variant::SharedBuf(sbuf)
+    }
+}
+
+impl From<Vec<Variant>> for Variant {
+
+    /// Create a list variant from the given variants
+    fn from(vlist: Vec<Variant>) -> Self {
+        Variant::ListValue(vlist)
+    }
+
+}
+
+
+/// Helps create list and map (ie structured) variants in a fluent manner, like
+///
+/// VariantBuilder::map().str("name", "x").int("count", 3).list("vals", vec![...]).build()
+///
+/// The map and list builders can be nested, by passing them to put/push.
+pub struct VariantBuilder;
+
+impl VariantBuilder {
+
+    /// Start building a map variant
+    pub fn map() -> MapBuilder {
+        MapBuilder { mval: BTreeMap::new() }
+    }
+
+    /// Start building a list variant
+    pub fn list() -> ListBuilder {
+        ListBuilder { lval: Vec::new() }
     }
 
 }
+
+/// Builds a map variant, refer to VariantBuilder.
+/// Setting a key which already exists replaces its value.
+#[derive(Debug, Clone)]
+pub struct MapBuilder {
+    mval: BTreeMap<String, Variant>,
+}
+
+impl MapBuilder {
+
+    /// Set the given key to the given variant (or builder)
+    pub fn put(mut self, key: &str, val: impl Into<Variant>) -> Self {
+        self.mval.insert(key.to_string(), val.into());
+        self
+    }
+
+    /// Set the given key to a string value
+    pub fn str(self, key: &str, sval: &str) -> Self {
+        self.put(key, Variant::StrValue(sval.to_string()))
+    }
+
+    /// Set the given key to a int value
+    pub fn int(self, key: &str, ival: isize) -> Self {
+        self.put(key, Variant::IntValue(ival))
+    }
+
+    /// Set the given key to a binary/byte buffer value
+    pub fn buf(self, key: &str, bval: &[u8]) -> Self {
+        self.put(key, Variant::BufValue(bval.to_vec()))
+    }
+
+    /// Set the given key to a list of the given variants
+    pub fn list(self, key: &str, vlist: Vec<Variant>) -> Self {
+        self.put(key, Variant::ListValue(vlist))
+    }
+
+    pub fn build(self) -> Variant {
+        Variant::MapValue(self.mval)
+    }
+
+}
+
+/// Builds a list variant, refer to VariantBuilder.
+#[derive(Debug, Clone)]
+pub struct ListBuilder {
+    lval: Vec<Variant>,
+}
+
+impl ListBuilder {
+
+    /// Append the given variant (or builder)
+    pub fn push(mut self, val: impl Into<Variant>) -> Self {
+        self.lval.push(val.into());
+        self
+    }
+
+    /// Append a string value
+    pub fn str(self, sval: &str) -> Self {
+        self.push(Variant::StrValue(sval.to_string()))
+    }
+
+    /// Append a int value
+    pub fn int(self, ival: isize) -> Self {
+        self.push(Variant::IntValue(ival))
+    }
+
+    /// Append a binary/byte buffer value
+    pub fn buf(self, bval: &[u8]) -> Self {
+        self.push(Variant::BufValue(bval.to_vec()))
+    }
+
+    pub fn build(self) -> Variant {
+        Variant::ListValue(self.lval)
+    }
+
+}
+
+impl From<MapBuilder> for Variant {
+    fn from(mb: MapBuilder) -> Self {
+        mb.build()
+    }
+}
+
+impl From<ListBuilder> for Variant {
+    fn from(lb: ListBuilder) -> Self {
+        lb.build()
+    }
+}